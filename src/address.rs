@@ -0,0 +1,118 @@
+//! Unique base-62 wallet address generation
+
+use std::collections::HashMap;
+use std::num::NonZeroU64;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{BlockchainError, Result};
+
+// Define the character set and constants for unique wallet address generation
+const CHARSET: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const N: u64 = 62; // Size of the alphabet
+const CODE_LEN: usize = 7; // Length of each code
+const MODULUS: u64 = 3_521_614_606_208; // 62^7 = 3,521,614,606,208
+
+/// Convert a number to a 7-character base-62 string
+/// 
+/// This function takes a u64 number and converts it to a base-62 string
+/// representation using the defined character set.
+pub fn to_code(x: u64) -> String {
+    if x == 0 {
+        return "0".repeat(CODE_LEN);
+    }
+    
+    let mut digits = Vec::with_capacity(CODE_LEN);
+    let mut num = x;
+    
+    while num > 0 {
+        let idx = (num % N) as usize;
+        digits.push(CHARSET[idx] as char);
+        num /= N;
+    }
+    
+    // Pad with leading zeros if necessary
+    while digits.len() < CODE_LEN {
+        digits.push('0');
+    }
+    
+    digits.reverse();
+    digits.into_iter().collect()
+}
+
+/// Struct to hold the generator state for unique wallet addresses
+#[derive(Clone, Serialize, Deserialize)]
+pub struct UniqueCodeGenerator {
+    counter: u64,
+    a: NonZeroU64, // Multiplier, coprime with MODULUS
+    b: u64,        // Offset
+    used_codes: HashMap<String, bool>, // Track used codes to prevent collisions
+}
+
+impl UniqueCodeGenerator {
+    /// Initialize with a permutation of the address space drawn from `rng`
+    ///
+    /// The same seeded generator gives the same sequence of addresses.
+    pub fn new(rng: &mut impl Rng) -> Result<Self> {
+        // Draw 'a', ensuring it's coprime with 62^7 (not divisible by 2 or 31)
+        let a = loop {
+            // Generate fresh random bytes for each attempt
+            let mut candidate_bytes = [0u8; 8];
+            rng.fill(&mut candidate_bytes);
+            
+            let candidate = u64::from_be_bytes(candidate_bytes);
+            
+            // Ensure candidate is not 0 and is coprime with MODULUS
+            // We need to check that gcd(candidate, MODULUS) = 1
+            // Since MODULUS = 62^7 and 62 = 2 * 31, we check that candidate is not divisible by 2 or 31
+            if candidate > 0 && candidate % 2 != 0 && candidate % 31 != 0 {
+                break candidate;
+            }
+            // No sleep needed, just continue the loop with new random values
+        };
+        
+        let a = NonZeroU64::new(a).ok_or_else(|| {
+            BlockchainError::System("Failed to generate valid multiplier for address generator".to_string())
+        })?;
+
+        // Generate a separate random value for 'b'
+        let mut b_bytes = [0u8; 8];
+        rng.fill(&mut b_bytes);
+        let b = u64::from_be_bytes(b_bytes) % MODULUS;
+
+        Ok(UniqueCodeGenerator { 
+            counter: 0, 
+            a, 
+            b,
+            used_codes: HashMap::new(),
+        })
+    }
+
+    /// Generate the next unique code
+    pub fn generate(&mut self) -> Result<String> {
+        // Try up to 10 times to generate a unique code
+        for _ in 0..10 {
+            // Compute x = (a * counter + b) mod 62^7 using u128 to avoid overflow
+            let x = (self.a.get() as u128 * self.counter as u128 + self.b as u128) % MODULUS as u128;
+            let x = x as u64;
+            self.counter = self.counter.wrapping_add(1); // Increment counter
+            
+            let code = to_code(x);
+            
+            // Check if this code has been used before
+            if !self.used_codes.contains_key(&code) {
+                self.used_codes.insert(code.clone(), true);
+                return Ok(code);
+            }
+        }
+        
+        // If we couldn't generate a unique code after several attempts
+        Err(BlockchainError::System("Failed to generate unique wallet address".to_string()))
+    }
+    
+    /// Reserve a specific code to prevent it from being generated
+    pub fn reserve_code(&mut self, code: &str) {
+        self.used_codes.insert(code.to_string(), true);
+    }
+}
@@ -0,0 +1,457 @@
+//! Trading strategies and the swap agents that drive the simulation
+
+use std::collections::HashMap;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::amm::{AmmPool, BASIS_POINTS};
+use crate::amount::{Amount, BASE_UNITS_PER_TOKEN};
+use crate::error::{BlockchainError, Result};
+use crate::transaction::{create_swap_transaction, Transaction};
+use crate::wallet::Wallet;
+use crate::SYSTEM_WALLET_ADDRESS;
+
+// Swaps accept up to 1% (100 basis points) less than the output quoted when they are prepared
+const SWAP_SLIPPAGE_TOLERANCE_BPS: u32 = 100;
+
+// Balances and trades below 0.000001 are not worth trading
+const MIN_TRADE_AMOUNT: Amount = Amount::from_base_units(1_000);
+
+// Swaps must be included in a block within this many seconds of being prepared
+const SWAP_DEADLINE_SECS: u64 = 60;
+
+// Network fees offered with swaps, in ZUX base units (0.00001 to 0.001 ZUX)
+const SWAP_FEE_RANGE: std::ops::Range<u128> = 10_000..1_000_000;
+
+// Whales outbid everyone else for a place in the next block
+const WHALE_FEE_MULTIPLIER: u128 = 10;
+
+/// Random network fee for a swap; whales pay more to be included first
+fn choose_fee(rng: &mut impl Rng, whale: bool) -> Amount {
+    let fee = Amount::from_base_units(rng.gen_range(SWAP_FEE_RANGE));
+    if whale {
+        Amount::from_base_units(fee.base_units() * WHALE_FEE_MULTIPLIER)
+    } else {
+        fee
+    }
+}
+
+/// Share of the trading wallets that become whales
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AgentMix {
+    pub whale_probability: f64,      // Chance a wallet makes massive trades
+    pub mega_whale_probability: f64, // Chance a wallet can move the market on its own
+}
+
+impl Default for AgentMix {
+    fn default() -> Self {
+        AgentMix {
+            whale_probability: 0.10, // Up from 5%
+            mega_whale_probability: 0.01,
+        }
+    }
+}
+
+/// Wallet structure to hold wallet data in memory with multiple currencies
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradingStrategy {
+    pub price_history: Vec<f64>,    // Recent price history for analysis
+    pub last_trade_time: u64,       // Timestamp of last trade
+    pub whale_mode: bool,           // Some wallets are "whales" that make massive trades
+    pub mega_whale_mode: bool,      // Ultra-large whales that can move markets dramatically
+    pub fomo_threshold: f64,        // Price increase that triggers FOMO buying
+    pub panic_threshold: f64,       // Price decrease that triggers panic selling
+    pub manipulation_intent: i8,    // -1: bear, 0: neutral, 1: bull (for market manipulation)
+}
+
+impl TradingStrategy {
+    /// Strategy with traits drawn from `rng`, starting from `initial_price`
+    pub fn new(initial_price: f64, rng: &mut impl Rng) -> Self {
+        Self::with_mix(initial_price, &AgentMix::default(), rng)
+    }
+    
+    /// Strategy with traits drawn from `rng` and whale odds from `mix`, starting from `initial_price`
+    pub fn with_mix(initial_price: f64, mix: &AgentMix, rng: &mut impl Rng) -> Self {
+        // Randomly assign whale status
+        let whale_mode = rng.gen_bool(mix.whale_probability);
+        
+        // Mega whales - can move markets dramatically
+        let mega_whale_mode = rng.gen_bool(mix.mega_whale_probability);
+        
+        // Random FOMO and panic thresholds (even more sensitive: 0.5-3%)
+        let fomo_threshold = rng.gen_range(0.005..0.03); // 0.5-3% price increase triggers FOMO
+        let panic_threshold = rng.gen_range(0.005..0.03); // 0.5-3% price decrease triggers panic
+        
+        // Market manipulation intent (-1: bear, 0: neutral, 1: bull)
+        // This determines if the wallet tries to manipulate price direction
+        let manipulation_intent = if rng.gen_bool(0.30) {
+            // 30% chance to have manipulation intent
+            if rng.gen_bool(0.5) { 1 } else { -1 }
+        } else {
+            0 // neutral
+        };
+        
+        TradingStrategy {
+            price_history: vec![initial_price],
+            last_trade_time: 0,
+            whale_mode,
+            mega_whale_mode,
+            fomo_threshold,
+            panic_threshold,
+            manipulation_intent,
+        }
+    }
+    
+    pub fn update_price_history(&mut self, current_price: f64) {
+        self.price_history.push(current_price);
+        
+        // Keep only the last 3 price points - extremely short-term memory
+        if self.price_history.len() > 3 {
+            self.price_history.remove(0);
+        }
+    }
+    
+    pub fn decide_action(&mut self, current_price: f64, _current_time: u64, wallet_zux: f64, wallet_usdz: f64,
+                         rng: &mut impl Rng) -> (TradeAction, f64) {
+        // Update price history
+        self.update_price_history(current_price);
+        
+        // 99% chance to make a trade on every opportunity (hyper-active trading)
+        if rng.gen_bool(0.99) {
+            // Get the previous price if available
+            let previous_price = if self.price_history.len() > 1 {
+                self.price_history[self.price_history.len() - 2]
+            } else {
+                current_price // Use current price if no history
+            };
+            
+            // Calculate price change percentage
+            let price_change_pct = (current_price - previous_price) / previous_price;
+            
+            // Mega whale market manipulation (these can move markets dramatically)
+            if self.mega_whale_mode && rng.gen_bool(0.8) {
+                // 80% chance for mega whales to act
+                match self.manipulation_intent {
+                    // Bullish manipulation
+                    1 if wallet_usdz > 0.0 => {
+                        // Buy with 95-100% of USDZ balance to pump price
+                        let position_size = wallet_usdz * rng.gen_range(0.95..1.0);
+                        return (TradeAction::Buy, position_size);
+                    },
+                    // Bearish manipulation
+                    -1 if wallet_zux > 0.0 => {
+                        // Sell with 95-100% of ZUX balance to dump price
+                        let position_size = wallet_zux * rng.gen_range(0.95..1.0);
+                        return (TradeAction::Sell, position_size);
+                    },
+                    _ => {} // Neutral, continue with normal logic
+                }
+            }
+            
+            // FOMO buying - buy more aggressively when price is rising
+            if price_change_pct > self.fomo_threshold {
+                // FOMO buy with 90% chance when price is rising (up from 80%)
+                if rng.gen_bool(0.9) && wallet_usdz > 0.0 {
+                    // Determine position size - extreme FOMO uses 90-100% of balance
+                    let position_size = wallet_usdz * rng.gen_range(0.9..1.0);
+                    return (TradeAction::Buy, position_size);
+                }
+            }
+            
+            // Panic selling - sell aggressively when price is falling
+            if price_change_pct < -self.panic_threshold {
+                // Panic sell with 90% chance when price is falling (up from 80%)
+                if rng.gen_bool(0.9) && wallet_zux > 0.0 {
+                    // Determine position size - panic selling uses 90-100% of balance
+                    let position_size = wallet_zux * rng.gen_range(0.9..1.0);
+                    return (TradeAction::Sell, position_size);
+                }
+            }
+            
+            // Regular whale manipulation - make massive trades to move the market
+            if self.whale_mode && rng.gen_bool(0.5) {
+                // 50% chance for whales to act (up from 30%)
+                if rng.gen_bool(0.5) && wallet_usdz > 0.0 {
+                    // Whale buy - use 90-100% of USDZ balance
+                    let position_size = wallet_usdz * rng.gen_range(0.9..1.0);
+                    return (TradeAction::Buy, position_size);
+                } else if wallet_zux > 0.0 {
+                    // Whale sell - use 90-100% of ZUX balance
+                    let position_size = wallet_zux * rng.gen_range(0.9..1.0);
+                    return (TradeAction::Sell, position_size);
+                }
+            }
+            
+            // Random trading with extreme position sizing
+            if rng.gen_bool(0.5) && wallet_usdz > 0.0 {
+                // Buy with 70-100% of available USDZ (up from 50-100%)
+                let position_size = wallet_usdz * rng.gen_range(0.7..1.0);
+                return (TradeAction::Buy, position_size);
+            } else if wallet_zux > 0.0 {
+                // Sell with 70-100% of available ZUX (up from 50-100%)
+                let position_size = wallet_zux * rng.gen_range(0.7..1.0);
+                return (TradeAction::Sell, position_size);
+            }
+        }
+        
+        // Default action is to hold
+        (TradeAction::Hold, 0.0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TradeAction {
+    Buy,
+    Sell,
+    Hold,
+}
+
+/// Addresses of every wallet but the system wallet, sorted so a seeded generator picks the same wallets
+fn trading_addresses(wallets: &HashMap<String, Wallet>) -> Vec<String> {
+    let mut addresses: Vec<String> = wallets.keys()
+        .filter(|&addr| addr != SYSTEM_WALLET_ADDRESS)
+        .cloned()
+        .collect();
+    addresses.sort_unstable();
+    addresses
+}
+
+/// Prepare a signed swap between a wallet and the AMM pool at `now` seconds
+///
+/// Nothing is executed here: balances and reserves only change when the block
+/// carrying the transaction is applied to the ledger. The wallet must hold the
+/// input and the network `fee`, which is paid in ZUX.
+pub fn prepare_swap(
+    wallet: &Wallet,
+    amm_pool: &AmmPool,
+    chain_id: &str,
+    is_zux_to_usd: bool,
+    input_amount: Amount,
+    fee: Amount,
+    now: u64
+) -> Result<Transaction> {
+    // Determine input currency
+    let input_currency = if is_zux_to_usd { "ZUX" } else { "USDZ" };
+    
+    // Check if wallet has sufficient balance
+    let wallet_balance = wallet.get_balance(input_currency);
+    if wallet_balance < input_amount {
+        return Err(BlockchainError::Transaction(
+            format!("Insufficient balance: {} {} (needed: {})", wallet_balance, input_currency, input_amount)
+        ));
+    }
+    let zux_needed = if is_zux_to_usd { input_amount.checked_add(fee)? } else { fee };
+    let zux_balance = wallet.get_balance("ZUX");
+    if zux_balance < zux_needed {
+        return Err(BlockchainError::Transaction(
+            format!("Insufficient balance: {} ZUX (needed: {} including the fee)", zux_balance, zux_needed)
+        ));
+    }
+    
+    // Make sure the pool would accept the swap at current reserves
+    if !amm_pool.is_funded() {
+        return Err(BlockchainError::Transaction("AMM pool has no liquidity".to_string()));
+    }
+    let quoted_output = amm_pool.calculate_output_amount(input_amount, is_zux_to_usd)?;
+    if quoted_output.is_zero() {
+        return Err(BlockchainError::Transaction("Swap would result in too small output".to_string()));
+    }
+    
+    // Create the swap transaction, protected against slippage and delays
+    let min_out = quoted_output.mul_div(
+        u128::from(BASIS_POINTS - SWAP_SLIPPAGE_TOLERANCE_BPS),
+        u128::from(BASIS_POINTS)
+    )?;
+    create_swap_transaction(wallet, chain_id, is_zux_to_usd, input_amount, min_out, now + SWAP_DEADLINE_SECS, fee, now)
+}
+
+/// Create an intelligent swap transaction based on trading strategy, at `current_time` seconds
+///
+/// Every random choice is drawn from `rng`: the same seeded generator and
+/// wallets give the same swap.
+pub fn create_intelligent_swap(
+    wallets: &mut HashMap<String, Wallet>,
+    amm_pool: &AmmPool,
+    chain_id: &str,
+    current_time: u64,
+    rng: &mut impl Rng
+) -> Result<(String, bool, Amount, Transaction)> {
+    let wallet_addresses = trading_addresses(wallets);
+    let wallet_count = wallet_addresses.len();
+    if wallet_count == 0 {
+        return Err(BlockchainError::Transaction("No wallets available for swap".to_string()));
+    }
+    
+    // Select a random wallet
+    let wallet_idx = rng.gen_range(0..wallet_count);
+    let wallet_address = wallet_addresses[wallet_idx].clone();
+    
+    // Get the wallet
+    let mut wallet = wallets.remove(&wallet_address)
+        .ok_or_else(|| BlockchainError::Wallet(format!("Wallet not found: {}", wallet_address)))?;
+    
+    // Get current price
+    let current_price = amm_pool.get_zux_price();
+    
+    // Initialize trading strategy if it doesn't exist
+    if wallet.trading_strategy.is_none() {
+        wallet.initialize_trading_strategy(current_price, rng);
+    }
+    
+    // Bid for inclusion; the fee is paid in ZUX, so it is set aside from what can be sold
+    let strategy = wallet.trading_strategy.as_ref().unwrap();
+    let fee = choose_fee(rng, strategy.whale_mode || strategy.mega_whale_mode);
+    let spendable_zux = wallet.get_balance("ZUX").checked_sub(fee).unwrap_or(Amount::ZERO);
+    
+    // Get the wallet's trading action
+    let trading_action = {
+        let zux_balance = wallet.get_balance("ZUX");
+        let usdz_balance = wallet.get_balance("USDZ");
+        let trading_strategy = wallet.trading_strategy.as_mut().unwrap();
+        trading_strategy.decide_action(current_price, current_time, zux_balance.to_f64(), usdz_balance.to_f64(), rng)
+    };
+    
+    // Determine swap direction and amount based on trading action
+    let (is_zux_to_usd, input_amount) = match trading_action {
+        (TradeAction::Buy, position_size) => {
+            // Buy ZUX with USDZ - ultra aggressive
+            let is_zux_to_usd = false; // USDZ to ZUX
+            let usdz_balance = wallet.get_balance("USDZ");
+            
+            // Skip if balance is too small
+            if usdz_balance < MIN_TRADE_AMOUNT {
+                wallets.insert(wallet_address, wallet);
+                return create_intelligent_swap(wallets, amm_pool, chain_id, current_time, rng);
+            }
+            
+            let input_amount = Amount::from_f64(position_size)?.min(usdz_balance);
+            (is_zux_to_usd, input_amount)
+        },
+        (TradeAction::Sell, position_size) => {
+            // Sell ZUX for USDZ - ultra aggressive
+            let is_zux_to_usd = true; // ZUX to USDZ
+            let zux_balance = spendable_zux;
+            
+            // Skip if balance is too small
+            if zux_balance < MIN_TRADE_AMOUNT {
+                wallets.insert(wallet_address, wallet);
+                return create_intelligent_swap(wallets, amm_pool, chain_id, current_time, rng);
+            }
+            
+            let input_amount = Amount::from_f64(position_size)?.min(zux_balance);
+            (is_zux_to_usd, input_amount)
+        },
+        (TradeAction::Hold, _) => {
+            // Even for hold, make a smaller random trade
+            let is_zux_to_usd = rng.gen_bool(0.5);
+            
+            let input_amount = if is_zux_to_usd {
+                let zux_balance = spendable_zux;
+                
+                // Skip if balance is too small
+                if zux_balance < MIN_TRADE_AMOUNT {
+                    wallets.insert(wallet_address, wallet);
+                    return create_intelligent_swap(wallets, amm_pool, chain_id, current_time, rng);
+                }
+                
+                // Use 10-30% of ZUX balance
+                zux_balance.mul_div(rng.gen_range(1_000..3_000), u128::from(BASIS_POINTS))?
+            } else {
+                let usdz_balance = wallet.get_balance("USDZ");
+                
+                // Skip if balance is too small
+                if usdz_balance < MIN_TRADE_AMOUNT {
+                    wallets.insert(wallet_address, wallet);
+                    return create_intelligent_swap(wallets, amm_pool, chain_id, current_time, rng);
+                }
+                
+                // Use 10-30% of USDZ balance
+                usdz_balance.mul_div(rng.gen_range(1_000..3_000), u128::from(BASIS_POINTS))?
+            };
+            
+            (is_zux_to_usd, input_amount)
+        },
+    };
+    
+    // Ensure minimum trade amount and skip if too small
+    if input_amount < MIN_TRADE_AMOUNT {
+        wallets.insert(wallet_address, wallet);
+        return create_intelligent_swap(wallets, amm_pool, chain_id, current_time, rng);
+    }
+    
+    // Prepare the swap
+    let result = prepare_swap(&wallet, amm_pool, chain_id, is_zux_to_usd, input_amount, fee, current_time);
+    
+    // Handle errors by trying again with another wallet
+    if result.is_err() {
+        wallets.insert(wallet_address, wallet);
+        return create_intelligent_swap(wallets, amm_pool, chain_id, current_time, rng);
+    }
+    
+    let transaction = result.unwrap();
+    
+    // Update last trade time
+    if let Some(trading_strategy) = wallet.trading_strategy.as_mut() {
+        trading_strategy.last_trade_time = current_time;
+    }
+    
+    // Put the wallet back in the map
+    wallets.insert(wallet_address.clone(), wallet);
+    
+    Ok((wallet_address, is_zux_to_usd, input_amount, transaction))
+}
+
+/// Create a random swap transaction for simulation at `now` seconds, drawn from `rng` (kept for backward compatibility)
+pub fn create_random_swap(
+    wallets: &mut HashMap<String, Wallet>,
+    amm_pool: &AmmPool,
+    chain_id: &str,
+    now: u64,
+    rng: &mut impl Rng
+) -> Result<(String, bool, Amount, Transaction)> {
+    let wallet_addresses = trading_addresses(wallets);
+    let wallet_count = wallet_addresses.len();
+    if wallet_count == 0 {
+        return Err(BlockchainError::Transaction("No wallets available for swap".to_string()));
+    }
+    
+    // Select a random wallet
+    let wallet_idx = rng.gen_range(0..wallet_count);
+    let wallet_address = wallet_addresses[wallet_idx].clone();
+    
+    // Randomly decide swap direction (ZUX to USD or USD to ZUX)
+    let is_zux_to_usd = rng.gen_bool(0.5);
+    
+    // Get the wallet
+    let wallet = wallets.remove(&wallet_address)
+        .ok_or_else(|| BlockchainError::Wallet(format!("Wallet not found: {}", wallet_address)))?;
+    
+    // Determine input currency based on swap direction
+    let input_currency = if is_zux_to_usd { "ZUX" } else { "USDZ" };
+    
+    // Get wallet balance for the input currency, keeping the fee aside when it is ZUX
+    let fee = choose_fee(rng, false);
+    let wallet_balance = wallet.get_balance(input_currency);
+    let wallet_balance = if is_zux_to_usd { wallet_balance.checked_sub(fee).unwrap_or(Amount::ZERO) } else { wallet_balance };
+    
+    // Generate a random amount between 0.000000001 and wallet balance (max 100.0)
+    let max_amount = wallet_balance.min(Amount::from_tokens(100));
+    let input_amount = if max_amount > Amount::BASE_UNIT {
+        // Scale max_amount by a random factor between 0.000000001 and 1
+        let random_factor = rng.gen_range(1..=BASE_UNITS_PER_TOKEN);
+        max_amount.mul_div(random_factor, BASE_UNITS_PER_TOKEN)?.max(Amount::BASE_UNIT) // Ensure minimum amount
+    } else {
+        // Skip this wallet if it has insufficient balance
+        wallets.insert(wallet_address, wallet);
+        return create_random_swap(wallets, amm_pool, chain_id, now, rng);
+    };
+    
+    // Prepare the swap
+    let transaction = prepare_swap(&wallet, amm_pool, chain_id, is_zux_to_usd, input_amount, fee, now)?;
+    
+    // Put the wallet back in the map
+    wallets.insert(wallet_address.clone(), wallet);
+    
+    Ok((wallet_address, is_zux_to_usd, input_amount, transaction))
+}
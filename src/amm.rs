@@ -0,0 +1,343 @@
+//! Constant product (x * y = k) AMM pool for the ZUX/USDZ pair
+//!
+//! Reserves and swap amounts are fixed-point [`Amount`]s and swap outputs are
+//! rounded down, so the pool never pays out more than the curve allows. Prices,
+//! volumes and `k_constant` are derived floats, kept for display only.
+//!
+//! The swap fee is taken from the input and stays in the pool, so fees are
+//! counted in the currency each swap paid in.
+//!
+//! The pool reads no clock: every operation is given the time it happens at,
+//! the timestamp of the block executing it, so price and volume statistics
+//! follow the chain.
+
+use serde::{Deserialize, Serialize};
+
+use crate::amount::Amount;
+use crate::error::{BlockchainError, Result};
+
+/// One hundred percent, in basis points
+pub const BASIS_POINTS: u32 = 10_000;
+
+// Enum to track different types of blockchain events
+/// Structure to represent a price point with timestamp
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PricePoint {
+    pub timestamp: u64,
+    pub price: f64,
+}
+
+/// Swap fees kept by the pool since it was funded, each in the currency it was paid in
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SwapFees {
+    pub zux: Amount,  // Paid by ZUX → USDZ swaps
+    pub usdz: Amount, // Paid by USDZ → ZUX swaps
+}
+
+impl SwapFees {
+    /// Count the fee of one swap
+    pub fn record(&mut self, is_zux_to_usd: bool, fee: Amount) -> Result<()> {
+        if is_zux_to_usd {
+            self.zux = self.zux.checked_add(fee)?;
+        } else {
+            self.usdz = self.usdz.checked_add(fee)?;
+        }
+        Ok(())
+    }
+
+    /// Value of all fees in USDZ at `zux_price`, for display
+    pub fn value_usd(&self, zux_price: f64) -> f64 {
+        self.zux.to_f64() * zux_price + self.usdz.to_f64()
+    }
+}
+
+/// AMM Pool structure implementing Constant Product Market Maker (x * y = k)
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AmmPool {
+    pub zux_reserve: Amount,
+    pub usd_reserve: Amount,
+    pub k_constant: f64,
+    pub fee_bps: u32,               // Swap fee in basis points, 30 = 0.3%
+    pub fees_collected: SwapFees,
+    pub price_history: Vec<PricePoint>,
+    // Volume tracking
+    pub total_volume_usd: f64,      // Since inception
+    pub recent_volume_usd: f64,     // Last 5 seconds
+    pub last_volume_reset: u64,     // Timestamp of last 5s reset
+    // Price tracking for 5s and since inception
+    pub price_5s_high: f64,
+    pub price_5s_low: f64,
+    pub price_5s_open: f64,
+    pub price_inception_high: f64,
+    pub price_inception_low: f64,
+    pub price_inception_open: f64,
+    pub last_price_reset: u64,      // Timestamp of last 5s price reset
+}
+
+impl AmmPool {
+    /// Create a new AMM pool with initial liquidity, funded at `timestamp`
+    pub fn new(initial_zux: Amount, initial_usd: Amount, fee_bps: u32, timestamp: u64) -> Self {
+        let k_constant = initial_zux.to_f64() * initial_usd.to_f64();
+        
+        let initial_price = initial_usd.to_f64() / initial_zux.to_f64();
+        
+        AmmPool {
+            zux_reserve: initial_zux,
+            usd_reserve: initial_usd,
+            k_constant,
+            fee_bps,
+            fees_collected: SwapFees::default(),
+            price_history: vec![PricePoint { timestamp, price: initial_price }],
+            total_volume_usd: 0.0,
+            recent_volume_usd: 0.0,
+            last_volume_reset: timestamp,
+            price_5s_high: initial_price,
+            price_5s_low: initial_price,
+            price_5s_open: initial_price,
+            price_inception_high: initial_price,
+            price_inception_low: initial_price,
+            price_inception_open: initial_price,
+            last_price_reset: timestamp,
+        }
+    }
+    
+    /// Create an unfunded pool; liquidity is added later with `add_liquidity`
+    pub fn empty(fee_bps: u32) -> Self {
+        let mut pool = Self::new(Amount::ZERO, Amount::ZERO, fee_bps, 0);
+        pool.price_history.clear();
+        pool.price_5s_high = 0.0;
+        pool.price_5s_low = 0.0;
+        pool.price_5s_open = 0.0;
+        pool.price_inception_high = 0.0;
+        pool.price_inception_low = 0.0;
+        pool.price_inception_open = 0.0;
+        pool
+    }
+    
+    /// Whether both reserves are funded so the pool can quote a price
+    pub fn is_funded(&self) -> bool {
+        !self.zux_reserve.is_zero() && !self.usd_reserve.is_zero()
+    }
+    
+    /// Swap fee as a percentage, for display
+    pub fn fee_percent(&self) -> f64 {
+        self.fee_bps as f64 / 100.0
+    }
+    
+    /// Get the current ZUX price in USD
+    pub fn get_zux_price(&self) -> f64 {
+        if self.zux_reserve.is_zero() {
+            return 0.0;
+        }
+        self.usd_reserve.to_f64() / self.zux_reserve.to_f64()
+    }
+    
+    /// Deposit liquidity in a single currency at `timestamp`
+    ///
+    /// Price tracking starts once both reserves have been funded.
+    pub fn add_liquidity(&mut self, currency: &str, amount: Amount, timestamp: u64) -> Result<()> {
+        if amount.is_zero() {
+            return Err(BlockchainError::Transaction("Liquidity amount must be greater than zero".to_string()));
+        }
+        
+        let was_funded = self.is_funded();
+        match currency {
+            "ZUX" => self.zux_reserve = self.zux_reserve.checked_add(amount)?,
+            "USDZ" => self.usd_reserve = self.usd_reserve.checked_add(amount)?,
+            _ => return Err(BlockchainError::Transaction(format!("Unsupported currency: {}", currency))),
+        }
+        self.k_constant = self.zux_reserve.to_f64() * self.usd_reserve.to_f64();
+        
+        // Reset price tracking from the first real price
+        if !was_funded && self.is_funded() {
+            *self = Self::new(self.zux_reserve, self.usd_reserve, self.fee_bps, timestamp);
+        }
+        
+        Ok(())
+    }
+    
+    /// Overwrite the reserves, used when the ledger reverts a block
+    pub fn set_reserves(&mut self, zux_reserve: Amount, usd_reserve: Amount) {
+        self.zux_reserve = zux_reserve;
+        self.usd_reserve = usd_reserve;
+        self.k_constant = zux_reserve.to_f64() * usd_reserve.to_f64();
+    }
+    
+    /// Calculate the output amount for a swap based on constant product formula
+    pub fn calculate_output_amount(&self, input_amount: Amount, input_is_zux: bool) -> Result<Amount> {
+        let (input_reserve, output_reserve) = if input_is_zux {
+            (self.zux_reserve, self.usd_reserve)
+        } else {
+            (self.usd_reserve, self.zux_reserve)
+        };
+        
+        Self::output_for_reserves(input_amount, input_reserve, output_reserve, self.fee_bps)
+    }
+    
+    /// Part of `input_amount` kept by the pool as the swap fee
+    pub fn swap_fee(input_amount: Amount, fee_bps: u32) -> Result<Amount> {
+        let input_with_fee = input_amount.mul_div(u128::from(BASIS_POINTS.saturating_sub(fee_bps)), u128::from(BASIS_POINTS))?;
+        input_amount.checked_sub(input_with_fee)
+    }
+    
+    /// Constant product output for arbitrary reserves, shared with the ledger's dry run
+    ///
+    /// The result is rounded down and may be zero for a tiny input.
+    pub fn output_for_reserves(input_amount: Amount, input_reserve: Amount, output_reserve: Amount, fee_bps: u32) -> Result<Amount> {
+        // Apply fee to input amount
+        let input_with_fee = input_amount.checked_sub(Self::swap_fee(input_amount, fee_bps)?)?;
+        
+        // Calculate output based on constant product formula: (x + dx) * (y - dy) = k
+        // Therefore: dy = y * dx / (x + dx)
+        let denominator = input_reserve.checked_add(input_with_fee)?;
+        output_reserve.mul_div(input_with_fee.base_units(), denominator.base_units())
+    }
+    
+    /// Swap ZUX for USD at `timestamp`
+    pub fn swap_zux_to_usd(&mut self, zux_amount: Amount, timestamp: u64) -> Result<Amount> {
+        if zux_amount.is_zero() {
+            return Err(BlockchainError::Transaction("Swap amount must be greater than zero".to_string()));
+        }
+        
+        let usd_output = self.calculate_output_amount(zux_amount, true)?;
+        
+        if usd_output.is_zero() {
+            return Err(BlockchainError::Transaction("Swap would result in too small output".to_string()));
+        }
+        
+        // Calculate USD values for volume tracking at current price
+        let current_price = self.get_zux_price();
+        let input_amount_usd = zux_amount.to_f64() * current_price;
+        let output_amount_usd = usd_output.to_f64();
+        
+        // Update reserves
+        self.zux_reserve = self.zux_reserve.checked_add(zux_amount)?;
+        self.usd_reserve = self.usd_reserve.checked_sub(usd_output)?;
+        
+        // Update k constant
+        self.k_constant = self.zux_reserve.to_f64() * self.usd_reserve.to_f64();
+        
+        // Record new price point
+        let new_price = self.get_zux_price();
+        self.price_history.push(PricePoint { timestamp, price: new_price });
+        
+        // Limit price history size to avoid memory issues
+        if self.price_history.len() > 1000 {
+            self.price_history.remove(0);
+        }
+        
+        // Add volume and fee tracking
+        self.add_volume(input_amount_usd, output_amount_usd, timestamp);
+        self.fees_collected.record(true, Self::swap_fee(zux_amount, self.fee_bps)?)?;
+        
+        Ok(usd_output)
+    }
+    
+    /// Swap USD for ZUX at `timestamp`
+    pub fn swap_usd_to_zux(&mut self, usd_amount: Amount, timestamp: u64) -> Result<Amount> {
+        if usd_amount.is_zero() {
+            return Err(BlockchainError::Transaction("Swap amount must be greater than zero".to_string()));
+        }
+        
+        let zux_output = self.calculate_output_amount(usd_amount, false)?;
+        
+        if zux_output.is_zero() {
+            return Err(BlockchainError::Transaction("Swap would result in too small output".to_string()));
+        }
+        
+        // Calculate USD values for volume tracking at current price
+        let current_price = self.get_zux_price();
+        let input_amount_usd = usd_amount.to_f64();
+        let output_amount_usd = zux_output.to_f64() * current_price;
+        
+        // Update reserves
+        self.usd_reserve = self.usd_reserve.checked_add(usd_amount)?;
+        self.zux_reserve = self.zux_reserve.checked_sub(zux_output)?;
+        
+        // Update k constant
+        self.k_constant = self.zux_reserve.to_f64() * self.usd_reserve.to_f64();
+        
+        // Record new price point
+        let new_price = self.get_zux_price();
+        self.price_history.push(PricePoint { timestamp, price: new_price });
+        
+        // Limit price history size to avoid memory issues
+        if self.price_history.len() > 1000 {
+            self.price_history.remove(0);
+        }
+        
+        // Add volume and fee tracking
+        self.add_volume(input_amount_usd, output_amount_usd, timestamp);
+        self.fees_collected.record(false, Self::swap_fee(usd_amount, self.fee_bps)?)?;
+        
+        Ok(zux_output)
+    }
+    
+    /// Get recent price history for display
+    pub fn get_recent_price_history(&self, count: usize) -> Vec<PricePoint> {
+        let start_idx = if self.price_history.len() > count {
+            self.price_history.len() - count
+        } else {
+            0
+        };
+        
+        self.price_history[start_idx..].to_vec()
+    }
+    
+    /// Add trading volume at `current_time` and update price tracking
+    pub fn add_volume(&mut self, input_amount_usd: f64, output_amount_usd: f64, current_time: u64) {
+        // Calculate total trade volume in USD (average of input/output to avoid double counting)
+        let trade_volume_usd = (input_amount_usd + output_amount_usd) / 2.0;
+        
+        // Add to total volume since inception
+        self.total_volume_usd += trade_volume_usd;
+        
+        // Reset 5s metrics if 5 seconds have passed
+        if current_time >= self.last_volume_reset + 5 {
+            self.recent_volume_usd = 0.0;
+            self.last_volume_reset = current_time;
+        }
+        
+        // Add to recent 5s volume
+        self.recent_volume_usd += trade_volume_usd;
+        
+        // Update price tracking
+        let current_price = self.get_zux_price();
+        
+        // Reset 5s price metrics if 5 seconds have passed
+        if current_time >= self.last_price_reset + 5 {
+            self.price_5s_high = current_price;
+            self.price_5s_low = current_price;
+            self.price_5s_open = current_price;
+            self.last_price_reset = current_time;
+        } else {
+            // Update 5s price ranges
+            if current_price > self.price_5s_high {
+                self.price_5s_high = current_price;
+            }
+            if current_price < self.price_5s_low {
+                self.price_5s_low = current_price;
+            }
+        }
+        
+        // Update inception price ranges
+        if current_price > self.price_inception_high {
+            self.price_inception_high = current_price;
+        }
+        if current_price < self.price_inception_low {
+            self.price_inception_low = current_price;
+        }
+    }
+}
+
+// Function to display AMM pool information
+pub fn display_amm_pool(amm_pool: &AmmPool) {
+    println!("\n________________________ZUX/USDZ AMM Pool_________________________________");
+    println!("ZUX Reserve     : {}", amm_pool.zux_reserve);
+    println!("USDZ Reserve    : {}", amm_pool.usd_reserve);
+    println!("K Constant      : {}", amm_pool.k_constant);
+    println!("Fee Percentage  : {}%", amm_pool.fee_percent());
+    println!("Fees Collected  : {} ZUX, {} USDZ", amm_pool.fees_collected.zux, amm_pool.fees_collected.usdz);
+    println!("Current Price   : {:.6} USDZ per ZUX", amm_pool.get_zux_price());
+    println!("____________________________________________________________________________\n");
+}
@@ -0,0 +1,162 @@
+//! Fixed-point token amounts
+//!
+//! Balances, reserves and transaction amounts are whole numbers of base units:
+//! every supported currency has 9 decimals, so one ZUX or USDZ is 10^9 base
+//! units. Arithmetic is checked and never rounds except where a division is
+//! explicit, so the total supply of a currency is conserved exactly.
+//!
+//! Amounts are written to JSON as decimal strings (`"100.000000000"`) so no
+//! reader has to go through a float to get them.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::codec::{Decode, Decoder, Encode, Encoder};
+use crate::error::{BlockchainError, Result};
+
+/// Number of decimals of every supported currency
+pub const DECIMALS: u32 = 9;
+
+/// Base units in one whole token
+pub const BASE_UNITS_PER_TOKEN: u128 = 10u128.pow(DECIMALS);
+
+/// A non-negative amount of a currency, in base units
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Amount(u128);
+
+impl Amount {
+    pub const ZERO: Amount = Amount(0);
+
+    /// Smallest non-zero amount, 0.000000001
+    pub const BASE_UNIT: Amount = Amount(1);
+
+    pub const fn from_base_units(units: u128) -> Self {
+        Amount(units)
+    }
+
+    pub const fn base_units(self) -> u128 {
+        self.0
+    }
+
+    /// Amount of `tokens` whole tokens
+    pub const fn from_tokens(tokens: u64) -> Self {
+        Amount(tokens as u128 * BASE_UNITS_PER_TOKEN)
+    }
+
+    /// Nearest amount to a float number of tokens, for sizes computed by trading heuristics
+    pub fn from_f64(tokens: f64) -> Result<Self> {
+        let units = (tokens * BASE_UNITS_PER_TOKEN as f64).round();
+        if !units.is_finite() || units < 0.0 || units >= u128::MAX as f64 {
+            return Err(BlockchainError::Amount(format!("{} is not a valid amount", tokens)));
+        }
+        Ok(Amount(units as u128))
+    }
+
+    /// Approximate number of tokens, for prices and other derived statistics
+    pub fn to_f64(self) -> f64 {
+        self.0 as f64 / BASE_UNITS_PER_TOKEN as f64
+    }
+
+    pub fn is_zero(self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn checked_add(self, other: Amount) -> Result<Amount> {
+        self.0.checked_add(other.0)
+            .map(Amount)
+            .ok_or_else(|| BlockchainError::Amount(format!("{} + {} overflows", self, other)))
+    }
+
+    pub fn checked_sub(self, other: Amount) -> Result<Amount> {
+        self.0.checked_sub(other.0)
+            .map(Amount)
+            .ok_or_else(|| BlockchainError::Amount(format!("{} - {} is negative", self, other)))
+    }
+
+    /// `self * numerator / denominator`, rounded down
+    pub fn mul_div(self, numerator: u128, denominator: u128) -> Result<Amount> {
+        if denominator == 0 {
+            return Err(BlockchainError::Amount(format!("{} divided by zero", self)));
+        }
+        self.0.checked_mul(numerator)
+            .map(|product| Amount(product / denominator))
+            .ok_or_else(|| BlockchainError::Amount(format!("{} * {} overflows", self, numerator)))
+    }
+
+    /// Sum of `amounts`, failing instead of overflowing
+    pub fn checked_sum(amounts: impl IntoIterator<Item = Amount>) -> Result<Amount> {
+        amounts.into_iter().try_fold(Amount::ZERO, Amount::checked_add)
+    }
+}
+
+/// Shows all 9 decimals, or as many as the precision asks for (truncated)
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let whole = self.0 / BASE_UNITS_PER_TOKEN;
+        let fraction = format!("{:09}", self.0 % BASE_UNITS_PER_TOKEN);
+        let decimals = f.precision().unwrap_or(DECIMALS as usize).min(DECIMALS as usize);
+        let text = if decimals == 0 {
+            whole.to_string()
+        } else {
+            format!("{}.{}", whole, &fraction[..decimals])
+        };
+
+        // Numbers are right-aligned unless asked otherwise
+        let padding = f.width().unwrap_or(0).saturating_sub(text.len());
+        let fill = f.fill().to_string();
+        let (before, after) = match f.align() {
+            Some(fmt::Alignment::Left) => (0, padding),
+            Some(fmt::Alignment::Center) => (padding / 2, padding - padding / 2),
+            _ => (padding, 0),
+        };
+        write!(f, "{}{}{}", fill.repeat(before), text, fill.repeat(after))
+    }
+}
+
+/// Parses a decimal number of tokens with at most 9 decimals, e.g. `12.5`
+impl FromStr for Amount {
+    type Err = BlockchainError;
+
+    fn from_str(text: &str) -> Result<Self> {
+        let invalid = || BlockchainError::Amount(format!("Invalid amount: {:?}", text));
+        let (whole, fraction) = text.split_once('.').unwrap_or((text, ""));
+        let is_digits = |part: &str| part.bytes().all(|byte| byte.is_ascii_digit());
+        if whole.is_empty() || !is_digits(whole) || !is_digits(fraction) || fraction.len() > DECIMALS as usize {
+            return Err(invalid());
+        }
+
+        let whole: u128 = whole.parse().map_err(|_| invalid())?;
+        let fraction: u128 = format!("{:0<9}", fraction).parse().map_err(|_| invalid())?;
+        whole.checked_mul(BASE_UNITS_PER_TOKEN)
+            .and_then(|units| units.checked_add(fraction))
+            .map(Amount)
+            .ok_or_else(invalid)
+    }
+}
+
+impl Serialize for Amount {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Amount {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        text.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl Encode for Amount {
+    fn encode(&self, out: &mut Encoder) {
+        out.put_u128(self.0);
+    }
+}
+
+impl Decode for Amount {
+    fn decode(input: &mut Decoder) -> Result<Self> {
+        Ok(Amount(input.u128()?))
+    }
+}
@@ -0,0 +1,490 @@
+//! Blocks, block events and proof-of-work mining
+
+use std::sync::atomic::AtomicBool;
+
+use chrono::{FixedOffset, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::amount::Amount;
+use crate::codec::{self, Decode, Decoder, Encode, Encoder};
+use crate::difficulty::Target;
+use crate::error::{BlockchainError, Result};
+use crate::miner::Miner;
+use crate::proof::{ProofStep, TransactionProof};
+use crate::transaction::Transaction;
+
+// Domain tag keeping block header hashes apart from any other hashed message
+const HEADER_DOMAIN: &str = "ZUX block header";
+
+// Blockchain configuration
+pub(crate) const BLOCK_VERSION: &str = "1.0.0.0.0";
+pub(crate) const INCEPTION_YEAR: u16 = 2025;
+
+/// Receipt of what a block did, derived by the ledger from executing its transactions
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum BlockEvent {
+    Genesis,
+    WalletCreation(String), // Wallet address
+    TokenCredit(String, String, Amount), // Wallet address, currency code, amount (transfer or mint)
+    Swap(String, bool, Amount, Amount), // Wallet address, is_zux_to_usd, input_amount, output_amount
+    LiquidityAdded(String, Amount, Amount), // Provider address, ZUX amount, USDZ amount
+    LiquidityRemoved(String, Amount, Amount), // Provider address, ZUX amount, USDZ amount
+}
+
+impl BlockEvent {
+    /// Short name of the event, for display
+    pub fn name(&self) -> &'static str {
+        match self {
+            BlockEvent::Genesis => "Genesis",
+            BlockEvent::WalletCreation(_) => "Wallet Creation",
+            BlockEvent::TokenCredit(_, _, _) => "Token Credit",
+            BlockEvent::Swap(_, _, _, _) => "Token Swap",
+            BlockEvent::LiquidityAdded(_, _, _) => "Liquidity Added",
+            BlockEvent::LiquidityRemoved(_, _, _) => "Liquidity Removed",
+        }
+    }
+}
+
+/// Block structure to store all block information
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Block {
+    pub id: u64,
+    pub hash: String,
+    pub parent_hash: String,
+    pub tx_root: String,       // Merkle root of the transactions and events
+    pub state_root: String,    // Sparse Merkle root of the ledger state after this block
+    pub timestamp: u64,
+    pub block_class: String,
+    pub block_type: String,
+    pub version: String,
+    pub inception_year: u16,
+    pub network_name: String,
+    pub producer: String,      // Address credited with the block reward and the fees of its transactions
+    pub transactions: Vec<Transaction>,
+    pub events: Vec<BlockEvent>, // Receipt of each transaction, in order; only genesis has one without a transaction
+    pub formatted_time: String,
+    pub target: Target,        // Proof-of-work target the block hash must not exceed
+    pub extra_nonce: u64,      // Rolled by the miner once every nonce has been tried
+    pub nonce: u64,            // Nonce used for mining
+    pub signature: Vec<u8>,    // Producer's Ed25519 signature of the hash, for signed consensus engines
+}
+
+impl Block {
+    /// Calculate a Merkle root hash from transactions and event data
+    ///
+    /// Without transactions the root is the hash of the genesis event.
+    pub fn calculate_merkle_root(transactions: &[Transaction], events: &[BlockEvent]) -> String {
+        // The last level of the tree holds the root
+        let levels = Self::merkle_levels(transactions, events);
+        levels[levels.len() - 1][0].clone()
+    }
+    
+    /// Merkle leaf committing to a single transaction
+    pub fn transaction_leaf(transaction: &Transaction) -> String {
+        transaction.hash()
+    }
+    
+    /// Merkle leaf committing to a block event
+    pub fn event_leaf(event: &BlockEvent) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(codec::to_bytes(event));
+        hex::encode(hasher.finalize())
+    }
+    
+    /// Hash two sibling nodes of the transaction tree into their parent
+    pub fn hash_pair(left: &str, right: &str) -> String {
+        let mut pair_hasher = Sha256::new();
+        pair_hasher.update(left.as_bytes());
+        pair_hasher.update(right.as_bytes());
+        hex::encode(pair_hasher.finalize())
+    }
+    
+    /// Every level of the transaction tree, from the leaves up to the root
+    fn merkle_levels(transactions: &[Transaction], events: &[BlockEvent]) -> Vec<Vec<String>> {
+        // Create leaf nodes from transaction hashes, followed by the events
+        let mut leaves: Vec<String> = transactions.iter()
+            .map(Self::transaction_leaf)
+            .chain(events.iter().map(Self::event_leaf))
+            .collect();
+        
+        // An empty tree still has a root
+        if leaves.is_empty() {
+            leaves.push(hex::encode(Sha256::digest([])));
+        }
+        
+        // Build the Merkle tree by repeatedly hashing pairs of nodes
+        let mut levels = vec![leaves];
+        while levels[levels.len() - 1].len() > 1 {
+            let nodes = &levels[levels.len() - 1];
+            let new_level = nodes.chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => Self::hash_pair(left, right),
+                    // Odd number of nodes, promote the last one
+                    _ => pair[0].clone(),
+                })
+                .collect();
+            
+            levels.push(new_level);
+        }
+        
+        levels
+    }
+    
+    /// Build a proof that the transaction at `tx_index` is committed to by this block's `tx_root`
+    pub fn transaction_proof(&self, tx_index: usize) -> Result<TransactionProof> {
+        let transaction = self.transactions.get(tx_index).ok_or_else(|| {
+            BlockchainError::Proof(format!("Block #{} has no transaction #{}", self.id, tx_index))
+        })?;
+        
+        let levels = Self::merkle_levels(&self.transactions, &self.events);
+        let mut steps = Vec::new();
+        let mut index = tx_index;
+        for level in &levels[..levels.len() - 1] {
+            // A promoted node has no sibling at this level
+            let sibling_index = index ^ 1;
+            if let Some(sibling) = level.get(sibling_index) {
+                steps.push(ProofStep {
+                    sibling: sibling.clone(),
+                    sibling_on_left: sibling_index < index,
+                });
+            }
+            index /= 2;
+        }
+        
+        Ok(TransactionProof {
+            block_id: self.id,
+            tx_index,
+            leaf_count: levels[0].len(),
+            tx_hash: Self::transaction_leaf(transaction),
+            steps,
+        })
+    }
+    
+    /// Canonical encoding of the header fields covered by the block hash, except the nonce
+    fn header_encoding(&self) -> Encoder {
+        let mut out = Encoder::new();
+        out.put_str(HEADER_DOMAIN);
+        out.put_u64(self.id);
+        out.put_str(&self.parent_hash);
+        out.put_str(&self.tx_root);
+        out.put_str(&self.state_root);
+        out.put_u64(self.timestamp);
+        out.put_str(&self.block_class);
+        out.put_str(&self.block_type);
+        out.put_str(&self.version);
+        out.put_u16(self.inception_year);
+        out.put_str(&self.network_name);
+        out.put_str(&self.producer);
+        self.target.encode(&mut out);
+        out.put_u64(self.extra_nonce);
+        out
+    }
+    
+    /// Hasher that has already absorbed every header field except the nonce
+    ///
+    /// Only the nonce changes between two attempts at the same header, so the
+    /// miner hashes this prefix once and clones it for each nonce.
+    pub(crate) fn header_prefix(&self) -> Sha256 {
+        Sha256::new_with_prefix(self.header_encoding().as_bytes())
+    }
+    
+    /// Hash of a header prefix completed with `nonce`
+    pub(crate) fn hash_with_nonce(prefix: &Sha256, nonce: u64) -> [u8; 32] {
+        prefix.clone().chain_update(nonce.to_be_bytes()).finalize().into()
+    }
+    
+    /// Hash of the block header with its current nonce
+    pub fn calculate_hash(&self) -> String {
+        hex::encode(Self::hash_with_nonce(&self.header_prefix(), self.nonce))
+    }
+    
+    /// Expected number of hashes it took to mine this block
+    pub fn difficulty(&self) -> f64 {
+        self.target.work()
+    }
+    
+    /// Mine the block with the default `Miner`, on every available core, for proof-of-work chains
+    pub fn mine(&mut self) -> Result<()> {
+        Miner::default().mine(self, &AtomicBool::new(false))?
+            .map(|_| ())
+            .ok_or_else(|| BlockchainError::Block(format!("Mining of block #{} was cancelled", self.id)))
+    }
+    
+    /// Verify that the block hash is valid
+    ///
+    /// The seal, a proof-of-work or a producer signature, is checked by the
+    /// chain's consensus engine, see `Consensus::verify_seal`.
+    pub fn verify(&self) -> Result<()> {
+        // Verify that the calculated hash matches the stored hash
+        let calculated_hash = self.calculate_hash();
+        if calculated_hash != self.hash {
+            return Err(BlockchainError::Block(format!("Invalid block hash: expected {}, got {}", self.hash, calculated_hash)));
+        }
+        
+        // Verify all transactions in the block
+        for tx in &self.transactions {
+            tx.verify()?;
+        }
+        
+        Ok(())
+    }
+    
+    /// Create a new block with transaction and event information, ready to be mined
+    ///
+    /// `events` and `state_root` are the receipts and the ledger state root after
+    /// executing the block at `creation_timestamp` with `producer` as its
+    /// producer, as returned by `State::preview`. `target` is the proof-of-work
+    /// target the ledger expects next, see `State::next_target`. The block has
+    /// no valid hash until it is sealed, see `Consensus::seal`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(current_block_id: u64, parent_hash: &str, transactions: &[Transaction], 
+           network_name: &str, block_ver: &str, inception_year: u16, producer: &str,
+           events: &[BlockEvent], state_root: &str, target: Target, creation_timestamp: u64) -> Result<Self> {
+        // Format the timestamp for display
+        let utc_datetime = Utc.timestamp_opt(creation_timestamp as i64, 0)
+            .single()
+            .ok_or_else(|| BlockchainError::Time("Invalid timestamp".to_string()))?;
+
+        let kuala_lumpur_tz = FixedOffset::east_opt(8 * 3600)
+            .ok_or_else(|| BlockchainError::Time("Failed to create timezone offset".to_string()))?;
+
+        let kl_datetime = utc_datetime.with_timezone(&kuala_lumpur_tz);
+        let formatted_kl_time = kl_datetime.format("%Y-%m-%d %H:%M:%S %Z").to_string();
+
+        // Determine block type based on its events
+        let block_type = match events.split_first() {
+            Some((first, rest)) if rest.iter().all(|event| event.name() == first.name()) => first.name(),
+            Some(_) => "Mixed",
+            None => "Empty",
+        };
+        
+        let block_class = if network_name == "ZUX-Testnet" { "Private" } else { "Public" };
+
+        // Create a merkle root from transactions using a more robust approach
+        let tx_root = Self::calculate_merkle_root(transactions, events);
+        
+        Ok(Block {
+            id: current_block_id,
+            hash: String::new(),
+            parent_hash: parent_hash.to_string(),
+            tx_root,
+            state_root: state_root.to_string(),
+            timestamp: creation_timestamp,
+            target,
+            extra_nonce: 0,
+            nonce: 0,
+            signature: Vec::new(),
+            block_class: block_class.to_string(),
+            block_type: block_type.to_string(),
+            version: block_ver.to_string(),
+            inception_year,
+            network_name: network_name.to_string(),
+            producer: producer.to_string(),
+            transactions: transactions.to_vec(),
+            events: events.to_vec(),
+            formatted_time: formatted_kl_time,
+        })
+    }
+
+    /// Print block information to console
+    pub fn print(&self) {
+        println!("\nThis is a private simulation of a local blockchain, run by one node or by several peers exchanging blocks.\n");
+        println!("________________________ZUX Block ({})_________________________________________", self.block_type);
+        println!("Block ID         : {:08}", self.id);
+        println!("Block Hash       : {}", self.hash);
+        println!("Parent Hash      : {}", self.parent_hash);
+        println!("Tx Root          : {}", self.tx_root);
+        println!("State Root       : {}", self.state_root);
+        println!("Creation Timestamp: {} (UNIX Epoch Seconds) ({})\n", self.timestamp, self.formatted_time);
+        println!("Target           : {} ({} leading zero bits)", self.target, self.target.leading_zero_bits());
+        println!("Difficulty       : {:.0} expected hashes", self.difficulty());
+        println!("Nonce            : {} (extra nonce {})", self.nonce, self.extra_nonce);
+        if !self.signature.is_empty() {
+            println!("Signature        : {}", hex::encode(&self.signature));
+        }
+        println!("Block Class      : {}", self.block_class);
+        println!("Block Type       : {}", self.block_type);
+        println!("Block Version    : {} // The very first version", self.version);
+        println!("Inception Year   : {}", self.inception_year);
+        println!("Network Name     : {} // Since its running on a private testnet", self.network_name);
+        println!("Producer         : {}", self.producer);
+        
+        // Print the receipt of every transaction
+        for event in &self.events {
+            match event {
+                BlockEvent::Genesis => {
+                    println!("Event           : Genesis Block Creation");
+                },
+                BlockEvent::WalletCreation(address) => {
+                    println!("Event           : Wallet Creation");
+                    println!("Wallet Address  : {}", address);
+                },
+                BlockEvent::TokenCredit(address, currency, amount) => {
+                    println!("Event           : Token Credit");
+                    println!("Wallet Address  : {}", address);
+                    println!("Currency        : {}", currency);
+                    println!("Credit Amount   : {}", amount);
+                },
+                BlockEvent::Swap(address, is_zux_to_usd, input_amount, output_amount) => {
+                    println!("Event           : Token Swap");
+                    println!("Wallet Address  : {}", address);
+                
+                    if *is_zux_to_usd {
+                        println!("Swap Direction  : ZUX → USDZ");
+                        println!("Input Amount    : {} ZUX", input_amount);
+                        println!("Output Amount   : {} USDZ", output_amount);
+                    } else {
+                        println!("Swap Direction  : USDZ → ZUX");
+                        println!("Input Amount    : {} USDZ", input_amount);
+                        println!("Output Amount   : {} ZUX", output_amount);
+                    }
+                
+                    // Calculate and display the effective price
+                    let effective_price = if *is_zux_to_usd {
+                        output_amount.to_f64() / input_amount.to_f64()
+                    } else {
+                        input_amount.to_f64() / output_amount.to_f64()
+                    };
+                
+                    println!("Effective Price : {:.6} USDZ per ZUX", effective_price);
+                },
+                BlockEvent::LiquidityAdded(address, zux_amount, usd_amount) => {
+                    println!("Event           : Liquidity Added");
+                    println!("Provider        : {}", address);
+                    println!("Deposited       : {} ZUX, {} USDZ", zux_amount, usd_amount);
+                },
+                BlockEvent::LiquidityRemoved(address, zux_amount, usd_amount) => {
+                    println!("Event           : Liquidity Removed");
+                    println!("Provider        : {}", address);
+                    println!("Withdrawn       : {} ZUX, {} USDZ", zux_amount, usd_amount);
+                },
+            }
+        }
+        
+        // Print transaction details if any
+        println!("Transactions     : {} transaction(s)", self.transactions.len());
+        for (i, tx) in self.transactions.iter().enumerate() {
+            println!("  Transaction #{}", i + 1);
+            println!("    Sender    : {}", tx.sender);
+            println!("    Kind      : {}", tx.kind);
+            println!("    Fee       : {} ZUX", tx.fee);
+            println!("    Timestamp : {}", tx.timestamp);
+        }
+        
+        println!("____________________________________________________________________________");
+    }
+}
+
+impl Encode for BlockEvent {
+    fn encode(&self, out: &mut Encoder) {
+        match self {
+            BlockEvent::Genesis => out.put_u8(0),
+            BlockEvent::WalletCreation(address) => {
+                out.put_u8(1);
+                out.put_str(address);
+            },
+            BlockEvent::TokenCredit(address, currency, amount) => {
+                out.put_u8(2);
+                out.put_str(address);
+                out.put_str(currency);
+                amount.encode(out);
+            },
+            BlockEvent::Swap(address, is_zux_to_usd, input_amount, output_amount) => {
+                out.put_u8(3);
+                out.put_str(address);
+                out.put_bool(*is_zux_to_usd);
+                input_amount.encode(out);
+                output_amount.encode(out);
+            },
+            BlockEvent::LiquidityAdded(address, zux_amount, usd_amount) => {
+                out.put_u8(4);
+                out.put_str(address);
+                zux_amount.encode(out);
+                usd_amount.encode(out);
+            },
+            BlockEvent::LiquidityRemoved(address, zux_amount, usd_amount) => {
+                out.put_u8(5);
+                out.put_str(address);
+                zux_amount.encode(out);
+                usd_amount.encode(out);
+            },
+        }
+    }
+}
+
+impl Decode for BlockEvent {
+    fn decode(input: &mut Decoder) -> Result<Self> {
+        Ok(match input.u8()? {
+            0 => BlockEvent::Genesis,
+            1 => BlockEvent::WalletCreation(input.string()?),
+            2 => BlockEvent::TokenCredit(input.string()?, input.string()?, Amount::decode(input)?),
+            3 => BlockEvent::Swap(input.string()?, input.bool()?, Amount::decode(input)?, Amount::decode(input)?),
+            4 => BlockEvent::LiquidityAdded(input.string()?, Amount::decode(input)?, Amount::decode(input)?),
+            5 => BlockEvent::LiquidityRemoved(input.string()?, Amount::decode(input)?, Amount::decode(input)?),
+            tag => return Err(BlockchainError::Encoding(format!("Unknown block event tag {}", tag))),
+        })
+    }
+}
+
+impl Encode for Block {
+    fn encode(&self, out: &mut Encoder) {
+        out.put_u64(self.id);
+        out.put_str(&self.hash);
+        out.put_str(&self.parent_hash);
+        out.put_str(&self.tx_root);
+        out.put_str(&self.state_root);
+        out.put_u64(self.timestamp);
+        out.put_str(&self.block_class);
+        out.put_str(&self.block_type);
+        out.put_str(&self.version);
+        out.put_u16(self.inception_year);
+        out.put_str(&self.network_name);
+        out.put_str(&self.producer);
+        out.put_seq_len(self.transactions.len());
+        for transaction in &self.transactions {
+            transaction.encode(out);
+        }
+        out.put_seq_len(self.events.len());
+        for event in &self.events {
+            event.encode(out);
+        }
+        out.put_str(&self.formatted_time);
+        self.target.encode(out);
+        out.put_u64(self.extra_nonce);
+        out.put_u64(self.nonce);
+        out.put_bytes(&self.signature);
+    }
+}
+
+impl Decode for Block {
+    fn decode(input: &mut Decoder) -> Result<Self> {
+        Ok(Block {
+            id: input.u64()?,
+            hash: input.string()?,
+            parent_hash: input.string()?,
+            tx_root: input.string()?,
+            state_root: input.string()?,
+            timestamp: input.u64()?,
+            block_class: input.string()?,
+            block_type: input.string()?,
+            version: input.string()?,
+            inception_year: input.u16()?,
+            network_name: input.string()?,
+            producer: input.string()?,
+            transactions: {
+                let count = input.seq_len()?;
+                (0..count).map(|_| Transaction::decode(input)).collect::<Result<_>>()?
+            },
+            events: {
+                let count = input.seq_len()?;
+                (0..count).map(|_| BlockEvent::decode(input)).collect::<Result<_>>()?
+            },
+            formatted_time: input.string()?,
+            target: Target::decode(input)?,
+            extra_nonce: input.u64()?,
+            nonce: input.u64()?,
+            signature: input.bytes()?,
+        })
+    }
+}
@@ -4,8 +4,9 @@
 use std::fs::File;
 use std::io::{self, BufReader};
 use std::thread;
-use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant};
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 use crossterm::{
     execute, 
@@ -20,85 +21,14 @@ use tui::{
     layout::{Layout, Constraint, Direction, Alignment, Rect},
     style::{Style, Modifier, Color}
 };
-use serde::{Deserialize, Serialize};
-use chrono;
-
-// Data structures for explorer communication
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct BlockInfo {
-    pub id: u64,
-    pub hash: String,
-    pub parent_hash: String,
-    pub timestamp: u64,
-    pub transactions_count: usize,
-    pub difficulty: u64,
-    pub nonce: u64,
-    pub size_bytes: usize,
-    pub formatted_time: String,
-    pub network_name: String,
-    pub version: String,
-}
-
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct AmmInfo {
-    pub zux_reserve: f64,
-    pub usd_reserve: f64,
-    pub k_constant: f64,
-    pub current_price: f64,
-    pub total_liquidity: f64,
-    pub volume_5s: f64,
-    pub volume_total: f64,
-    pub price_5s_change: f64,
-    pub price_5s_high: f64,
-    pub price_5s_low: f64,
-    pub price_inception_change: f64,
-    pub price_inception_high: f64,
-    pub price_inception_low: f64,
-    pub fees_collected: f64,
-    pub swap_count: u64,
-    pub avg_trade_size: f64,
-    pub price_history: Vec<PricePoint>,
-}
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct PricePoint {
-    pub timestamp: u64,
-    pub price: f64,
-}
-
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct WalletInfo {
-    pub address: String,
-    pub zux_balance: f64,
-    pub usdz_balance: f64,
-    pub total_value_usd: f64,
-    pub transaction_count: u64,
-    pub is_whale: bool,
-    pub is_mega_whale: bool,
-    pub last_activity: u64,
-}
-
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct SystemWalletInfo {
-    pub address: String,
-    pub zux_balance: f64,
-    pub usdz_balance: f64,
-    pub total_issued_zux: f64,
-    pub total_issued_usdz: f64,
-    pub active_wallets: u64,
-    pub total_transactions: u64,
-    pub network_hash_rate: f64,
-    pub avg_block_time: f64,
-}
-
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct ExplorerData {
-    pub blocks: Vec<BlockInfo>,
-    pub amm_info: AmmInfo,
-    pub wallets: Vec<WalletInfo>,
-    pub system_wallet: SystemWalletInfo,
-    pub last_update: u64,
-}
+use zux::amm::{SwapFees, BASIS_POINTS};
+use zux::amount::Amount;
+use zux::clock::{Clock, SystemClock};
+use zux::consensus::EngineKind;
+use zux::explorer::{AmmInfo, BlockInfo, ChainView, ExplorerData, SystemWalletInfo, WalletInfo};
+use zux::simulation::{base_state, DEFAULT_DATA_DIR};
+use zux::store::FileStore;
 
 // Tab enumeration for navigation
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
@@ -174,8 +104,8 @@ impl ExplorerState {
             data: ExplorerData {
                 blocks: Vec::new(),
                 amm_info: AmmInfo {
-                    zux_reserve: 0.0,
-                    usd_reserve: 0.0,
+                    zux_reserve: Amount::ZERO,
+                    usd_reserve: Amount::ZERO,
                     k_constant: 0.0,
                     current_price: 0.0,
                     total_liquidity: 0.0,
@@ -187,7 +117,8 @@ impl ExplorerState {
                     price_inception_change: 0.0,
                     price_inception_high: 0.0,
                     price_inception_low: 0.0,
-                    fees_collected: 0.0,
+                    fee_bps: 0,
+                    fees_collected: SwapFees::default(),
                     swap_count: 0,
                     avg_trade_size: 0.0,
                     price_history: Vec::new(),
@@ -195,16 +126,19 @@ impl ExplorerState {
                 wallets: Vec::new(),
                 system_wallet: SystemWalletInfo {
                     address: "SYSTEM".to_string(),
-                    zux_balance: 0.0,
-                    usdz_balance: 0.0,
-                    total_issued_zux: 0.0,
-                    total_issued_usdz: 0.0,
+                    zux_balance: Amount::ZERO,
+                    usdz_balance: Amount::ZERO,
+                    total_issued_zux: Amount::ZERO,
+                    total_issued_usdz: Amount::ZERO,
+                    block_rewards_zux: Amount::ZERO,
+                    network_fees_zux: Amount::ZERO,
                     active_wallets: 0,
                     total_transactions: 0,
                     network_hash_rate: 0.0,
                     avg_block_time: 0.0,
                 },
                 last_update: 0,
+                consensus: EngineKind::default(),
             },
             last_update: Instant::now(),
             scroll_position,
@@ -290,7 +224,7 @@ impl ExplorerState {
 
 // Render the tab navigation bar
 fn render_tabs(f: &mut tui::Frame<CrosstermBackend<std::io::Stdout>>, area: Rect, current_tab: Tab) {
-    let tabs = vec![Tab::Blocks, Tab::Amm, Tab::Wallets, Tab::SystemWallet];
+    let tabs = [Tab::Blocks, Tab::Amm, Tab::Wallets, Tab::SystemWallet];
     let tab_chunks = Layout::default()
         .direction(Direction::Horizontal)
         .constraints(vec![Constraint::Percentage(25); 4])
@@ -398,7 +332,7 @@ fn render_blocks_tab(f: &mut tui::Frame<CrosstermBackend<std::io::Stdout>>, area
                 Cell::from(block.id.to_string()).style(style),
                 Cell::from(hash_short).style(style),
                 Cell::from(block.transactions_count.to_string()).style(style),
-                Cell::from(block.difficulty.to_string()).style(style),
+                Cell::from(format!("{:.0}", block.difficulty)).style(style),
                 Cell::from(time_short).style(style),
             ])
         });
@@ -469,16 +403,15 @@ fn render_amm_tab(f: &mut tui::Frame<CrosstermBackend<std::io::Stdout>>, area: R
     
     let apr_estimate = pool_utilization * 0.365; // Rough APR estimate
     
-    let pool_info = vec![
-        format!("ZUX Reserve: {:.9} tokens", state.data.amm_info.zux_reserve),
+    let pool_info = [format!("ZUX Reserve: {:.9} tokens", state.data.amm_info.zux_reserve),
         format!("USDZ Reserve: {:.9} tokens", state.data.amm_info.usd_reserve),
         format!("K Constant: {:.2}", state.data.amm_info.k_constant),
         format!("Total Liquidity: ${:.9}", state.data.amm_info.total_liquidity),
         format!("Pool Utilization: {:.2}%", pool_utilization),
         format!("Est. APR: {:.2}%", apr_estimate),
         format!("Total Swaps: {} trades", state.data.amm_info.swap_count),
-        format!("Fees Collected: ${:.9}", state.data.amm_info.fees_collected),
-    ];
+        format!("Fees Collected: {:.9} ZUX + {:.9} USDZ",
+            state.data.amm_info.fees_collected.zux, state.data.amm_info.fees_collected.usdz)];
 
     let pool_paragraph = Paragraph::new(pool_info.join("\n"))
         .style(Style::default().fg(Color::White))
@@ -494,17 +427,15 @@ fn render_amm_tab(f: &mut tui::Frame<CrosstermBackend<std::io::Stdout>>, area: R
     // Advanced trading metrics - now using real calculated values
     let avg_trade_size = state.data.amm_info.avg_trade_size;
     
-    let fee_rate = 0.003; // 0.3%
-    let daily_fees = state.data.amm_info.volume_total * fee_rate;
+    let fee_rate = state.data.amm_info.fee_bps as f64 / BASIS_POINTS as f64;
+    let fee_revenue = state.data.amm_info.fees_collected.value_usd(state.data.amm_info.current_price);
     
-    let trading_info = vec![
-        format!("Avg Trade Size: ${:.9}", avg_trade_size),
+    let trading_info = [format!("Avg Trade Size: ${:.9}", avg_trade_size),
         format!("Trading Fee Rate: {:.1}%", fee_rate * 100.0),
-        format!("Total Fee Revenue: ${:.9}", daily_fees),
-        format!("Price Impact Model: Constant Product"),
-        format!("Slippage Protection: Active"),
-        format!("MEV Protection: Enabled"),
-    ];
+        format!("Total Fee Revenue: ${:.9}", fee_revenue),
+        "Price Impact Model: Constant Product".to_string(),
+        "Slippage Protection: Active".to_string(),
+        "MEV Protection: Enabled".to_string()];
 
     let trading_paragraph = Paragraph::new(trading_info.join("\n"))
         .style(Style::default().fg(Color::White))
@@ -519,17 +450,15 @@ fn render_amm_tab(f: &mut tui::Frame<CrosstermBackend<std::io::Stdout>>, area: R
 
     // Liquidity analysis
     let zux_ratio = if state.data.amm_info.total_liquidity > 0.0 {
-        (state.data.amm_info.zux_reserve * state.data.amm_info.current_price) / state.data.amm_info.total_liquidity * 100.0
+        (state.data.amm_info.zux_reserve.to_f64() * state.data.amm_info.current_price) / state.data.amm_info.total_liquidity * 100.0
     } else { 50.0 };
     let usdz_ratio = 100.0 - zux_ratio;
     
-    let liquidity_info = vec![
-        format!("Pool Composition:"),
-        format!("  ZUX: {:.1}% (${:.2})", zux_ratio, state.data.amm_info.zux_reserve * state.data.amm_info.current_price),
+    let liquidity_info = ["Pool Composition:".to_string(),
+        format!("  ZUX: {:.1}% (${:.2})", zux_ratio, state.data.amm_info.zux_reserve.to_f64() * state.data.amm_info.current_price),
         format!("  USDZ: {:.1}% (${:.2})", usdz_ratio, state.data.amm_info.usd_reserve),
-        format!("Impermanent Loss Risk: MODERATE"),
-        format!("Pool Health: EXCELLENT"),
-    ];
+        "Impermanent Loss Risk: MODERATE".to_string(),
+        "Pool Health: EXCELLENT".to_string()];
 
     let liquidity_paragraph = Paragraph::new(liquidity_info.join("\n"))
         .style(Style::default().fg(Color::LightBlue))
@@ -543,14 +472,12 @@ fn render_amm_tab(f: &mut tui::Frame<CrosstermBackend<std::io::Stdout>>, area: R
     f.render_widget(liquidity_paragraph, left_chunks[2]);
 
     // Comprehensive price information with 5s and inception timeframes
-    let price_info = vec![
-        format!("Current Price: ${:.9}", state.data.amm_info.current_price),
+    let price_info = [format!("Current Price: ${:.9}", state.data.amm_info.current_price),
         format!("5s Change: {:.2}%", state.data.amm_info.price_5s_change),
         format!("5s High: ${:.9}", state.data.amm_info.price_5s_high),
         format!("5s Low: ${:.9}", state.data.amm_info.price_5s_low),
         format!("Since Inception: {:.2}%", state.data.amm_info.price_inception_change),
-        format!("Price Oracle: AMM-based"),
-    ];
+        "Price Oracle: AMM-based".to_string()];
 
     let price_paragraph = Paragraph::new(price_info.join("\n"))
         .style(Style::default().fg(Color::White))
@@ -564,15 +491,13 @@ fn render_amm_tab(f: &mut tui::Frame<CrosstermBackend<std::io::Stdout>>, area: R
     f.render_widget(price_paragraph, right_chunks[0]);
 
     // Volume analysis with 5s and inception metrics
-    let volume_info = vec![
-        format!("5s Volume: ${:.9}", state.data.amm_info.volume_5s),
+    let volume_info = [format!("5s Volume: ${:.9}", state.data.amm_info.volume_5s),
         format!("Total Volume: ${:.9}", state.data.amm_info.volume_total),
         format!("Volume/Liquidity: {:.2}%", pool_utilization),
-        format!("Active Traders: 1000 wallets"),
-        format!("Whale Activity: MODERATE"),
+        "Active Traders: 1000 wallets".to_string(),
+        "Whale Activity: MODERATE".to_string(),
         format!("Inception High: ${:.9}", state.data.amm_info.price_inception_high),
-        format!("Inception Low: ${:.9}", state.data.amm_info.price_inception_low),
-    ];
+        format!("Inception Low: ${:.9}", state.data.amm_info.price_inception_low)];
 
     let volume_paragraph = Paragraph::new(volume_info.join("\n"))
         .style(Style::default().fg(Color::White))
@@ -628,6 +553,47 @@ fn render_amm_tab(f: &mut tui::Frame<CrosstermBackend<std::io::Stdout>>, area: R
 }
 
 // Render block details panel (right side)
+// Verify the inclusion proofs of a block's transactions against its tx root
+fn transaction_proof_lines(block: &BlockInfo) -> Vec<String> {
+    let mut lines = vec![format!("Tx Root: {}...", &block.tx_root[..block.tx_root.len().min(32)]),
+        format!("State Root: {}...", &block.state_root[..block.state_root.len().min(32)])];
+
+    if block.transaction_proofs.is_empty() {
+        lines.push("Tx Proofs: none (system event block)".to_string());
+        return lines;
+    }
+
+    for proof in &block.transaction_proofs {
+        let status = match proof.verify(&block.tx_root) {
+            Ok(()) => "VERIFIED".to_string(),
+            Err(e) => format!("FAILED ({})", e),
+        };
+        lines.push(format!("Tx #{} {}...: {} step(s), {}",
+            proof.tx_index + 1, &proof.tx_hash[..proof.tx_hash.len().min(16)], proof.steps.len(), status));
+    }
+    lines
+}
+
+// Verify a wallet's balance proof against the state root of the block it was taken at
+fn balance_proof_lines(wallet: &WalletInfo, blocks: &[BlockInfo]) -> Vec<String> {
+    let proof = match &wallet.balance_proof {
+        Some(proof) => proof,
+        None => return vec!["Balance Proof: not available".to_string()],
+    };
+
+    let status = match blocks.iter().rev().find(|block| block.id == proof.block_id) {
+        Some(block) => match proof.verify(&block.state_root) {
+            Ok(()) => "VERIFIED".to_string(),
+            Err(e) => format!("FAILED ({})", e),
+        },
+        None => "UNVERIFIED (block not loaded)".to_string(),
+    };
+
+    vec![format!("Balance Proof @ Block #{}: {}", proof.block_id, status),
+        format!("  Proven ZUX: {:.9} | USDZ: {:.9}", proof.balance("ZUX"), proof.balance("USDZ")),
+        format!("  Path: {} non-empty sibling(s) of 256", proof.siblings.len())]
+}
+
 fn render_block_details_panel(f: &mut tui::Frame<CrosstermBackend<std::io::Stdout>>, area: Rect, state: &ExplorerState) {
     if !state.data.blocks.is_empty() && state.selected_block_index < state.data.blocks.len() {
         // Get the selected block from the reversed list (newest first display)
@@ -636,7 +602,7 @@ fn render_block_details_panel(f: &mut tui::Frame<CrosstermBackend<std::io::Stdou
             let chunks = Layout::default()
                 .direction(Direction::Vertical)
                 .constraints([
-                    Constraint::Length(10), // Basic block information
+                    Constraint::Length(11), // Basic block information
                     Constraint::Length(9),  // Complete hash information
                     Constraint::Length(8),  // Technical mining details
                     Constraint::Length(7),  // Network and validation info
@@ -644,23 +610,18 @@ fn render_block_details_panel(f: &mut tui::Frame<CrosstermBackend<std::io::Stdou
                 ])
                 .split(area);
 
-            // Comprehensive basic block information
-            let time_since_creation = SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap()
-                .as_secs()
-                .saturating_sub(block.timestamp);
+            // Comprehensive basic block information, aged by the simulation's clock
+            let time_since_creation = state.data.last_update.saturating_sub(block.timestamp);
 
-            let basic_info = vec![
-                format!("Block ID: #{}", block.id),
+            let basic_info = [format!("Block ID: #{}", block.id),
                 format!("Network: {}", block.network_name),
                 format!("Version: {}", block.version),
+                format!("Producer: {}", block.producer),
                 format!("Timestamp: {}", block.timestamp),
                 format!("Created: {}", block.formatted_time),
                 format!("Age: {}s ago", time_since_creation),
                 format!("Transactions: {} included", block.transactions_count),
-                format!("Block Size: {} bytes", block.size_bytes),
-            ];
+                format!("Block Size: {} bytes", block.size_bytes)];
 
             let basic_paragraph = Paragraph::new(basic_info.join("\n"))
                 .style(Style::default().fg(Color::White))
@@ -674,15 +635,13 @@ fn render_block_details_panel(f: &mut tui::Frame<CrosstermBackend<std::io::Stdou
             f.render_widget(basic_paragraph, chunks[0]);
 
             // Complete hash information with full details
-            let hash_info = vec![
-                format!("Block Hash (SHA-256):"),
+            let hash_info = ["Block Hash (SHA-256):".to_string(),
                 format!("  {}", &block.hash[..32]),
                 format!("  {}", &block.hash[32..]),
-                format!("Parent Block Hash:"),
+                "Parent Block Hash:".to_string(),
                 format!("  {}", &block.parent_hash[..32]),
                 format!("  {}", if block.parent_hash.len() > 32 { &block.parent_hash[32..] } else { "" }),
-                format!("Hash Algorithm: SHA-256"),
-            ];
+                "Hash Algorithm: SHA-256".to_string()];
 
             let hash_paragraph = Paragraph::new(hash_info.join("\n"))
                 .style(Style::default().fg(Color::White))
@@ -695,39 +654,38 @@ fn render_block_details_panel(f: &mut tui::Frame<CrosstermBackend<std::io::Stdou
             
             f.render_widget(hash_paragraph, chunks[1]);
 
-            // Detailed technical mining information
-            let mining_time = if block.id > 0 { time_since_creation.min(60) } else { 0 };
-            let hash_rate_estimate = if mining_time > 0 { block.difficulty as f64 / mining_time as f64 } else { 0.0 };
-            
-            let tech_info = vec![
-                format!("Mining Difficulty: {}", block.difficulty),
-                format!("Nonce Value: {}", block.nonce),
-                format!("Estimated Hash Rate: {:.2} H/s", hash_rate_estimate),
-                format!("Mining Algorithm: Proof of Work"),
-                format!("Block Reward: Calculated"),
-                format!("Mining Time: ~{}s", mining_time),
-            ];
+            // Detailed technical sealing information; signed engines seal with the producer's signature
+            let mut tech_info = vec![format!("Seal: {}", state.data.consensus.name())];
+            if block.signature.is_empty() {
+                tech_info.extend([format!("Mining Difficulty: {:.0} expected hashes", block.difficulty),
+                    format!("Target: {}...", &block.target[..block.target.len().min(24)]),
+                    format!("Nonce Value: {}", block.nonce),
+                    format!("Network Hash Rate: {:.2} H/s", state.data.system_wallet.network_hash_rate)]);
+            } else {
+                tech_info.extend(["Producer Signature (Ed25519):".to_string(),
+                    format!("  {}", &block.signature[..block.signature.len().min(64)]),
+                    format!("  {}", block.signature.get(64..).unwrap_or(""))]);
+            }
+            tech_info.push(format!("Block Reward: {} ZUX + {} ZUX fees", block.reward, block.fees));
 
             let tech_paragraph = Paragraph::new(tech_info.join("\n"))
                 .style(Style::default().fg(Color::White))
                 .block(
                     Block::default()
                         .borders(Borders::ALL)
-                        .title("Mining & Technical Details")
+                        .title("Sealing & Technical Details")
                         .style(Style::default().fg(Color::LightBlue).add_modifier(Modifier::BOLD))
                 );
             
             f.render_widget(tech_paragraph, chunks[2]);
 
             // Network and validation information
-            let validation_info = vec![
-                format!("Network: ZUX Blockchain"),
-                format!("Consensus: Proof of Work"),
-                format!("Signature Algorithm: Ed25519"),
-                format!("Hash Function: SHA-256"),
-                format!("Block Status: CONFIRMED"),
-                format!("Confirmations: {}", state.data.blocks.len().saturating_sub(block.id as usize)),
-            ];
+            let validation_info = ["Network: ZUX Blockchain".to_string(),
+                format!("Consensus: {}", state.data.consensus.name()),
+                "Signature Algorithm: Ed25519".to_string(),
+                "Hash Function: SHA-256".to_string(),
+                "Block Status: CONFIRMED".to_string(),
+                format!("Confirmations: {}", state.data.blocks.len().saturating_sub(block.id as usize))];
 
             let validation_paragraph = Paragraph::new(validation_info.join("\n"))
                 .style(Style::default().fg(Color::White))
@@ -741,12 +699,10 @@ fn render_block_details_panel(f: &mut tui::Frame<CrosstermBackend<std::io::Stdou
             f.render_widget(validation_paragraph, chunks[3]);
 
             // Status and position in blockchain
-            let additional_info = vec![
-                format!("Position in Chain: {} of {}", state.selected_block_index + 1, state.data.blocks.len()),
-                format!("Block Explorer: ZUX Network"),
-                format!("Data Integrity: VERIFIED"),
-                format!("Immutable: YES"),
-            ];
+            let mut additional_info = vec![format!("Position in Chain: {} of {}", state.selected_block_index + 1, state.data.blocks.len()),
+                "Block Explorer: ZUX Network".to_string(),
+                "Immutable: YES".to_string()];
+            additional_info.extend(transaction_proof_lines(block));
 
             let additional_paragraph = Paragraph::new(additional_info.join("\n"))
                 .style(Style::default().fg(Color::LightBlue))
@@ -923,13 +879,11 @@ fn render_wallet_details_panel(f: &mut tui::Frame<CrosstermBackend<std::io::Stdo
                 .unwrap_or_default()
                 .format("%Y-%m-%d %H:%M:%S");
 
-            let basic_info = vec![
-                format!("Address: {}", wallet.address),
+            let basic_info = [format!("Address: {}", wallet.address),
                 format!("Type: {}", wallet_type_full),
                 format!("Last Activity: {}", creation_time),
                 format!("Total Transactions: {}", wallet.transaction_count),
-                format!("Status: ACTIVE"),
-            ];
+                "Status: ACTIVE".to_string()];
 
             let basic_paragraph = Paragraph::new(basic_info.join("\n"))
                 .style(Style::default().fg(Color::White))
@@ -944,20 +898,18 @@ fn render_wallet_details_panel(f: &mut tui::Frame<CrosstermBackend<std::io::Stdo
 
             // Balance and value details
             let current_zux_price = state.data.amm_info.current_price;
-            let zux_value_in_usd = wallet.zux_balance * current_zux_price;
-            let total_usd_value = zux_value_in_usd + wallet.usdz_balance;
+            let zux_value_in_usd = wallet.zux_balance.to_f64() * current_zux_price;
+            let total_usd_value = zux_value_in_usd + wallet.usdz_balance.to_f64();
             
-            let balance_info = vec![
-                format!("ZUX Balance: {:.9}", wallet.zux_balance),
+            let balance_info = [format!("ZUX Balance: {:.9}", wallet.zux_balance),
                 format!("ZUX Value (USD): ${:.9}", zux_value_in_usd),
                 format!("USDZ Balance: {:.9}", wallet.usdz_balance),
                 format!("Total USD Value: ${:.9}", total_usd_value),
-                format!("Portfolio Distribution:"),
+                "Portfolio Distribution:".to_string(),
                 format!("  ZUX: {:.1}% | USDZ: {:.1}%", 
                     if total_usd_value > 0.0 { (zux_value_in_usd / total_usd_value) * 100.0 } else { 0.0 },
-                    if total_usd_value > 0.0 { (wallet.usdz_balance / total_usd_value) * 100.0 } else { 0.0 }
-                ),
-            ];
+                    if total_usd_value > 0.0 { (wallet.usdz_balance.to_f64() / total_usd_value) * 100.0 } else { 0.0 }
+                )];
 
             let balance_paragraph = Paragraph::new(balance_info.join("\n"))
                 .style(Style::default().fg(Color::White))
@@ -973,7 +925,7 @@ fn render_wallet_details_panel(f: &mut tui::Frame<CrosstermBackend<std::io::Stdo
             // Trading information
             let avg_trade_size = if wallet.transaction_count > 0 {
                 // Average trade size should be based on trading volume, not total wallet value
-                (wallet.zux_balance + wallet.usdz_balance) / (wallet.transaction_count as f64 * 2.0)
+                (wallet.zux_balance.to_f64() + wallet.usdz_balance.to_f64()) / (wallet.transaction_count as f64 * 2.0)
             } else {
                 0.0
             };
@@ -986,8 +938,7 @@ fn render_wallet_details_panel(f: &mut tui::Frame<CrosstermBackend<std::io::Stdo
                 else { base_profit * 0.35 }
             } else { 0.0 };
 
-            let trading_info = vec![
-                format!("Avg Trade Size: ${:.9}", avg_trade_size),
+            let trading_info = [format!("Avg Trade Size: ${:.9}", avg_trade_size),
                 format!("Current ZUX Price: ${:.9}", current_zux_price),
                 format!("Total Profit/Loss: ${:.9}", profitability),
                 format!("Profitability: {:.2}%", (profitability / total_usd_value.max(1.0)) * 100.0),
@@ -995,8 +946,7 @@ fn render_wallet_details_panel(f: &mut tui::Frame<CrosstermBackend<std::io::Stdo
                     if wallet.is_mega_whale { "Conservative High-Volume" }
                     else if wallet.is_whale { "Balanced Growth" }
                     else { "Active Trading" }
-                ),
-            ];
+                )];
 
             let trading_paragraph = Paragraph::new(trading_info.join("\n"))
                 .style(Style::default().fg(Color::White))
@@ -1039,18 +989,13 @@ fn render_wallet_details_panel(f: &mut tui::Frame<CrosstermBackend<std::io::Stdo
             f.render_widget(trades_paragraph, chunks[3]);
 
             // Status and additional information
-            let time_since_activity = SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap()
-                .as_secs()
-                .saturating_sub(wallet.last_activity);
-
-            let status_info = vec![
-                format!("Network Status: CONNECTED"),
+            let time_since_activity = state.data.last_update.saturating_sub(wallet.last_activity);
+
+            let mut status_info = vec!["Network Status: CONNECTED".to_string(),
                 format!("Time Since Activity: {}s", time_since_activity),
                 format!("Wallet Rank: {} of {}", state.selected_wallet_index + 1, state.data.wallets.len()),
-                format!("Security: Ed25519 Verified"),
-            ];
+                "Security: Ed25519 Verified".to_string()];
+            status_info.extend(balance_proof_lines(wallet, &state.data.blocks));
 
             let status_paragraph = Paragraph::new(status_info.join("\n"))
                 .style(Style::default().fg(Color::LightBlue))
@@ -1093,7 +1038,7 @@ fn render_system_tab(f: &mut tui::Frame<CrosstermBackend<std::io::Stdout>>, area
     let left_chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(11), // System wallet comprehensive info
+            Constraint::Length(13), // System wallet comprehensive info
             Constraint::Length(10), // Token economics
             Constraint::Min(6),     // Economic metrics
         ])
@@ -1110,13 +1055,13 @@ fn render_system_tab(f: &mut tui::Frame<CrosstermBackend<std::io::Stdout>>, area
         .split(main_chunks[1]);
 
     // Comprehensive system wallet information - separate currencies
-    let distributed_zux = (1000.0 * 100.0) + state.data.amm_info.zux_reserve; // Wallets + AMM
-    let distributed_usdz = (1000.0 * 500.0) + state.data.amm_info.usd_reserve; // Wallets + AMM
-    let zux_circulation_ratio = if state.data.system_wallet.total_issued_zux > 0.0 { 
-        (distributed_zux / state.data.system_wallet.total_issued_zux) * 100.0 
+    let distributed_zux = (1000.0 * 100.0) + state.data.amm_info.zux_reserve.to_f64(); // Wallets + AMM
+    let distributed_usdz = (1000.0 * 500.0) + state.data.amm_info.usd_reserve.to_f64(); // Wallets + AMM
+    let zux_circulation_ratio = if !state.data.system_wallet.total_issued_zux.is_zero() { 
+        (distributed_zux / state.data.system_wallet.total_issued_zux.to_f64()) * 100.0 
     } else { 0.0 };
-    let usdz_circulation_ratio = if state.data.system_wallet.total_issued_usdz > 0.0 { 
-        (distributed_usdz / state.data.system_wallet.total_issued_usdz) * 100.0 
+    let usdz_circulation_ratio = if !state.data.system_wallet.total_issued_usdz.is_zero() { 
+        (distributed_usdz / state.data.system_wallet.total_issued_usdz.to_f64()) * 100.0 
     } else { 0.0 };
     
     let system_info = vec![
@@ -1125,6 +1070,8 @@ fn render_system_tab(f: &mut tui::Frame<CrosstermBackend<std::io::Stdout>>, area
         format!("System USDZ Balance: {:.9}", state.data.system_wallet.usdz_balance),
         format!("Total ZUX Issued: {:.9}", state.data.system_wallet.total_issued_zux),
         format!("Total USDZ Issued: {:.9}", state.data.system_wallet.total_issued_usdz),
+        format!("Block Rewards Issued: {:.9} ZUX", state.data.system_wallet.block_rewards_zux),
+        format!("Network Fees Paid: {:.9} ZUX", state.data.system_wallet.network_fees_zux),
         format!("ZUX Circulation: {:.9} ({:.3}%)", distributed_zux, zux_circulation_ratio),
         format!("USDZ Circulation: {:.9} ({:.3}%)", distributed_usdz, usdz_circulation_ratio),
         format!("Active Wallets: 1000"),
@@ -1147,16 +1094,14 @@ fn render_system_tab(f: &mut tui::Frame<CrosstermBackend<std::io::Stdout>>, area
     let zux_market_cap = distributed_zux * state.data.amm_info.current_price;
     let total_market_cap = zux_market_cap + distributed_usdz; // USDZ is 1:1 with USD
     
-    let economics_info = vec![
-        format!("ZUX Market Cap: ${:.9}", zux_market_cap),
+    let economics_info = [format!("ZUX Market Cap: ${:.9}", zux_market_cap),
         format!("Total Market Cap: ${:.9}", total_market_cap),
         format!("ZUX in Circulation: {:.9}", distributed_zux),
         format!("USDZ in Circulation: {:.9}", distributed_usdz),
-        format!("Token Standard: Native"),
-        format!("Monetary Policy: Fixed Supply"),
-        format!("Trading Mechanism: AMM"),
-        format!("Fee Structure: 0.3% swap fee"),
-    ];
+        "Token Standard: Native".to_string(),
+        "Monetary Policy: Fixed Supply".to_string(),
+        "Trading Mechanism: AMM".to_string(),
+        "Fee Structure: 0.3% swap fee".to_string()];
 
     let economics_paragraph = Paragraph::new(economics_info.join("\n"))
         .style(Style::default().fg(Color::White))
@@ -1170,20 +1115,18 @@ fn render_system_tab(f: &mut tui::Frame<CrosstermBackend<std::io::Stdout>>, area
     f.render_widget(economics_paragraph, left_chunks[1]);
 
     // Economic and performance metrics
-    let avg_tx_per_block = if state.data.blocks.len() > 0 {
+    let avg_tx_per_block = if !state.data.blocks.is_empty() {
         state.data.system_wallet.total_transactions as f64 / state.data.blocks.len() as f64
     } else { 0.0 };
     
     let daily_volume = state.data.amm_info.volume_total;
     let network_value = total_market_cap;
     
-    let metrics_info = vec![
-        format!("Avg Tx per Block: {:.1}", avg_tx_per_block),
+    let metrics_info = [format!("Avg Tx per Block: {:.1}", avg_tx_per_block),
         format!("Total Volume: ${:.9}", daily_volume),
         format!("Network Value: ${:.9}", network_value),
-        format!("Transaction Fees: 0.001 ZUX"),
-        format!("Economic Security: HIGH"),
-    ];
+        "Transaction Fees: 0.001 ZUX".to_string(),
+        "Economic Security: HIGH".to_string()];
 
     let metrics_paragraph = Paragraph::new(metrics_info.join("\n"))
         .style(Style::default().fg(Color::LightBlue))
@@ -1201,15 +1144,13 @@ fn render_system_tab(f: &mut tui::Frame<CrosstermBackend<std::io::Stdout>>, area
         avg_tx_per_block / state.data.system_wallet.avg_block_time
     } else { 0.0 };
     
-    let network_performance = vec![
-        format!("Total Transactions: {}", state.data.system_wallet.total_transactions),
+    let network_performance = [format!("Total Transactions: {}", state.data.system_wallet.total_transactions),
         format!("Network Hash Rate: {:.2} H/s", state.data.system_wallet.network_hash_rate),
         format!("Average Block Time: {:.2}s", state.data.system_wallet.avg_block_time),
         format!("Transactions/Second: {:.2}", tps),
-        format!("Block Size Limit: 1MB"),
-        format!("Network Throughput: OPTIMAL"),
-        format!("Finality Time: ~60s"),
-    ];
+        "Block Size Limit: 1MB".to_string(),
+        "Network Throughput: OPTIMAL".to_string(),
+        "Finality Time: ~60s".to_string()];
 
     let performance_paragraph = Paragraph::new(network_performance.join("\n"))
         .style(Style::default().fg(Color::White))
@@ -1223,14 +1164,12 @@ fn render_system_tab(f: &mut tui::Frame<CrosstermBackend<std::io::Stdout>>, area
     f.render_widget(performance_paragraph, right_chunks[0]);
 
     // Security and consensus information
-    let security_info = vec![
-        format!("Consensus Algorithm: Proof of Work"),
-        format!("Signature Scheme: Ed25519"),
-        format!("Hash Function: SHA-256"),
-        format!("Block Validation: Full Nodes"),
-        format!("Network Security: MAXIMUM"),
-        format!("51% Attack Cost: PROHIBITIVE"),
-    ];
+    let security_info = [format!("Consensus Algorithm: {}", state.data.consensus.name()),
+        "Signature Scheme: Ed25519".to_string(),
+        "Hash Function: SHA-256".to_string(),
+        "Block Validation: Full Nodes".to_string(),
+        "Network Security: MAXIMUM".to_string(),
+        "51% Attack Cost: PROHIBITIVE".to_string()];
 
     let security_paragraph = Paragraph::new(security_info.join("\n"))
         .style(Style::default().fg(Color::White))
@@ -1245,21 +1184,12 @@ fn render_system_tab(f: &mut tui::Frame<CrosstermBackend<std::io::Stdout>>, area
 
     // Enhanced network health and monitoring
     let uptime_percentage = 99.9; // Simulated uptime
-    let time_since_update = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_secs() 
-        .saturating_sub(state.data.last_update);
+    let time_since_update = state.last_update.elapsed().as_secs();
     
-    let health_info = vec![
-        format!("Network Status: OPERATIONAL"),
+    let health_info = ["Network Status: OPERATIONAL".to_string(),
         format!("Network Uptime: {:.3}%", uptime_percentage),
-        format!("Architecture: Single Deterministic Node"),
-        format!("Consensus: In-Memory Proof of Work"),
-        format!("Validation: Deterministic Algorithm"),
-        format!("Memory Usage: ~50MB (In-Memory)"),
-        format!("Last Update: {}s ago", time_since_update),
-    ];
+        format!("Consensus: {}", state.data.consensus.name()),
+        format!("Last Update: {}s ago", time_since_update)];
 
     let health_paragraph = Paragraph::new(health_info.join("\n"))
         .style(Style::default().fg(Color::LightBlue))
@@ -1275,6 +1205,25 @@ fn render_system_tab(f: &mut tui::Frame<CrosstermBackend<std::io::Stdout>>, area
 
 // Main explorer application entry point
 pub fn main() -> io::Result<()> {
+    // Usage: blockchain_explorer [--chain <dir>]
+    // With --chain only the stored chain is shown; otherwise the live feed is
+    // preferred and the default chain store is browsed when there is none.
+    let mut chain_dir = PathBuf::from(DEFAULT_DATA_DIR);
+    let mut chain_only = false;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match (arg.as_str(), args.next()) {
+            ("--chain", Some(dir)) => {
+                chain_dir = PathBuf::from(dir);
+                chain_only = true;
+            },
+            _ => {
+                eprintln!("Usage: blockchain_explorer [--chain <dir>]");
+                std::process::exit(2);
+            }
+        }
+    }
+
     // Initialize terminal for TUI
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen, Hide)?;
@@ -1351,9 +1300,12 @@ pub fn main() -> io::Result<()> {
     let es2 = explorer_state.clone();
     
     thread::spawn(move || {
+        let mut chain_view: Option<ChainView> = None;
+        
         while *r2.lock().unwrap() {
-            match File::open(explorer_data_path) {
-                Ok(file) => {
+            let live_feed = if chain_only { None } else { File::open(explorer_data_path).ok() };
+            match live_feed {
+                Some(file) => {
                     let reader = BufReader::new(file);
                     match serde_json::from_reader::<_, ExplorerData>(reader) {
                         Ok(data) => {
@@ -1367,8 +1319,29 @@ pub fn main() -> io::Result<()> {
                         }
                     }
                 },
-                Err(_) => {
-                    // File doesn't exist yet, wait for main application to create it
+                None => {
+                    // No live feed: browse the stored chain, applying only the blocks appended since the last look
+                    // (a chain continued from a snapshot starts from that snapshot's ledger)
+                    if chain_view.is_none() {
+                        chain_view = base_state(&chain_dir).ok().map(ChainView::new);
+                    }
+                    if let (Ok(store), Some(view)) = (FileStore::open_read_only(&chain_dir), chain_view.as_mut()) {
+                        match view.sync(&store) {
+                            Ok(true) => {
+                                if let Ok(data) = view.explorer_data(SystemClock.now()) {
+                                    let mut state = es2.lock().unwrap();
+                                    state.data = data;
+                                    state.validate_selection_indices();
+                                    state.last_update = Instant::now();
+                                }
+                            },
+                            Ok(false) => {},
+                            Err(_) => {
+                                // A different chain replaced the one being shown, start over
+                                chain_view = None;
+                            },
+                        }
+                    }
                 }
             }
             
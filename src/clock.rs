@@ -0,0 +1,79 @@
+//! Sources of time for the simulation
+//!
+//! Whatever needs the current time asks a `Clock` rather than the operating
+//! system: block and transaction timestamps, swap deadlines, trading decisions,
+//! the market feed and the explorer data. The ledger reads no clock at all; it
+//! executes every block, pool statistics included, at the block's timestamp.
+//!
+//! `SystemClock` follows the wall clock. `SimulatedClock` only moves when told
+//! to, by a fixed step every time a block is produced, so a run's timestamps
+//! and time-based metrics do not depend on how fast the machine is and can be
+//! replayed.
+
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A source of the current time
+pub trait Clock: Debug + Send + Sync {
+    /// Milliseconds since the Unix epoch
+    fn now_millis(&self) -> u64;
+
+    /// Seconds since the Unix epoch
+    fn now(&self) -> u64 {
+        self.now_millis() / 1_000
+    }
+
+    /// A block was produced; a simulated clock moves forward, the wall clock ignores it
+    fn tick(&self) {}
+}
+
+/// The operating system's wall clock
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_millis(&self) -> u64 {
+        // A clock set before 1970 reads as the epoch
+        SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_millis() as u64)
+    }
+}
+
+/// A clock that advances by `step_millis` on every tick, and otherwise only when advanced
+#[derive(Debug)]
+pub struct SimulatedClock {
+    millis: AtomicU64,
+    step_millis: u64,
+}
+
+impl SimulatedClock {
+    /// Clock reading `start_millis` since the Unix epoch
+    pub fn new(start_millis: u64, step_millis: u64) -> Self {
+        SimulatedClock { millis: AtomicU64::new(start_millis), step_millis }
+    }
+
+    /// Time the clock moves forward on every tick, in milliseconds
+    pub fn step_millis(&self) -> u64 {
+        self.step_millis
+    }
+
+    /// Move the clock forward by `millis`
+    pub fn advance(&self, millis: u64) {
+        self.millis.fetch_add(millis, Ordering::Relaxed);
+    }
+
+    /// Move the clock to `millis` since the Unix epoch, unless it is already past it
+    pub fn advance_to(&self, millis: u64) {
+        self.millis.fetch_max(millis, Ordering::Relaxed);
+    }
+}
+
+impl Clock for SimulatedClock {
+    fn now_millis(&self) -> u64 {
+        self.millis.load(Ordering::Relaxed)
+    }
+
+    fn tick(&self) {
+        self.advance(self.step_millis);
+    }
+}
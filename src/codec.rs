@@ -0,0 +1,201 @@
+//! Canonical binary encoding
+//!
+//! Everything that is hashed, signed or written to the chain store is encoded
+//! the same way: a leading format version byte, then each field in a fixed
+//! order. Integers are big-endian, floats are their IEEE-754 bits, booleans are
+//! one byte, and byte strings, text and sequences carry a `u32` length prefix,
+//! so no two different values share an encoding.
+
+use crate::error::{BlockchainError, Result};
+
+/// Current encoding format version, the first byte of every encoding
+pub const ENCODING_VERSION: u8 = 2;
+
+/// Types with a canonical binary encoding
+pub trait Encode {
+    /// Append the fields of `self` to `out`
+    fn encode(&self, out: &mut Encoder);
+}
+
+/// Types that can be read back from their canonical encoding
+pub trait Decode: Sized {
+    /// Read a value written by [`Encode::encode`]
+    fn decode(input: &mut Decoder) -> Result<Self>;
+}
+
+/// Versioned canonical encoding of `value`
+pub fn to_bytes<T: Encode + ?Sized>(value: &T) -> Vec<u8> {
+    let mut out = Encoder::new();
+    value.encode(&mut out);
+    out.into_bytes()
+}
+
+/// Decode a value from exactly `bytes`, rejecting other versions and trailing data
+pub fn from_bytes<T: Decode>(bytes: &[u8]) -> Result<T> {
+    let mut input = Decoder::new(bytes)?;
+    let value = T::decode(&mut input)?;
+    input.finish()?;
+    Ok(value)
+}
+
+/// Builds a canonical encoding
+#[derive(Debug, Clone)]
+pub struct Encoder {
+    bytes: Vec<u8>,
+}
+
+impl Encoder {
+    /// Start an encoding with the current version byte
+    pub fn new() -> Self {
+        Encoder { bytes: vec![ENCODING_VERSION] }
+    }
+
+    pub fn put_u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    pub fn put_bool(&mut self, value: bool) {
+        self.bytes.push(value as u8);
+    }
+
+    pub fn put_u16(&mut self, value: u16) {
+        self.bytes.extend_from_slice(&value.to_be_bytes());
+    }
+
+    pub fn put_u32(&mut self, value: u32) {
+        self.bytes.extend_from_slice(&value.to_be_bytes());
+    }
+
+    pub fn put_u64(&mut self, value: u64) {
+        self.bytes.extend_from_slice(&value.to_be_bytes());
+    }
+
+    pub fn put_u128(&mut self, value: u128) {
+        self.bytes.extend_from_slice(&value.to_be_bytes());
+    }
+
+    pub fn put_f64(&mut self, value: f64) {
+        self.put_u64(value.to_bits());
+    }
+
+    /// Number of elements of a sequence that follows
+    pub fn put_seq_len(&mut self, len: usize) {
+        let len = u32::try_from(len).expect("encoded sequence longer than u32::MAX");
+        self.put_u32(len);
+    }
+
+    /// Length-prefixed byte string
+    pub fn put_bytes(&mut self, value: &[u8]) {
+        self.put_seq_len(value.len());
+        self.bytes.extend_from_slice(value);
+    }
+
+    /// Length-prefixed UTF-8 text
+    pub fn put_str(&mut self, value: &str) {
+        self.put_bytes(value.as_bytes());
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+impl Default for Encoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Reads a canonical encoding
+#[derive(Debug)]
+pub struct Decoder<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Decoder<'a> {
+    /// Start reading an encoding, checking its version byte
+    pub fn new(bytes: &'a [u8]) -> Result<Self> {
+        let mut input = Decoder { bytes };
+        let version = input.u8()?;
+        if version != ENCODING_VERSION {
+            return Err(BlockchainError::Encoding(
+                format!("Unsupported encoding version {} (expected {})", version, ENCODING_VERSION)
+            ));
+        }
+        Ok(input)
+    }
+
+    /// Check that the whole encoding has been read
+    pub fn finish(self) -> Result<()> {
+        if !self.bytes.is_empty() {
+            return Err(BlockchainError::Encoding(format!("{} unexpected trailing bytes", self.bytes.len())));
+        }
+        Ok(())
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        if len > self.bytes.len() {
+            return Err(BlockchainError::Encoding(
+                format!("Unexpected end of data: needed {} bytes, {} left", len, self.bytes.len())
+            ));
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+
+    pub fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub fn bool(&mut self) -> Result<bool> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            other => Err(BlockchainError::Encoding(format!("Invalid boolean byte {}", other))),
+        }
+    }
+
+    pub fn u16(&mut self) -> Result<u16> {
+        Ok(u16::from_be_bytes(self.array()?))
+    }
+
+    pub fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_be_bytes(self.array()?))
+    }
+
+    pub fn u64(&mut self) -> Result<u64> {
+        Ok(u64::from_be_bytes(self.array()?))
+    }
+
+    pub fn u128(&mut self) -> Result<u128> {
+        Ok(u128::from_be_bytes(self.array()?))
+    }
+
+    pub fn f64(&mut self) -> Result<f64> {
+        Ok(f64::from_bits(self.u64()?))
+    }
+
+    /// Number of elements of a sequence that follows
+    pub fn seq_len(&mut self) -> Result<usize> {
+        Ok(self.u32()? as usize)
+    }
+
+    pub fn bytes(&mut self) -> Result<Vec<u8>> {
+        let len = self.seq_len()?;
+        Ok(self.take(len)?.to_vec())
+    }
+
+    pub fn string(&mut self) -> Result<String> {
+        String::from_utf8(self.bytes()?)
+            .map_err(|e| BlockchainError::Encoding(format!("Invalid UTF-8 text: {}", e)))
+    }
+}
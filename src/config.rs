@@ -0,0 +1,192 @@
+//! Scenario parameters of a simulated run
+//!
+//! The economy a new chain starts with (network name, genesis supply, wallet
+//! count and credits, share of whales), the AMM pool it seeds (liquidity
+//! fraction, target price and fee) and the length and pacing of the trading
+//! phase. A scenario is read
+//! from a TOML or JSON file, fields it leaves out keep their defaults, and
+//! single fields can be overridden by name, which is what the command line
+//! flags of `practicerust2` do.
+
+use std::path::Path;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::agent::AgentMix;
+use crate::amm::BASIS_POINTS;
+use crate::amount::Amount;
+use crate::block::INCEPTION_YEAR;
+use crate::error::{BlockchainError, Result};
+
+// Ratios are applied to amounts as parts per trillion
+const RATIO_SCALE: f64 = 1e12;
+
+/// Parameters of a simulated run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SimulationConfig {
+    pub network_name: String,        // Chain id every transaction is signed for
+    pub inception_year: u16,         // Recorded in every block header
+    pub zux_supply: Amount,          // ZUX allocated to the system wallet at genesis
+    pub usdz_supply: Amount,         // USDZ allocated to the system wallet at genesis
+    pub wallet_count: usize,         // Trading wallets registered during setup
+    pub zux_credit: Amount,          // ZUX the system wallet credits to each of them
+    pub usdz_credit: Amount,         // USDZ the system wallet credits to each of them
+    pub whale_probability: f64,      // Chance a trading wallet is a whale
+    pub mega_whale_probability: f64, // Chance a trading wallet is a mega whale
+    pub liquidity_fraction: f64,     // Share of the system wallet's remaining ZUX deposited in the pool
+    pub target_price: f64,           // USDZ per ZUX the pool opens at
+    pub fee_percent: f64,            // Swap fee kept by the pool, in whole basis points
+    pub total_transactions: u64,     // Swaps executed before the run ends
+    pub block_delay_ms: u64,         // Pause after each swap block
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        SimulationConfig {
+            network_name: "ZUX-Testnet".to_string(),
+            inception_year: INCEPTION_YEAR,
+            zux_supply: Amount::from_tokens(1_000_000_000),
+            usdz_supply: Amount::from_tokens(5_000_000_000),
+            wallet_count: 1000,
+            zux_credit: Amount::from_tokens(100),
+            usdz_credit: Amount::from_tokens(500),
+            whale_probability: AgentMix::default().whale_probability,
+            mega_whale_probability: AgentMix::default().mega_whale_probability,
+            liquidity_fraction: 0.0001, // A microscopic pool for extreme volatility
+            target_price: 0.01,
+            fee_percent: 0.3,
+            total_transactions: 10_000,
+            block_delay_ms: 5,
+        }
+    }
+}
+
+/// Parse `value` for the field `key`
+fn parse<T: FromStr>(key: &str, value: &str) -> Result<T> where T::Err: std::fmt::Display {
+    value.parse().map_err(|e| BlockchainError::Config(format!("Invalid {} {:?}: {}", key, value, e)))
+}
+
+/// `amount * ratio`, rounded down
+fn scale(amount: Amount, ratio: f64) -> Result<Amount> {
+    amount.mul_div((ratio * RATIO_SCALE).round() as u128, RATIO_SCALE as u128)
+}
+
+impl SimulationConfig {
+    /// Names of the fields a scenario can set
+    pub const KEYS: [&'static str; 14] = [
+        "network_name", "inception_year", "zux_supply", "usdz_supply", "wallet_count", "zux_credit", "usdz_credit",
+        "whale_probability", "mega_whale_probability", "liquidity_fraction", "target_price", "fee_percent",
+        "total_transactions", "block_delay_ms",
+    ];
+
+    /// Read a scenario file, JSON if its extension is `.json` and TOML otherwise
+    ///
+    /// The scenario is not validated, so flags can still correct it.
+    pub fn read_from(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)?;
+        let invalid = |e: &dyn std::fmt::Display| BlockchainError::Config(format!("Invalid scenario {}: {}", path.display(), e));
+        if path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("json")) {
+            serde_json::from_str(&text).map_err(|e| invalid(&e))
+        } else {
+            toml::from_str(&text).map_err(|e| invalid(&e))
+        }
+    }
+
+    /// The scenario as a TOML file it can be read back from
+    pub fn to_toml(&self) -> Result<String> {
+        toml::to_string(self).map_err(|e| BlockchainError::Config(format!("Failed to serialize scenario: {}", e)))
+    }
+
+    /// Set the field `key` from its textual value
+    pub fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "network_name" => self.network_name = value.to_string(),
+            "inception_year" => self.inception_year = parse(key, value)?,
+            "zux_supply" => self.zux_supply = parse(key, value)?,
+            "usdz_supply" => self.usdz_supply = parse(key, value)?,
+            "wallet_count" => self.wallet_count = parse(key, value)?,
+            "zux_credit" => self.zux_credit = parse(key, value)?,
+            "usdz_credit" => self.usdz_credit = parse(key, value)?,
+            "whale_probability" => self.whale_probability = parse(key, value)?,
+            "mega_whale_probability" => self.mega_whale_probability = parse(key, value)?,
+            "liquidity_fraction" => self.liquidity_fraction = parse(key, value)?,
+            "target_price" => self.target_price = parse(key, value)?,
+            "fee_percent" => self.fee_percent = parse(key, value)?,
+            "total_transactions" => self.total_transactions = parse(key, value)?,
+            "block_delay_ms" => self.block_delay_ms = parse(key, value)?,
+            _ => return Err(BlockchainError::Config(format!("Unknown scenario field '{}'", key))),
+        }
+        Ok(())
+    }
+
+    /// Swap fee in basis points
+    pub fn fee_bps(&self) -> u32 {
+        (self.fee_percent * 100.0).round() as u32
+    }
+
+    /// Whale odds the trading wallets' strategies are drawn with
+    pub fn agent_mix(&self) -> AgentMix {
+        AgentMix {
+            whale_probability: self.whale_probability,
+            mega_whale_probability: self.mega_whale_probability,
+        }
+    }
+
+    /// ZUX and USDZ the system wallet deposits in the pool out of `remaining_zux`
+    pub fn pool_deposit(&self, remaining_zux: Amount) -> Result<(Amount, Amount)> {
+        let zux = scale(remaining_zux, self.liquidity_fraction)?;
+        Ok((zux, scale(zux, self.target_price)?))
+    }
+
+    /// Check that a new chain can be set up and traded on with these parameters
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |message: String| Err(BlockchainError::Config(message));
+        if self.network_name.trim().is_empty() {
+            return invalid("network_name must not be empty".to_string());
+        }
+        if self.wallet_count == 0 {
+            return invalid("wallet_count must be at least 1".to_string());
+        }
+        if self.total_transactions == 0 {
+            return invalid("total_transactions must be at least 1".to_string());
+        }
+        for (key, probability) in [("whale_probability", self.whale_probability), ("mega_whale_probability", self.mega_whale_probability)] {
+            if !(0.0..=1.0).contains(&probability) {
+                return invalid(format!("{} must be in [0, 1], not {}", key, probability));
+            }
+        }
+        if !(self.liquidity_fraction > 0.0 && self.liquidity_fraction <= 1.0) {
+            return invalid(format!("liquidity_fraction must be in (0, 1], not {}", self.liquidity_fraction));
+        }
+        if !(self.target_price.is_finite() && self.target_price > 0.0) {
+            return invalid(format!("target_price must be a positive number, not {}", self.target_price));
+        }
+        let fee_bps = self.fee_percent * 100.0;
+        if !(fee_bps >= 0.0 && fee_bps < BASIS_POINTS as f64) || (fee_bps - fee_bps.round()).abs() > 1e-6 {
+            return invalid(format!("fee_percent must be a whole number of basis points below 100%, not {}", self.fee_percent));
+        }
+
+        // The system wallet credits every wallet and seeds the pool from what is left
+        let wallets = self.wallet_count as u128;
+        let (Ok(zux_credits), Ok(usdz_credits)) = (self.zux_credit.mul_div(wallets, 1), self.usdz_credit.mul_div(wallets, 1)) else {
+            return invalid("wallet credits overflow".to_string());
+        };
+        let (Ok(remaining_zux), Ok(remaining_usdz)) = (self.zux_supply.checked_sub(zux_credits), self.usdz_supply.checked_sub(usdz_credits)) else {
+            return invalid(format!(
+                "a supply of {} ZUX and {} USDZ cannot credit {} wallets with {} ZUX and {} USDZ",
+                self.zux_supply, self.usdz_supply, self.wallet_count, self.zux_credit, self.usdz_credit
+            ));
+        };
+        match self.pool_deposit(remaining_zux) {
+            Ok((zux, usdz)) if zux.is_zero() || usdz.is_zero() =>
+                invalid(format!("the pool would open with {} ZUX and {} USDZ; raise liquidity_fraction or target_price", zux, usdz)),
+            Ok((_, usdz)) if usdz > remaining_usdz =>
+                invalid(format!("the pool needs {} USDZ but only {} remain after the credits", usdz, remaining_usdz)),
+            Ok(_) => Ok(()),
+            Err(e) => invalid(format!("the pool deposit cannot be computed: {}", e)),
+        }
+    }
+}
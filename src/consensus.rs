@@ -0,0 +1,361 @@
+//! Pluggable consensus engines
+//!
+//! A `Consensus` engine decides who may produce the next block and how that
+//! block is sealed:
+//!
+//! - `ProofOfWork`: anyone may produce a block; its header hash must meet the
+//!   target the ledger expects, found by the `Miner`.
+//! - `ProofOfAuthority`: a fixed list of authorities takes turns, one block
+//!   each, and the scheduled authority signs the header hash with its Ed25519
+//!   key.
+//! - `ProofOfStake`: the producer is drawn from the accounts holding ZUX,
+//!   weighted by balance, using the parent hash as the randomness. The drawn
+//!   account signs the header hash with the key bound to it on chain. Until
+//!   some account holds ZUX, a bootstrap authority produces the blocks.
+//!
+//! The engine is a rule of the chain: the ledger holds its `ConsensusRule` and
+//! checks the seal of every block it applies. Sealing needs node-local
+//! resources, hash power or keys, which a `Sealer` provides.
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::AtomicBool;
+use std::time::Instant;
+
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::amount::Amount;
+use crate::block::Block;
+use crate::error::{BlockchainError, Result};
+use crate::miner::{Miner, MiningStats};
+use crate::state::State;
+
+// Domain tag keeping the stake draw apart from any other hashed message
+const STAKE_DRAW_DOMAIN: &str = "ZUX stake draw";
+
+/// Decides who produces each block and how blocks are sealed
+pub trait Consensus {
+    /// Short name of the engine, for display
+    fn name(&self) -> &'static str;
+
+    /// Account entitled to produce the block on top of `state`, or `None` if any account may
+    fn scheduled_producer(&self, state: &State) -> Result<Option<String>>;
+
+    /// Seal `block`, whose header is otherwise complete
+    ///
+    /// Returns the work done, or `None` if `sealer.cancel` was raised first.
+    fn seal(&self, block: &mut Block, sealer: &Sealer) -> Result<Option<MiningStats>>;
+
+    /// Check the part of the seal of `block` that does not depend on the ledger
+    ///
+    /// Done before a block is given any weight, including blocks on side
+    /// branches that are not executed yet.
+    fn check_seal(&self, block: &Block) -> Result<()>;
+
+    /// Check the seal of `block`, which is about to be applied on top of `state`
+    fn verify_seal(&self, block: &Block, state: &State) -> Result<()>;
+
+    /// Weight `block` adds to its branch; fork choice follows the heaviest branch
+    fn block_weight(&self, block: &Block) -> f64;
+}
+
+/// Node-local means of sealing blocks
+pub struct Sealer<'a> {
+    pub miner: &'a Miner,
+    pub cancel: &'a AtomicBool,
+    pub key: Option<&'a SigningKey>, // Key of the block's producer, needed by signed engines
+}
+
+/// A block signer known to the chain
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Authority {
+    pub address: String,     // Account credited with the blocks it produces
+    pub public_key: Vec<u8>, // Ed25519 key its seals are checked against
+}
+
+/// SHA-256 proof-of-work against the ledger's retargeted target
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofOfWork;
+
+/// Round-robin block signing by a fixed set of authorities
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofOfAuthority {
+    pub authorities: Vec<Authority>, // Block #n is produced by authority n modulo their number
+}
+
+/// Block signing by an account drawn with probability proportional to its ZUX balance
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofOfStake {
+    pub bootstrap: Authority, // Produces the blocks while no account holds ZUX
+}
+
+/// Consensus engine a chain runs under
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConsensusRule {
+    ProofOfWork(ProofOfWork),
+    ProofOfAuthority(ProofOfAuthority),
+    ProofOfStake(ProofOfStake),
+}
+
+impl Default for ConsensusRule {
+    fn default() -> Self {
+        ConsensusRule::ProofOfWork(ProofOfWork)
+    }
+}
+
+impl ConsensusRule {
+    /// The engine implementing this rule
+    pub fn engine(&self) -> &dyn Consensus {
+        match self {
+            ConsensusRule::ProofOfWork(engine) => engine,
+            ConsensusRule::ProofOfAuthority(engine) => engine,
+            ConsensusRule::ProofOfStake(engine) => engine,
+        }
+    }
+
+    /// Kind of engine this rule selects
+    pub fn kind(&self) -> EngineKind {
+        match self {
+            ConsensusRule::ProofOfWork(_) => EngineKind::ProofOfWork,
+            ConsensusRule::ProofOfAuthority(_) => EngineKind::ProofOfAuthority,
+            ConsensusRule::ProofOfStake(_) => EngineKind::ProofOfStake,
+        }
+    }
+}
+
+/// Consensus engine selected by configuration, before its authorities are known
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EngineKind {
+    #[default]
+    #[serde(rename = "pow")]
+    ProofOfWork,
+    #[serde(rename = "poa")]
+    ProofOfAuthority,
+    #[serde(rename = "pos")]
+    ProofOfStake,
+}
+
+impl EngineKind {
+    /// Name the engine is shown with
+    pub fn name(self) -> &'static str {
+        match self {
+            EngineKind::ProofOfWork => "Proof of Work",
+            EngineKind::ProofOfAuthority => "Proof of Authority",
+            EngineKind::ProofOfStake => "Proof of Stake",
+        }
+    }
+}
+
+/// The configuration name: `pow`, `poa` or `pos`
+impl fmt::Display for EngineKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            EngineKind::ProofOfWork => "pow",
+            EngineKind::ProofOfAuthority => "poa",
+            EngineKind::ProofOfStake => "pos",
+        })
+    }
+}
+
+impl FromStr for EngineKind {
+    type Err = BlockchainError;
+
+    fn from_str(text: &str) -> Result<Self> {
+        match text {
+            "pow" => Ok(EngineKind::ProofOfWork),
+            "poa" => Ok(EngineKind::ProofOfAuthority),
+            "pos" => Ok(EngineKind::ProofOfStake),
+            _ => Err(BlockchainError::Config(format!("Unknown consensus engine '{}' (expected pow, poa or pos)", text))),
+        }
+    }
+}
+
+impl Consensus for ProofOfWork {
+    fn name(&self) -> &'static str {
+        EngineKind::ProofOfWork.name()
+    }
+
+    fn scheduled_producer(&self, _state: &State) -> Result<Option<String>> {
+        Ok(None)
+    }
+
+    fn seal(&self, block: &mut Block, sealer: &Sealer) -> Result<Option<MiningStats>> {
+        block.signature.clear();
+        sealer.miner.mine(block, sealer.cancel)
+    }
+
+    fn check_seal(&self, block: &Block) -> Result<()> {
+        let hash: [u8; 32] = hex::decode(&block.hash).ok()
+            .and_then(|hash| hash.try_into().ok())
+            .ok_or_else(|| BlockchainError::Block(format!("Block #{} hash {} is not 32 bytes of hex", block.id, block.hash)))?;
+        if !block.target.is_met_by(&hash) {
+            return Err(BlockchainError::Block(format!("Block #{} hash does not meet its target {}", block.id, block.target)));
+        }
+        if !block.signature.is_empty() {
+            return Err(BlockchainError::Block(format!("Proof-of-work block #{} carries a signature", block.id)));
+        }
+        Ok(())
+    }
+
+    fn verify_seal(&self, block: &Block, _state: &State) -> Result<()> {
+        self.check_seal(block)
+    }
+
+    /// The expected number of hashes behind the block, so the branch with the most work wins
+    fn block_weight(&self, block: &Block) -> f64 {
+        block.difficulty()
+    }
+}
+
+impl ProofOfAuthority {
+    /// Authority whose turn it is to produce block `height`
+    fn authority_at(&self, height: u64) -> Result<&Authority> {
+        match self.authorities.len() as u64 {
+            0 => Err(BlockchainError::Block("Proof-of-authority chain has no authorities".to_string())),
+            count => Ok(&self.authorities[(height % count) as usize]),
+        }
+    }
+}
+
+impl Consensus for ProofOfAuthority {
+    fn name(&self) -> &'static str {
+        EngineKind::ProofOfAuthority.name()
+    }
+
+    fn scheduled_producer(&self, state: &State) -> Result<Option<String>> {
+        Ok(Some(self.authority_at(state.height() + 1)?.address.clone()))
+    }
+
+    fn seal(&self, block: &mut Block, sealer: &Sealer) -> Result<Option<MiningStats>> {
+        sign_header(block, sealer)
+    }
+
+    /// The schedule and the authorities' keys are part of the rule, so the whole seal is checked
+    fn check_seal(&self, block: &Block) -> Result<()> {
+        let authority = self.authority_at(block.id)?;
+        verify_header_signature(block, &authority.address, &authority.public_key)
+    }
+
+    fn verify_seal(&self, block: &Block, _state: &State) -> Result<()> {
+        self.check_seal(block)
+    }
+
+    /// Every block counts the same, so the longest branch wins
+    fn block_weight(&self, _block: &Block) -> f64 {
+        1.0
+    }
+}
+
+impl ProofOfStake {
+    /// Account drawn to produce the block on top of `state`, and the key it must sign with
+    fn drawn_producer(&self, state: &State) -> Result<(String, Vec<u8>)> {
+        // Accounts that can sign, with their stake, in a fixed order
+        let mut stakers: Vec<(&String, &Vec<u8>, Amount)> = state.accounts().iter()
+            .filter_map(|(address, account)| {
+                let stake = account.balances.get("ZUX").copied().unwrap_or_default();
+                match &account.public_key {
+                    Some(key) if !stake.is_zero() => Some((address, key, stake)),
+                    _ => None,
+                }
+            })
+            .collect();
+        stakers.sort_by(|a, b| a.0.cmp(b.0));
+
+        let total_stake = Amount::checked_sum(stakers.iter().map(|(_, _, stake)| *stake))?;
+        if total_stake.is_zero() {
+            return Ok((self.bootstrap.address.clone(), self.bootstrap.public_key.clone()));
+        }
+
+        // Every node derives the same draw from the parent block
+        let seed = Sha256::new()
+            .chain_update(STAKE_DRAW_DOMAIN.as_bytes())
+            .chain_update(state.tip_hash().as_bytes())
+            .chain_update((state.height() + 1).to_be_bytes())
+            .finalize();
+        let draw = u128::from_be_bytes(seed[..16].try_into().unwrap()) % total_stake.base_units();
+
+        let mut cumulative = 0;
+        for (address, key, stake) in stakers {
+            cumulative += stake.base_units();
+            if draw < cumulative {
+                return Ok((address.clone(), key.clone()));
+            }
+        }
+        unreachable!("the draw is below the total stake")
+    }
+}
+
+impl Consensus for ProofOfStake {
+    fn name(&self) -> &'static str {
+        EngineKind::ProofOfStake.name()
+    }
+
+    fn scheduled_producer(&self, state: &State) -> Result<Option<String>> {
+        Ok(Some(self.drawn_producer(state)?.0))
+    }
+
+    fn seal(&self, block: &mut Block, sealer: &Sealer) -> Result<Option<MiningStats>> {
+        sign_header(block, sealer)
+    }
+
+    /// The drawn producer and its key depend on the ledger; only a signature can be required
+    fn check_seal(&self, block: &Block) -> Result<()> {
+        signature_bytes(block).map(|_| ())
+    }
+
+    fn verify_seal(&self, block: &Block, state: &State) -> Result<()> {
+        let (address, public_key) = self.drawn_producer(state)?;
+        verify_header_signature(block, &address, &public_key)
+    }
+
+    /// Every block counts the same, so the longest branch wins
+    fn block_weight(&self, _block: &Block) -> f64 {
+        1.0
+    }
+}
+
+/// Seal a block by signing its header hash with the producer's key; no work is needed
+fn sign_header(block: &mut Block, sealer: &Sealer) -> Result<Option<MiningStats>> {
+    let key = sealer.key.ok_or_else(|| {
+        BlockchainError::Block(format!("No key to sign block #{} as {}", block.id, block.producer))
+    })?;
+
+    let started = Instant::now();
+    block.extra_nonce = 0;
+    block.nonce = 0;
+    block.hash = block.calculate_hash();
+    let hash = hex::decode(&block.hash)
+        .map_err(|e| BlockchainError::Block(format!("Invalid block hash: {}", e)))?;
+    block.signature = key.sign(&hash).to_bytes().to_vec();
+
+    Ok(Some(MiningStats { hashes: 1, elapsed: started.elapsed(), threads: 1, extra_nonces: 0 }))
+}
+
+/// Check that `block` was produced by `address` and signed with `public_key`
+fn verify_header_signature(block: &Block, address: &str, public_key: &[u8]) -> Result<()> {
+    if block.producer != address {
+        return Err(BlockchainError::Block(
+            format!("Block #{} was produced by {} but {} was scheduled", block.id, block.producer, address)
+        ));
+    }
+
+    let verifying_key = VerifyingKey::from_bytes(&public_key.try_into().map_err(|_| {
+        BlockchainError::Block(format!("Invalid public key length for {}", address))
+    })?).map_err(|e| BlockchainError::Block(format!("Invalid public key for {}: {}", address, e)))?;
+
+    let signature_bytes = signature_bytes(block)?;
+    let hash = hex::decode(&block.hash)
+        .map_err(|e| BlockchainError::Block(format!("Invalid block hash: {}", e)))?;
+
+    verifying_key.verify(&hash, &Signature::from_bytes(&signature_bytes))
+        .map_err(|e| BlockchainError::Block(format!("Block #{} signature verification failed: {}", block.id, e)))
+}
+
+/// The Ed25519 signature `block` carries
+fn signature_bytes(block: &Block) -> Result<[u8; 64]> {
+    block.signature.as_slice().try_into().map_err(|_| {
+        BlockchainError::Block(format!("Block #{} has no valid signature", block.id))
+    })
+}
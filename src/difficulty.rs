@@ -0,0 +1,237 @@
+//! Proof-of-work targets and difficulty retargeting
+//!
+//! A block hash meets its target when, read as a 256-bit big-endian number, it
+//! is at most the target. The expected number of hashes to find one is the
+//! block's work, `2^256 / (target + 1)`, which is also what explorers show as
+//! its difficulty.
+//!
+//! Every `retarget_interval` blocks the ledger moves the target towards the
+//! configured block time: the target is scaled by how long the last window
+//! actually took over how long it should have taken, limited to a factor of
+//! `max_adjustment` either way and never easier than the `pow_limit`.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::codec::{Decode, Decoder, Encode, Encoder};
+use crate::error::{BlockchainError, Result};
+
+/// Number of most recent blocks the network hash rate is estimated over
+pub const HASH_RATE_WINDOW: usize = 100;
+
+/// A 256-bit proof-of-work target, big-endian
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Target([u8; 32]);
+
+impl Target {
+    /// The easiest possible target, met by every hash
+    pub const MAX: Target = Target([0xff; 32]);
+
+    pub const fn from_bytes(bytes: [u8; 32]) -> Self {
+        Target(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    /// Target met by hashes starting with at least `bits` zero bits
+    pub fn from_leading_zero_bits(bits: u32) -> Self {
+        let bits = bits.min(256) as usize;
+        let mut bytes = [0xff; 32];
+        for (index, byte) in bytes.iter_mut().enumerate() {
+            let zeros = bits.saturating_sub(index * 8).min(8);
+            *byte = if zeros == 8 { 0 } else { 0xff >> zeros };
+        }
+        Target(bytes)
+    }
+
+    /// Number of leading zero bits every hash meeting this target has
+    pub fn leading_zero_bits(&self) -> u32 {
+        let mut bits = 0;
+        for byte in self.0 {
+            bits += byte.leading_zeros();
+            if byte != 0 {
+                break;
+            }
+        }
+        bits
+    }
+
+    /// Whether `hash` meets this target
+    pub fn is_met_by(&self, hash: &[u8; 32]) -> bool {
+        hash <= &self.0
+    }
+
+    /// Expected number of hashes to meet this target
+    pub fn work(&self) -> f64 {
+        let target = self.0.iter().fold(0.0, |value, byte| value * 256.0 + *byte as f64);
+        2f64.powi(256) / (target + 1.0)
+    }
+
+    /// `self * numerator / denominator`, rounded down, saturating at `Target::MAX` and never zero
+    pub fn scaled(&self, numerator: u64, denominator: u64) -> Target {
+        // Big-endian 64-bit limbs, with one more limb for the product's overflow
+        let mut limbs = [0u64; 5];
+        for (limb, chunk) in limbs[1..].iter_mut().zip(self.0.chunks(8)) {
+            *limb = u64::from_be_bytes(chunk.try_into().unwrap());
+        }
+
+        let mut carry = 0u128;
+        for limb in limbs.iter_mut().rev() {
+            let product = *limb as u128 * numerator as u128 + carry;
+            *limb = product as u64;
+            carry = product >> 64;
+        }
+
+        let denominator = denominator.max(1) as u128;
+        let mut remainder = 0u128;
+        for limb in limbs.iter_mut() {
+            let dividend = (remainder << 64) | *limb as u128;
+            *limb = (dividend / denominator) as u64;
+            remainder = dividend % denominator;
+        }
+
+        if limbs[0] != 0 {
+            return Target::MAX;
+        }
+        let mut bytes = [0u8; 32];
+        for (chunk, limb) in bytes.chunks_mut(8).zip(&limbs[1..]) {
+            chunk.copy_from_slice(&limb.to_be_bytes());
+        }
+        if bytes == [0; 32] {
+            bytes[31] = 1;
+        }
+        Target(bytes)
+    }
+}
+
+/// Lower-case hex of the 32 target bytes
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&hex::encode(self.0))
+    }
+}
+
+impl FromStr for Target {
+    type Err = BlockchainError;
+
+    fn from_str(text: &str) -> Result<Self> {
+        let bytes = hex::decode(text)
+            .ok()
+            .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+            .ok_or_else(|| BlockchainError::Block(format!("Invalid target: {:?}", text)))?;
+        Ok(Target(bytes))
+    }
+}
+
+impl Serialize for Target {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Target {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        text.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl Encode for Target {
+    fn encode(&self, out: &mut Encoder) {
+        out.put_bytes(&self.0);
+    }
+}
+
+impl Decode for Target {
+    fn decode(input: &mut Decoder) -> Result<Self> {
+        let bytes = input.bytes()?;
+        <[u8; 32]>::try_from(bytes)
+            .map(Target)
+            .map_err(|bytes| BlockchainError::Encoding(format!("Target of {} bytes instead of 32", bytes.len())))
+    }
+}
+
+/// How the proof-of-work target follows the block time
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RetargetRule {
+    pub target_block_time_ms: u64,
+    pub retarget_interval: u64, // Blocks between two adjustments
+    pub max_adjustment: u64,    // Largest factor a single adjustment changes the target by
+    pub pow_limit: Target,      // Easiest target allowed, and the target of genesis
+}
+
+impl Default for RetargetRule {
+    fn default() -> Self {
+        RetargetRule {
+            target_block_time_ms: 100,
+            retarget_interval: 50,
+            max_adjustment: 4,
+            pow_limit: Target::from_leading_zero_bits(8),
+        }
+    }
+}
+
+impl RetargetRule {
+    /// Whether the target is adjusted after block `height`
+    ///
+    /// Windows start at genesis, so every adjustment measures exactly
+    /// `retarget_interval` block intervals.
+    pub fn is_retarget_height(&self, height: u64) -> bool {
+        height > 1 && self.retarget_interval > 0 && (height - 1).is_multiple_of(self.retarget_interval)
+    }
+
+    /// Target after a window that was mined at `current` and took `actual_secs`
+    pub fn retarget(&self, current: &Target, actual_secs: u64) -> Target {
+        let expected_ms = self.target_block_time_ms.saturating_mul(self.retarget_interval).max(1);
+        let max_adjustment = self.max_adjustment.max(1);
+        let actual_ms = actual_secs.saturating_mul(1_000)
+            .clamp(expected_ms / max_adjustment, expected_ms.saturating_mul(max_adjustment));
+        current.scaled(actual_ms.max(1), expected_ms).min(self.pow_limit)
+    }
+}
+
+/// Where the ledger is in the current retarget window
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Difficulty {
+    pub target: Target,     // Target the next block must carry
+    pub window_start: u64,  // Timestamp of the block that opened the current window
+}
+
+impl Difficulty {
+    /// Difficulty of a chain without blocks
+    pub fn initial(rule: &RetargetRule) -> Self {
+        Difficulty { target: rule.pow_limit, window_start: 0 }
+    }
+
+    /// Difficulty once block `height`, timestamped `timestamp`, is applied
+    pub fn after_block(&self, rule: &RetargetRule, height: u64, timestamp: u64) -> Self {
+        if height == 1 {
+            return Difficulty { target: self.target, window_start: timestamp };
+        }
+        if !rule.is_retarget_height(height) {
+            return *self;
+        }
+        Difficulty {
+            target: rule.retarget(&self.target, timestamp.saturating_sub(self.window_start)),
+            window_start: timestamp,
+        }
+    }
+}
+
+/// Hashes per second implied by a run of consecutive blocks, given as (timestamp, target) pairs
+///
+/// The first block only marks the start of the interval; its own work was done before it.
+pub fn estimate_hash_rate(blocks: &[(u64, Target)]) -> f64 {
+    let [(first_timestamp, _), later @ ..] = blocks else {
+        return 0.0;
+    };
+    let Some((last_timestamp, _)) = later.last() else {
+        return 0.0;
+    };
+    let work: f64 = later.iter().map(|(_, target)| target.work()).sum();
+    work / last_timestamp.saturating_sub(*first_timestamp).max(1) as f64
+}
@@ -0,0 +1,51 @@
+//! Error type shared by every part of the simulation
+
+use std::io;
+
+use thiserror::Error;
+
+// Custom error type for the application
+#[derive(Error, Debug)]
+pub enum BlockchainError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+    
+    #[error("Time error: {0}")]
+    Time(String),
+    
+    #[error("Wallet error: {0}")]
+    Wallet(String),
+    
+    #[error("Transaction error: {0}")]
+    Transaction(String),
+    
+    #[error("Amount error: {0}")]
+    Amount(String),
+    
+    #[error("Replay rejected: {0}")]
+    Replay(String),
+    
+    #[error("Mempool rejected: {0}")]
+    Mempool(String),
+    
+    #[error("Block error: {0}")]
+    Block(String),
+    
+    #[error("Proof error: {0}")]
+    Proof(String),
+    
+    #[error("Encoding error: {0}")]
+    Encoding(String),
+    
+    #[error("Configuration error: {0}")]
+    Config(String),
+    
+    #[error("Network error: {0}")]
+    Network(String),
+    
+    #[error("System error: {0}")]
+    System(String),
+}
+
+// Type alias for Result with our custom error type
+pub type Result<T> = std::result::Result<T, BlockchainError>;
@@ -0,0 +1,340 @@
+//! Data structures shared between the simulator and the blockchain explorer
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::amm::SwapFees;
+use crate::amount::Amount;
+use crate::block::{Block, BlockEvent};
+use crate::codec;
+use crate::consensus::EngineKind;
+use crate::difficulty::{estimate_hash_rate, HASH_RATE_WINDOW};
+use crate::error::{BlockchainError, Result};
+use crate::proof::{BalanceProof, TransactionProof};
+use crate::state::State;
+use crate::store::ChainStore;
+use crate::wallet::Wallet;
+use crate::SYSTEM_WALLET_ADDRESS;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BlockInfo {
+    pub id: u64,
+    pub hash: String,
+    pub parent_hash: String,
+    #[serde(default)]
+    pub tx_root: String,
+    #[serde(default)]
+    pub state_root: String,
+    pub timestamp: u64,
+    pub transactions_count: usize,
+    pub difficulty: f64, // Expected hashes to mine the block
+    #[serde(default)]
+    pub target: String,
+    pub nonce: u64,
+    pub size_bytes: usize,
+    pub formatted_time: String,
+    pub network_name: String,
+    pub version: String,
+    #[serde(default)]
+    pub producer: String,
+    #[serde(default)]
+    pub fees: Amount,   // Network fees paid to the producer
+    #[serde(default)]
+    pub reward: Amount, // Block reward issued to the producer
+    #[serde(default)]
+    pub transaction_proofs: Vec<TransactionProof>, // One per transaction, against tx_root
+    #[serde(default)]
+    pub signature: String, // Producer signature in hex, empty for proof-of-work blocks
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AmmInfo {
+    pub zux_reserve: Amount,
+    pub usd_reserve: Amount,
+    pub k_constant: f64,
+    pub current_price: f64,
+    pub total_liquidity: f64,
+    pub volume_5s: f64,
+    pub volume_total: f64,
+    pub price_5s_change: f64,
+    pub price_5s_high: f64,
+    pub price_5s_low: f64,
+    pub price_inception_change: f64,
+    pub price_inception_high: f64,
+    pub price_inception_low: f64,
+    #[serde(default)]
+    pub fee_bps: u32,
+    #[serde(default)]
+    pub fees_collected: SwapFees, // Swap fees kept by the pool, per input currency
+    pub swap_count: u64,
+    pub avg_trade_size: f64,
+    pub price_history: Vec<PricePoint>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PricePoint {
+    pub timestamp: u64,
+    pub price: f64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WalletInfo {
+    pub address: String,
+    pub zux_balance: Amount,
+    pub usdz_balance: Amount,
+    pub total_value_usd: f64,
+    pub transaction_count: u64,
+    pub is_whale: bool,
+    pub is_mega_whale: bool,
+    pub last_activity: u64,
+    #[serde(default)]
+    pub balance_proof: Option<BalanceProof>, // Against the state root of the latest block
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SystemWalletInfo {
+    pub address: String,
+    pub zux_balance: Amount,
+    pub usdz_balance: Amount,
+    pub total_issued_zux: Amount,
+    pub total_issued_usdz: Amount,
+    #[serde(default)]
+    pub block_rewards_zux: Amount, // Part of total_issued_zux issued as block rewards
+    #[serde(default)]
+    pub network_fees_zux: Amount,  // Paid by transactions to block producers
+    pub active_wallets: u64,
+    pub total_transactions: u64,
+    pub network_hash_rate: f64,
+    pub avg_block_time: f64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ExplorerData {
+    pub blocks: Vec<BlockInfo>,
+    pub amm_info: AmmInfo,
+    pub wallets: Vec<WalletInfo>,
+    pub system_wallet: SystemWalletInfo,
+    pub last_update: u64,
+    #[serde(default)]
+    pub consensus: EngineKind, // Engine sealing the chain's blocks
+}
+
+/// Update explorer data file with current blockchain state, as of `now` seconds
+pub fn update_explorer_data(
+    blocks: &[Block],
+    state: &State,
+    wallets: &HashMap<String, Wallet>,
+    total_transactions: u64,
+    swap_count: u64,
+    now: u64,
+) -> Result<()> {
+    let explorer_data = build_explorer_data(blocks, state, wallets, total_transactions, swap_count, now)?;
+    
+    // Write to JSON file
+    let json_data = serde_json::to_string_pretty(&explorer_data)
+        .map_err(|e| BlockchainError::System(format!("Failed to serialize explorer data: {}", e)))?;
+    
+    std::fs::write("explorer_data.json", json_data)
+        .map_err(BlockchainError::Io)?;
+    
+    Ok(())
+}
+
+/// Ledger rebuilt from a stored chain, e.g. after the simulator has exited
+///
+/// Trading profiles are not part of the chain, so wallets are shown without
+/// whale flags.
+pub struct ChainView {
+    state: State,
+    blocks: Vec<Block>,
+    swap_count: u64,
+}
+
+impl ChainView {
+    /// Start from `genesis`, the state the chain was produced from
+    pub fn new(genesis: State) -> Self {
+        ChainView {
+            state: genesis,
+            blocks: Vec::new(),
+            swap_count: 0,
+        }
+    }
+    
+    /// Apply the blocks appended to `store` since the last sync; returns whether any were
+    pub fn sync(&mut self, store: &dyn ChainStore) -> Result<bool> {
+        // The store must still hold the chain this view was built from
+        let height = self.state.height();
+        let (base_height, base_hash) = store.base();
+        let stored_hash = if height == base_height {
+            Some(base_hash)
+        } else {
+            store.block_at(height)?.map(|block| block.hash)
+        };
+        if height > 0 && stored_hash.as_deref() != Some(self.state.tip_hash()) {
+            return Err(BlockchainError::Block(format!("Stored chain no longer contains block #{} {}", height, self.state.tip_hash())));
+        }
+        
+        let new_blocks = store.blocks_from(self.state.height() + 1)?;
+        
+        for block in &new_blocks {
+            self.state.apply_block(block)?;
+            self.swap_count += block.events.iter()
+                .filter(|event| matches!(event, BlockEvent::Swap(_, _, _, _)))
+                .count() as u64;
+        }
+        
+        let changed = !new_blocks.is_empty();
+        self.blocks.extend(new_blocks);
+        Ok(changed)
+    }
+    
+    /// Explorer data for the synced chain, as of `now` seconds
+    pub fn explorer_data(&self, now: u64) -> Result<ExplorerData> {
+        build_explorer_data(&self.blocks, &self.state, &HashMap::new(), self.state.height(), self.swap_count, now)
+    }
+}
+
+/// Assemble explorer data from the ledger as of `current_time` seconds; `wallets` supplies the trading profiles
+pub fn build_explorer_data(
+    blocks: &[Block],
+    state: &State,
+    wallets: &HashMap<String, Wallet>,
+    total_transactions: u64,
+    swap_count: u64,
+    current_time: u64,
+) -> Result<ExplorerData> {
+    let amm_pool = state.amm_pool();
+    let rewards = state.rewards();
+    
+    // Convert blocks to explorer format
+    let explorer_blocks: Vec<BlockInfo> = blocks.iter()
+        .map(|block| Ok(BlockInfo {
+            id: block.id,
+            hash: block.hash.clone(),
+            parent_hash: block.parent_hash.clone(),
+            tx_root: block.tx_root.clone(),
+            state_root: block.state_root.clone(),
+            timestamp: block.timestamp,
+            transactions_count: block.transactions.len(),
+            difficulty: block.difficulty(),
+            target: block.target.to_string(),
+            nonce: block.nonce,
+            size_bytes: codec::to_bytes(block).len(),
+            formatted_time: block.formatted_time.clone(),
+            network_name: block.network_name.clone(),
+            version: block.version.clone(),
+            producer: block.producer.clone(),
+            fees: Amount::checked_sum(block.transactions.iter().map(|tx| tx.fee))?,
+            reward: rewards.reward_at(block.id),
+            transaction_proofs: (0..block.transactions.len())
+                .filter_map(|index| block.transaction_proof(index).ok())
+                .collect(),
+            signature: hex::encode(&block.signature),
+        }))
+        .collect::<Result<_>>()?;
+    let network_fees_zux = Amount::checked_sum(explorer_blocks.iter().map(|block| block.fees))?;
+    
+    // Calculate price changes
+    let current_price = amm_pool.get_zux_price();
+    let price_5s_change = if amm_pool.price_5s_open > 0.0 {
+        ((current_price - amm_pool.price_5s_open) / amm_pool.price_5s_open) * 100.0
+    } else { 0.0 };
+    let price_inception_change = if amm_pool.price_inception_open > 0.0 {
+        ((current_price - amm_pool.price_inception_open) / amm_pool.price_inception_open) * 100.0
+    } else { 0.0 };
+    
+    // Calculate average trade size
+    let avg_trade_size = if swap_count > 0 {
+        amm_pool.total_volume_usd / swap_count as f64
+    } else { 0.0 };
+    
+    // Convert AMM pool data
+    let explorer_amm = AmmInfo {
+        zux_reserve: amm_pool.zux_reserve,
+        usd_reserve: amm_pool.usd_reserve,
+        k_constant: amm_pool.k_constant,
+        current_price,
+        total_liquidity: (amm_pool.zux_reserve.to_f64() * current_price) + amm_pool.usd_reserve.to_f64(), // Convert to USD equivalent
+        volume_5s: amm_pool.recent_volume_usd,
+        volume_total: amm_pool.total_volume_usd,
+        price_5s_change,
+        price_5s_high: amm_pool.price_5s_high,
+        price_5s_low: amm_pool.price_5s_low,
+        price_inception_change,
+        price_inception_high: amm_pool.price_inception_high,
+        price_inception_low: amm_pool.price_inception_low,
+        fee_bps: amm_pool.fee_bps,
+        fees_collected: amm_pool.fees_collected,
+        swap_count,
+        avg_trade_size,
+        price_history: amm_pool.price_history.iter()
+            .map(|p| PricePoint {
+                timestamp: p.timestamp,
+                price: p.price,
+            })
+            .collect(),
+    };
+    
+    // Convert account data from the ledger (limit to most interesting wallets)
+    let mut explorer_wallets: Vec<WalletInfo> = state.accounts().keys()
+        .filter(|addr| **addr != SYSTEM_WALLET_ADDRESS)
+        .map(|addr| {
+            let zux_balance = state.balance(addr, "ZUX");
+            let usdz_balance = state.balance(addr, "USDZ");
+            let total_value_usd = usdz_balance.to_f64() + (zux_balance.to_f64() * current_price);
+            let strategy = wallets.get(addr).and_then(|wallet| wallet.trading_strategy.as_ref());
+            
+            WalletInfo {
+                address: addr.clone(),
+                zux_balance,
+                usdz_balance,
+                total_value_usd,
+                transaction_count: 1, // Simplified
+                is_whale: strategy.map(|s| s.whale_mode).unwrap_or(false),
+                is_mega_whale: strategy.map(|s| s.mega_whale_mode).unwrap_or(false),
+                last_activity: current_time,
+                balance_proof: state.balance_proof(addr).ok(),
+            }
+        })
+        .collect();
+    
+    // Sort by total value (descending) - show all 1000 wallets
+    explorer_wallets.sort_by(|a, b| b.total_value_usd.partial_cmp(&a.total_value_usd).unwrap());
+    
+    // Hash rate and block time measured over the most recent blocks
+    let recent_blocks: Vec<(u64, _)> = blocks[blocks.len().saturating_sub(HASH_RATE_WINDOW)..].iter()
+        .map(|block| (block.timestamp, block.target))
+        .collect();
+    let avg_block_time = match (recent_blocks.first(), recent_blocks.last()) {
+        (Some(first), Some(last)) if recent_blocks.len() > 1 =>
+            last.0.saturating_sub(first.0) as f64 / (recent_blocks.len() - 1) as f64,
+        _ => 0.0,
+    };
+    
+    // Convert system wallet data
+    let block_rewards_zux = rewards.total_issued(state.height())?;
+    let explorer_system_wallet = SystemWalletInfo {
+        address: SYSTEM_WALLET_ADDRESS.to_string(),
+        zux_balance: state.balance(SYSTEM_WALLET_ADDRESS, "ZUX"),
+        usdz_balance: state.balance(SYSTEM_WALLET_ADDRESS, "USDZ"),
+        total_issued_zux: Amount::from_tokens(1_000_000_000).checked_add(block_rewards_zux)?, // 1 billion ZUX at genesis plus block rewards
+        total_issued_usdz: Amount::from_tokens(5_000_000_000), // 5 billion USDZ initially created
+        block_rewards_zux,
+        network_fees_zux,
+        active_wallets: state.accounts().len().saturating_sub(1) as u64, // Exclude system wallet
+        total_transactions,
+        network_hash_rate: estimate_hash_rate(&recent_blocks),
+        avg_block_time,
+    };
+    
+    // Create the complete explorer data
+    Ok(ExplorerData {
+        blocks: explorer_blocks,
+        amm_info: explorer_amm,
+        wallets: explorer_wallets,
+        system_wallet: explorer_system_wallet,
+        last_update: current_time,
+        consensus: state.consensus().kind(),
+    })
+}
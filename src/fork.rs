@@ -0,0 +1,263 @@
+//! Block tree, fork choice and chain reorganizations
+//!
+//! A `BlockTree` keeps every block it has accepted, whether or not it is on
+//! the canonical chain, and the ledger at the canonical head. A block is
+//! accepted if its parent is any known block and the part of its seal that
+//! does not depend on the ledger is valid. Each block has a cumulative
+//! weight, its parent's plus the weight the consensus engine gives it: the
+//! work behind it under proof-of-work, one per block under the signed
+//! engines. The heaviest branch is canonical; on a tie the branch seen first
+//! stays canonical.
+//!
+//! When a side branch becomes the heaviest, the tree reorganizes: it reverts
+//! the canonical blocks down to the fork point using their state diffs, then
+//! applies the branch. A side branch is only executed at that point, so a
+//! branch holding an invalid block is rejected then and the previous chain is
+//! restored.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::block::Block;
+use crate::error::{BlockchainError, Result};
+use crate::state::{State, StateDiff};
+
+/// What importing a block did to the canonical chain
+#[derive(Debug, Clone)]
+pub enum Imported {
+    Known,                    // The block was already in the tree
+    Extended(Box<StateDiff>), // The block extends the canonical head
+    SideChain,                // The block is on a branch that is not the heaviest
+    Reorganized {
+        reverted: Vec<StateDiff>, // Canonical blocks undone, oldest first
+        applied: Vec<StateDiff>,  // Blocks of the new canonical branch, oldest first
+    },
+}
+
+/// A known block and the cumulative weight of the branch it ends
+#[derive(Debug, Clone)]
+struct TreeBlock {
+    block: Block,
+    weight: f64,
+}
+
+/// Every known block above a base, and the ledger at the heaviest one
+#[derive(Debug, Clone)]
+pub struct BlockTree {
+    state: State,
+    base_height: u64,
+    base_hash: String,
+    blocks: HashMap<String, TreeBlock>,
+    canonical: Vec<StateDiff>, // Diffs of the canonical blocks above the base, in order
+    invalid: HashSet<String>,  // Blocks that failed to apply, and their descendants
+}
+
+impl BlockTree {
+    /// Tree rooted at the current tip of `state`
+    pub fn new(state: State) -> Self {
+        BlockTree {
+            base_height: state.height(),
+            base_hash: state.tip_hash().to_string(),
+            state,
+            blocks: HashMap::new(),
+            canonical: Vec::new(),
+            invalid: HashSet::new(),
+        }
+    }
+
+    /// Ledger at the canonical head
+    pub fn state(&self) -> &State {
+        &self.state
+    }
+
+    /// Hash of the canonical head
+    pub fn head_hash(&self) -> &str {
+        self.state.tip_hash()
+    }
+
+    /// Height of the canonical head
+    pub fn height(&self) -> u64 {
+        self.state.height()
+    }
+
+    /// Cumulative weight of the canonical branch above the base
+    pub fn head_weight(&self) -> f64 {
+        self.weight_of(self.head_hash()).unwrap_or(0.0)
+    }
+
+    /// A known block, canonical or not
+    pub fn block(&self, hash: &str) -> Option<&Block> {
+        self.blocks.get(hash).map(|known| &known.block)
+    }
+
+    /// Height and hash of the block the tree is rooted at
+    pub fn base(&self) -> (u64, &str) {
+        (self.base_height, &self.base_hash)
+    }
+
+    /// Height of a known block or of the base
+    pub fn height_of(&self, hash: &str) -> Option<u64> {
+        if hash == self.base_hash {
+            return Some(self.base_height);
+        }
+        self.block(hash).map(|block| block.id)
+    }
+
+    /// Hash of the canonical block at `height`, if it is above the base
+    pub fn canonical_hash(&self, height: u64) -> Option<&str> {
+        let index = height.checked_sub(self.base_height + 1)? as usize;
+        self.canonical.get(index).map(|diff| diff.block_hash.as_str())
+    }
+
+    /// Hashes of canonical blocks, newest first and increasingly sparse, ending with the base
+    ///
+    /// A peer finds the last block it shares with this chain by looking for the
+    /// first hash it knows.
+    pub fn locator(&self) -> Vec<String> {
+        let mut locator = Vec::new();
+        let mut height = self.height();
+        let mut step = 1;
+        while height > self.base_height {
+            locator.extend(self.canonical_hash(height).map(str::to_string));
+            if locator.len() >= 10 {
+                step *= 2;
+            }
+            height = height.saturating_sub(step);
+        }
+        locator.push(self.base_hash.clone());
+        locator
+    }
+
+    /// Whether `hash` is a known block or the base
+    pub fn contains(&self, hash: &str) -> bool {
+        hash == self.base_hash || self.blocks.contains_key(hash)
+    }
+
+    /// Number of known blocks above the base, canonical or not
+    pub fn len(&self) -> usize {
+        self.blocks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+
+    /// Whether `hash` is the base or a block of the canonical chain
+    pub fn is_canonical(&self, hash: &str) -> bool {
+        if hash == self.base_hash {
+            return true;
+        }
+        self.block(hash).is_some_and(|block| self.canonical_hash(block.id) == Some(hash))
+    }
+
+    /// Canonical blocks above the base, oldest first
+    pub fn canonical_blocks(&self) -> impl Iterator<Item = &Block> + '_ {
+        self.canonical.iter().map(|diff| &self.blocks[&diff.block_hash].block)
+    }
+
+    /// Cumulative weight of the branch ending at `hash`, if known
+    fn weight_of(&self, hash: &str) -> Option<f64> {
+        if hash == self.base_hash {
+            return Some(0.0);
+        }
+        self.blocks.get(hash).map(|known| known.weight)
+    }
+
+    /// Add a block whose parent is any known block, moving the canonical head if its branch is now the heaviest
+    ///
+    /// Blocks extending the head, or making a side branch the heaviest, are
+    /// fully validated against the ledger; a block that fails is remembered as
+    /// invalid together with its descendants, and the canonical chain is left
+    /// as it was. Blocks on lighter branches are only checked for a
+    /// consistent hash and height and a seal until their branch is executed.
+    /// A block is only remembered as invalid once its hash is known to be its
+    /// own, so a forged copy cannot get a valid block refused.
+    pub fn import(&mut self, block: Block) -> Result<Imported> {
+        if self.invalid.contains(&block.hash) || self.invalid.contains(&block.parent_hash) {
+            return Err(BlockchainError::Block(format!("Block #{} {} is on an invalid branch", block.id, block.hash)));
+        }
+        if self.contains(&block.hash) {
+            return Ok(Imported::Known);
+        }
+
+        let parent_weight = self.weight_of(&block.parent_hash).ok_or_else(|| {
+            BlockchainError::Block(format!("Block #{} parent {} is unknown", block.id, block.parent_hash))
+        })?;
+        let parent_height = self.block(&block.parent_hash).map_or(self.base_height, |parent| parent.id);
+        if block.id != parent_height + 1 {
+            return Err(BlockchainError::Block(
+                format!("Block #{} does not follow its parent #{}", block.id, parent_height)
+            ));
+        }
+        block.verify()?;
+        self.state.consensus().engine().check_seal(&block)?;
+
+        let hash = block.hash.clone();
+        let extends_head = block.parent_hash == self.head_hash();
+        let weight = parent_weight + self.state.consensus().engine().block_weight(&block);
+        self.blocks.insert(hash.clone(), TreeBlock { block, weight });
+
+        if extends_head {
+            return match self.state.apply_block(&self.blocks[&hash].block) {
+                Ok(diff) => {
+                    self.canonical.push(diff.clone());
+                    Ok(Imported::Extended(Box::new(diff)))
+                },
+                Err(e) => {
+                    self.invalid.insert(hash);
+                    Err(e)
+                },
+            };
+        }
+        if weight > self.head_weight() {
+            return self.reorganize(&hash);
+        }
+        Ok(Imported::SideChain)
+    }
+
+    /// Make the branch ending at `tip` canonical
+    fn reorganize(&mut self, tip: &str) -> Result<Imported> {
+        // The branch from just above the fork point to the new tip, oldest first
+        let mut branch = Vec::new();
+        let mut cursor = tip.to_string();
+        while !self.is_canonical(&cursor) {
+            if self.invalid.contains(&cursor) {
+                self.invalid.insert(tip.to_string());
+                return Err(BlockchainError::Block(format!("Block {} descends from invalid block {}", tip, cursor)));
+            }
+            let parent = self.blocks[&cursor].block.parent_hash.clone();
+            branch.push(cursor);
+            cursor = parent;
+        }
+        branch.reverse();
+
+        // Undo the canonical blocks above the fork point, newest first
+        let fork_height = self.block(&cursor).map_or(self.base_height, |fork| fork.id);
+        let reverted = self.canonical.split_off((fork_height - self.base_height) as usize);
+        for diff in reverted.iter().rev() {
+            self.state.revert(diff)?;
+        }
+
+        let mut applied: Vec<StateDiff> = Vec::new();
+        for (index, hash) in branch.iter().enumerate() {
+            match self.state.apply_block(&self.blocks[hash].block) {
+                Ok(diff) => applied.push(diff),
+                Err(e) => {
+                    self.invalid.extend(branch[index..].iter().cloned());
+
+                    // Put the previous canonical chain back
+                    for diff in applied.iter().rev() {
+                        self.state.revert(diff)?;
+                    }
+                    for diff in &reverted {
+                        let restored = self.state.apply_block(&self.blocks[&diff.block_hash].block)?;
+                        self.canonical.push(restored);
+                    }
+                    return Err(BlockchainError::Block(format!("Reorganization to {} failed: {}", tip, e)));
+                },
+            }
+        }
+
+        self.canonical.extend(applied.iter().cloned());
+        Ok(Imported::Reorganized { reverted, applied })
+    }
+}
@@ -0,0 +1,227 @@
+//! Launching the price monitor and blockchain explorer next to a run
+//!
+//! The companions are the `price_monitor` and `blockchain_explorer` binaries
+//! built next to the running simulator; they are started directly rather than
+//! through `cargo run`, in the directory the simulator writes its feeds to.
+//! Each needs a terminal of its own: a tmux pane when the simulator runs inside
+//! tmux, otherwise a new terminal window. When no way to open one is available
+//! the run carries on without the companion and says how to start it by hand.
+
+use std::fmt;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::str::FromStr;
+
+use log::{info, warn};
+
+use crate::error::{BlockchainError, Result};
+
+// Terminal emulators tried on Linux, in order, with the arguments that precede the command to run
+const TERMINAL_EMULATORS: [(&str, &[&str]); 8] = [
+    ("x-terminal-emulator", &["-e"]),
+    ("gnome-terminal", &["--"]),
+    ("konsole", &["-e"]),
+    ("xfce4-terminal", &["-x"]),
+    ("alacritty", &["-e"]),
+    ("kitty", &[]),
+    ("wezterm", &["start", "--"]),
+    ("xterm", &["-e"]),
+];
+
+/// Where the companions of a run are opened
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LaunchMode {
+    /// A tmux pane inside tmux, otherwise a terminal window if there is a display
+    #[default]
+    Auto,
+    /// A pane split off the simulator's tmux window
+    Tmux,
+    /// A new terminal window
+    Terminal,
+    /// Not at all
+    Headless,
+}
+
+/// The configuration name: `auto`, `tmux`, `terminal` or `headless`
+impl fmt::Display for LaunchMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            LaunchMode::Auto => "auto",
+            LaunchMode::Tmux => "tmux",
+            LaunchMode::Terminal => "terminal",
+            LaunchMode::Headless => "headless",
+        })
+    }
+}
+
+impl FromStr for LaunchMode {
+    type Err = BlockchainError;
+
+    fn from_str(text: &str) -> Result<Self> {
+        match text {
+            "auto" => Ok(LaunchMode::Auto),
+            "tmux" => Ok(LaunchMode::Tmux),
+            "terminal" => Ok(LaunchMode::Terminal),
+            "headless" => Ok(LaunchMode::Headless),
+            _ => Err(BlockchainError::Config(format!("Unknown launch mode '{}' (expected auto, tmux, terminal or headless)", text))),
+        }
+    }
+}
+
+/// A binary shipped next to the simulator that follows a run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Companion {
+    PriceMonitor,
+    BlockchainExplorer,
+}
+
+impl Companion {
+    /// Name of the companion's binary, without the platform's executable suffix
+    pub fn binary_name(self) -> &'static str {
+        match self {
+            Companion::PriceMonitor => "price_monitor",
+            Companion::BlockchainExplorer => "blockchain_explorer",
+        }
+    }
+
+    /// The companion's binary in the directory of the running executable
+    pub fn binary_path(self) -> Result<PathBuf> {
+        let current = std::env::current_exe()
+            .map_err(|e| BlockchainError::System(format!("Cannot locate the running executable: {}", e)))?;
+        Ok(current.with_file_name(format!("{}{}", self.binary_name(), std::env::consts::EXE_SUFFIX)))
+    }
+}
+
+/// How a companion was opened
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Launched {
+    TmuxPane,
+    Window(String), // Terminal emulator it was opened in
+    Skipped,        // Headless, or nothing could open it
+}
+
+/// Opens the companions of a run the way `mode` asks
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Launcher {
+    pub mode: LaunchMode,
+}
+
+impl Launcher {
+    pub fn new(mode: LaunchMode) -> Self {
+        Launcher { mode }
+    }
+
+    /// Open `companion` if the launch mode allows it
+    ///
+    /// Failing to open it is not an error: the run continues and the companion
+    /// can be started by hand, as the warning logged says.
+    pub fn launch(&self, companion: Companion) -> Launched {
+        if self.mode == LaunchMode::Headless {
+            return Launched::Skipped;
+        }
+        let binary = match companion.binary_path() {
+            Ok(binary) if binary.is_file() => binary,
+            Ok(binary) => {
+                warn!("Not starting {}: {} does not exist; build it with `cargo build --bins`", companion.binary_name(), binary.display());
+                return Launched::Skipped;
+            },
+            Err(e) => {
+                warn!("Not starting {}: {}", companion.binary_name(), e);
+                return Launched::Skipped;
+            },
+        };
+        let directory = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+
+        let inside_tmux = std::env::var_os("TMUX").is_some();
+        let use_tmux = match self.mode {
+            LaunchMode::Tmux if !inside_tmux => {
+                warn!("--launch tmux needs the simulator to run inside tmux; opening {} in a terminal window instead", companion.binary_name());
+                false
+            },
+            mode => inside_tmux && mode != LaunchMode::Terminal,
+        };
+        let launched = if use_tmux {
+            open_tmux_pane(&binary, &directory).map(|_| Launched::TmuxPane)
+        } else {
+            open_window(&binary, &directory).map(Launched::Window)
+        };
+
+        match launched {
+            Ok(launched) => {
+                match &launched {
+                    Launched::Window(terminal) => info!("Started {} in a {} window.", companion.binary_name(), terminal),
+                    _ => info!("Started {} in a tmux pane.", companion.binary_name()),
+                }
+                launched
+            },
+            Err(e) => {
+                warn!("Not starting {}: {}; run {} in another terminal to follow the run", companion.binary_name(), e, binary.display());
+                Launched::Skipped
+            },
+        }
+    }
+}
+
+// Why a companion could not be opened
+type LaunchError = String;
+
+/// Split a pane off the current tmux window running `binary`, leaving the focus where it is
+fn open_tmux_pane(binary: &Path, directory: &Path) -> std::result::Result<(), LaunchError> {
+    // tmux hands a single command argument to the shell, so the path is quoted
+    let command = format!("'{}'", binary.display().to_string().replace('\'', r"'\''"));
+    let status = Command::new("tmux")
+        .args(["split-window", "-d", "-h", "-c"])
+        .arg(directory)
+        .arg(command)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map_err(|e| format!("cannot run tmux: {}", e))?;
+    if !status.success() {
+        return Err(format!("tmux split-window failed ({})", status));
+    }
+    Ok(())
+}
+
+/// Open a terminal window running `binary`, returning the terminal used
+fn open_window(binary: &Path, directory: &Path) -> std::result::Result<String, LaunchError> {
+    let spawn = |program: &str, args: &[&str]| {
+        Command::new(program)
+            .args(args)
+            .arg(binary)
+            .current_dir(directory)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map(|_| program.to_string())
+    };
+
+    if cfg!(windows) {
+        // `start` takes the first quoted argument as the window title
+        return spawn("cmd", &["/c", "start", ""])
+            .map_err(|e| format!("cannot run cmd: {}", e));
+    }
+    if cfg!(target_os = "macos") {
+        return spawn("open", &["-a", "Terminal"])
+            .map_err(|e| format!("cannot run open: {}", e));
+    }
+
+    if std::env::var_os("DISPLAY").is_none() && std::env::var_os("WAYLAND_DISPLAY").is_none() {
+        return Err("no graphical display to open a terminal window on".to_string());
+    }
+    let preferred = std::env::var("TERMINAL").ok().filter(|terminal| !terminal.is_empty());
+    let candidates = preferred.iter()
+        .map(|terminal| (terminal.as_str(), &["-e"][..]))
+        .chain(TERMINAL_EMULATORS.iter().map(|(terminal, args)| (*terminal, *args)));
+    for (terminal, args) in candidates {
+        match spawn(terminal, args) {
+            Ok(terminal) => return Ok(terminal),
+            Err(e) if e.kind() == ErrorKind::NotFound => continue,
+            Err(e) => return Err(format!("cannot run {}: {}", terminal, e)),
+        }
+    }
+    Err("no terminal emulator found; set $TERMINAL to one".to_string())
+}
@@ -0,0 +1,77 @@
+//! ZUX blockchain simulation library
+//!
+//! This crate contains the in-memory chain, the ZUX/USDZ AMM pool, the trading
+//! agents and the data feeds consumed by the price monitor and explorer. The
+//! `practicerust2`, `price_monitor` and `blockchain_explorer` binaries are thin
+//! front-ends over it, and research harnesses can embed the same components.
+
+use once_cell::sync::Lazy;
+
+pub mod error;
+pub mod clock;
+pub mod codec;
+pub mod amount;
+pub mod address;
+pub mod wallet;
+pub mod transaction;
+pub mod amm;
+pub mod agent;
+pub mod block;
+pub mod difficulty;
+pub mod miner;
+pub mod consensus;
+pub mod merkle;
+pub mod proof;
+pub mod reward;
+pub mod state;
+pub mod fork;
+pub mod p2p;
+pub mod tcp;
+pub mod network;
+pub mod netsim;
+pub mod mempool;
+pub mod store;
+pub mod snapshot;
+pub mod market;
+pub mod explorer;
+pub mod launcher;
+pub mod config;
+pub mod report;
+pub mod simulation;
+pub mod sweep;
+
+pub use error::{BlockchainError, Result};
+pub use clock::{Clock, SimulatedClock, SystemClock};
+pub use amount::Amount;
+pub use address::UniqueCodeGenerator;
+pub use wallet::Wallet;
+pub use transaction::{Transaction, TxKind};
+pub use amm::{AmmPool, PricePoint, SwapFees};
+pub use agent::{AgentMix, TradeAction, TradingStrategy};
+pub use block::{Block, BlockEvent};
+pub use difficulty::{RetargetRule, Target};
+pub use miner::{Miner, MiningStats};
+pub use consensus::{Consensus, ConsensusRule, EngineKind};
+pub use proof::{BalanceProof, TransactionProof};
+pub use reward::RewardSchedule;
+pub use state::{BlockBuilder, State, StateDiff};
+pub use fork::{BlockTree, Imported};
+pub use p2p::{Message, Node, NodeStats};
+pub use tcp::TcpNode;
+pub use netsim::{LinkConfig, NetworkEvent, SimConfig, SimNetwork};
+pub use mempool::{BlockLimits, Mempool, MempoolConfig};
+pub use store::{ChainStore, FileStore, MemoryStore};
+pub use snapshot::Snapshot;
+pub use config::SimulationConfig;
+pub use launcher::{Companion, LaunchMode, Launcher};
+pub use report::RunReport;
+pub use sweep::{Axis, SweepOptions, SweepResult};
+
+// Constants for the application
+pub static SUPPORTED_CURRENCIES: Lazy<Vec<&'static str>> = Lazy::new(|| vec!["ZUX", "USDZ"]);
+
+// Constants for AMM pool
+pub const AMM_POOL_ADDRESS: &str = "AMM_POOL_ZUX_USDZ";
+pub const PRICE_UPDATE_INTERVAL_MS: u64 = 50; // Update price display more frequently for responsive charts
+
+pub const SYSTEM_WALLET_ADDRESS: &str = "SYSTEM";
@@ -0,0 +1,286 @@
+//! Market data feed consumed by the price monitor
+
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use log::{error, info};
+
+use crate::amm::{AmmPool, SwapFees};
+use crate::amount::Amount;
+use crate::clock::Clock;
+use crate::error::{BlockchainError, Result};
+
+/// Enhanced market data structure for the price monitor
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct EnhancedMarketData {
+    pub current_price: f64,
+    pub volume_1m: f64,
+    pub volume_10s: f64,
+    pub volume_5s: f64,
+    pub high_1m: f64,
+    pub low_1m: f64,
+    pub price_change_1m: f64,
+    pub price_change_10s: f64,
+    pub price_change_5s: f64,
+    pub total_liquidity: f64,
+    pub market_cap: f64,
+    pub circulating_supply: f64,
+    pub trades_count: u64,
+    pub fees_collected: SwapFees, // Swap fees kept by the pool, each in the currency it was paid in
+    pub avg_trade_size: f64,
+    pub zux_reserve: Amount,
+    pub usd_reserve: Amount,
+    pub k_constant: f64,
+    pub pool_utilization: f64,
+    pub total_blocks: u64,
+    pub total_transactions: u64,
+    pub network_hash_rate: f64,
+    pub active_wallets: u64,
+    pub last_update: u64,
+    pub price_history: Vec<(u64, f64)>, // timestamp, price pairs
+}
+
+/// Chain figures the simulator republishes after every block for the market feed
+#[derive(Clone, Debug, Default)]
+pub struct NetworkStats {
+    pub total_blocks: u64,
+    pub network_hash_rate: f64, // Measured over the most recent blocks and their targets
+}
+
+/// Publish the enhanced market feed for the price monitor from a separate thread, timing it by `clock`
+///
+/// The monitor itself is a companion binary reading the feed file, see [`crate::launcher`].
+pub fn run_price_monitor(amm_pool: Arc<Mutex<AmmPool>>, network: Arc<Mutex<NetworkStats>>, stop_signal: Arc<Mutex<bool>>,
+                         clock: Arc<dyn Clock>) -> Result<()> {
+    // Enhanced data file path
+    let enhanced_data_path = "enhanced_market_data.json";
+    
+    info!("Publishing the enhanced market feed to {}.", enhanced_data_path);
+    
+    // High-frequency data updater thread
+    thread::spawn(move || {
+        let mut price_history: Vec<(u64, f64)> = Vec::new();
+        let mut volume_tracker = VolumeTracker::new();
+        
+        loop {
+            // Check if we should stop
+            if *stop_signal.lock().unwrap() {
+                break;
+            }
+            
+            let current_time = clock.now();
+            
+            // Get comprehensive market data
+            let (current_price, volume_data, liquidity_data) = {
+                let pool = amm_pool.lock().unwrap();
+                let price = pool.get_zux_price();
+                let total_liquidity = (pool.zux_reserve.to_f64() * price) + pool.usd_reserve.to_f64();
+                
+                (price, 
+                 (pool.total_volume_usd, pool.recent_volume_usd, 
+                  pool.price_5s_high, pool.price_5s_low),
+                 total_liquidity)
+            };
+            
+            // Update price history (keep last 1000 points)
+            price_history.push((current_time, current_price));
+            if price_history.len() > 1000 {
+                price_history.remove(0);
+            }
+            
+            // Update volume tracker
+            volume_tracker.update(current_time, volume_data.1);
+            
+            // Calculate time-based metrics with realistic timeframes for fast blockchain
+            let price_change_1m = calculate_price_change(&price_history, current_time, 60);
+            let price_change_10s = calculate_price_change(&price_history, current_time, 10);
+            let price_change_5s = calculate_price_change(&price_history, current_time, 5);
+            
+            let (high_1m, low_1m) = calculate_high_low(&price_history, current_time, 60);
+            
+            // Get comprehensive pool data
+            let (pool_data, swap_count, fees_collected) = {
+                let pool = amm_pool.lock().unwrap();
+                ((pool.zux_reserve, pool.usd_reserve, pool.k_constant), 
+                 volume_tracker.get_trades_count(),
+                 pool.fees_collected)
+            };
+            
+            // Calculate average trade size
+            let avg_trade_size = if swap_count > 0 {
+                volume_data.0 / swap_count as f64
+            } else { 0.0 };
+            
+            // Calculate REAL pool utilization percentage
+            let pool_utilization = if !pool_data.0.is_zero() && !pool_data.1.is_zero() {
+                let total_pool_value_usd = (pool_data.0.to_f64() * current_price) + pool_data.1.to_f64();
+                let max_efficient_value = pool_data.2.sqrt() * 2.0 * current_price; // Optimal AMM range
+                if max_efficient_value > 0.0 {
+                    (total_pool_value_usd / max_efficient_value) * 100.0
+                } else {
+                    0.0
+                }
+            } else {
+                0.0
+            };
+            
+            let network_stats = network.lock().unwrap().clone();
+            
+            // Create enhanced market data with all required fields
+            let enhanced_data = EnhancedMarketData {
+                current_price,
+                volume_1m: volume_tracker.get_volume_1m(current_time),
+                volume_10s: volume_tracker.get_volume_10s(current_time),
+                volume_5s: volume_tracker.get_volume_5s(current_time),
+                high_1m,
+                low_1m,
+                price_change_1m,
+                price_change_10s,
+                price_change_5s,
+                total_liquidity: liquidity_data,
+                market_cap: current_price * 1_000_000_000.0, // REAL market cap for all ZUX
+                circulating_supply: 1_000_000_000.0, // Total ZUX supply
+                trades_count: swap_count,
+                fees_collected,
+                avg_trade_size,
+                zux_reserve: pool_data.0,
+                usd_reserve: pool_data.1,
+                k_constant: pool_data.2,
+                pool_utilization,
+                total_blocks: network_stats.total_blocks,
+                total_transactions: swap_count + 3003, // Swaps + setup blocks
+                network_hash_rate: network_stats.network_hash_rate,
+                active_wallets: 1000,
+                last_update: current_time,
+                price_history: price_history.clone(),
+            };
+            
+            // Write enhanced data to JSON file with error handling
+            match serde_json::to_string_pretty(&enhanced_data) {
+                Ok(json_data) => {
+                    if let Err(e) = std::fs::write(enhanced_data_path, json_data) {
+                        error!("Failed to write enhanced market data: {}", e);
+                    }
+                }
+                Err(e) => {
+                    error!("Failed to serialize enhanced market data: {}", e);
+                }
+            }
+            
+            // High-frequency update (20ms for 50 FPS data feed)
+            thread::sleep(Duration::from_millis(20));
+        }
+        
+        // Clean up the enhanced data file when done
+        if let Err(e) = std::fs::remove_file(enhanced_data_path) {
+            error!("Failed to remove enhanced market data file: {}", e);
+        }
+        
+        Ok::<(), BlockchainError>(())
+    });
+    
+    Ok(())
+}
+
+/// Volume tracking utility for enhanced metrics
+pub struct VolumeTracker {
+    pub volume_points: Vec<(u64, f64)>,
+    pub trades_count: u64,
+}
+
+impl Default for VolumeTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VolumeTracker {
+    pub fn new() -> Self {
+        Self {
+            volume_points: Vec::new(),
+            trades_count: 0,
+        }
+    }
+    
+    pub fn update(&mut self, timestamp: u64, volume: f64) {
+        self.volume_points.push((timestamp, volume));
+        self.trades_count += 1;
+        
+        // Keep only last 24 hours of data
+        let cutoff = timestamp.saturating_sub(86400);
+        self.volume_points.retain(|(t, _)| *t >= cutoff);
+    }
+    
+    pub fn get_volume_period(&self, current_time: u64, period_secs: u64) -> f64 {
+        let cutoff = current_time.saturating_sub(period_secs);
+        self.volume_points.iter()
+            .filter(|(t, _)| *t >= cutoff)
+            .map(|(_, v)| *v)
+            .sum()
+    }
+    
+    pub fn get_volume_24h(&self) -> f64 {
+        self.volume_points.iter().map(|(_, v)| *v).sum()
+    }
+    
+    pub fn get_volume_1h(&self, current_time: u64) -> f64 {
+        self.get_volume_period(current_time, 3600)
+    }
+    
+    pub fn get_volume_1m(&self, current_time: u64) -> f64 {
+        self.get_volume_period(current_time, 60)
+    }
+    
+    pub fn get_volume_10s(&self, current_time: u64) -> f64 {
+        self.get_volume_period(current_time, 10)
+    }
+    
+    pub fn get_volume_5s(&self, current_time: u64) -> f64 {
+        self.get_volume_period(current_time, 5)
+    }
+    
+    pub fn get_volume_5m(&self, current_time: u64) -> f64 {
+        self.get_volume_period(current_time, 300)
+    }
+    
+    pub fn get_trades_count(&self) -> u64 {
+        self.trades_count
+    }
+}
+
+/// Calculate price change percentage over a time period
+pub fn calculate_price_change(price_history: &[(u64, f64)], current_time: u64, period_secs: u64) -> f64 {
+    let cutoff = current_time.saturating_sub(period_secs);
+    
+    if let Some(old_price) = price_history.iter()
+        .find(|(t, _)| *t >= cutoff)
+        .map(|(_, p)| *p) {
+        
+        if let Some((_, current_price)) = price_history.last() {
+            if old_price > 0.0 {
+                return ((current_price - old_price) / old_price) * 100.0;
+            }
+        }
+    }
+    0.0
+}
+
+/// Calculate high and low prices over a time period
+pub fn calculate_high_low(price_history: &[(u64, f64)], current_time: u64, period_secs: u64) -> (f64, f64) {
+    let cutoff = current_time.saturating_sub(period_secs);
+    
+    let prices: Vec<f64> = price_history.iter()
+        .filter(|(t, _)| *t >= cutoff)
+        .map(|(_, p)| *p)
+        .collect();
+    
+    if prices.is_empty() {
+        return (0.0, 0.0);
+    }
+    
+    let high = prices.iter().fold(f64::NEG_INFINITY, |a, &b| a.max(b));
+    let low = prices.iter().fold(f64::INFINITY, |a, &b| a.min(b));
+    
+    (high, low)
+}
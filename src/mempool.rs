@@ -0,0 +1,382 @@
+//! Pending transactions waiting to be packed into blocks
+//!
+//! Agents submit signed transactions to the `Mempool`, which checks them
+//! against the ledger on admission and keeps them until a block includes them,
+//! they expire or they are evicted to make room for better-paying ones. Block
+//! producers take the highest fees first while every sender's transactions
+//! stay in nonce order, so a block only ever carries a sender's next nonces.
+
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BTreeMap, BinaryHeap, HashMap, HashSet};
+
+use crate::amount::Amount;
+use crate::codec;
+use crate::error::{BlockchainError, Result};
+use crate::state::{State, StateDiff};
+use crate::transaction::{Transaction, TxKind};
+
+/// Admission and eviction rules of a mempool
+#[derive(Debug, Clone)]
+pub struct MempoolConfig {
+    pub capacity: usize,       // Most transactions kept; beyond it the lowest fee is evicted
+    pub max_per_sender: usize, // Most pending transactions of a single sender
+    pub min_fee: Amount,       // Lowest fee accepted
+    pub expiry_secs: u64,      // How long a transaction is kept without being included
+}
+
+impl Default for MempoolConfig {
+    fn default() -> Self {
+        MempoolConfig {
+            capacity: 5_000,
+            max_per_sender: 16,
+            min_fee: Amount::ZERO,
+            expiry_secs: 300,
+        }
+    }
+}
+
+/// How much a single block may carry
+#[derive(Debug, Clone)]
+pub struct BlockLimits {
+    pub max_transactions: usize,
+    pub max_bytes: usize, // Encoded size of the block's transactions
+}
+
+impl Default for BlockLimits {
+    fn default() -> Self {
+        BlockLimits {
+            max_transactions: 25,
+            max_bytes: 64 * 1024,
+        }
+    }
+}
+
+/// A transaction waiting in the mempool
+#[derive(Debug, Clone)]
+struct PendingTransaction {
+    transaction: Transaction,
+    hash: String,
+    size: usize,      // Encoded size in bytes
+    received_at: u64, // When it was admitted, for expiry
+    sequence: u64,    // Admission order, which breaks fee ties
+}
+
+impl PendingTransaction {
+    /// Ordering key: higher fees first, then earlier admission
+    fn priority(&self) -> (Amount, Reverse<u64>) {
+        (self.transaction.fee, Reverse(self.sequence))
+    }
+}
+
+/// Next transaction of one sender, ranked for packing
+#[derive(PartialEq, Eq)]
+struct Candidate {
+    priority: (Amount, Reverse<u64>),
+    sender: String,
+    nonce: u64,
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.priority.cmp(&other.priority)
+    }
+}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Transactions chosen for the next block, with the ledger changes they make
+#[derive(Debug)]
+pub struct PackedBlock {
+    pub transactions: Vec<Transaction>,
+    pub diff: StateDiff,                        // As `State::preview` returns it for `transactions`
+    pub dropped: Vec<(String, BlockchainError)>, // Hash and failure of each transaction that would not execute
+}
+
+/// Pool of valid transactions not yet included in a block
+#[derive(Debug)]
+pub struct Mempool {
+    config: MempoolConfig,
+    by_sender: HashMap<String, BTreeMap<u64, PendingTransaction>>, // Pending transactions of each sender by nonce
+    hashes: HashSet<String>,
+    len: usize,
+    next_sequence: u64,
+}
+
+impl Mempool {
+    pub fn new(config: MempoolConfig) -> Self {
+        Mempool {
+            config,
+            by_sender: HashMap::new(),
+            hashes: HashSet::new(),
+            len: 0,
+            next_sequence: 0,
+        }
+    }
+
+    pub fn config(&self) -> &MempoolConfig {
+        &self.config
+    }
+
+    /// Number of pending transactions
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Whether the transaction with this hash is pending
+    pub fn contains(&self, hash: &str) -> bool {
+        self.hashes.contains(hash)
+    }
+
+    /// The pending transaction with this hash
+    pub fn get(&self, hash: &str) -> Option<&Transaction> {
+        if !self.hashes.contains(hash) {
+            return None;
+        }
+        self.by_sender.values()
+            .flat_map(|pending| pending.values())
+            .find(|tx| tx.hash == hash)
+            .map(|tx| &tx.transaction)
+    }
+
+    /// Nonce the next transaction from `address` should carry, counting its pending ones
+    pub fn next_nonce(&self, address: &str, state: &State) -> u64 {
+        let ledger_nonce = state.next_nonce(address);
+        match self.by_sender.get(address).and_then(|pending| pending.keys().next_back()) {
+            Some(last) if *last >= ledger_nonce => last + 1,
+            _ => ledger_nonce,
+        }
+    }
+
+    /// Validate `transaction` against the ledger and add it, returning its hash
+    ///
+    /// A transaction reusing the nonce of a pending one replaces it if it pays a
+    /// higher fee. When the pool is full, the lowest-fee transaction that is the
+    /// last of its sender is evicted to make room for a better-paying one.
+    pub fn submit(&mut self, transaction: Transaction, state: &State, now: u64) -> Result<String> {
+        // Signature and kind checks
+        transaction.verify()?;
+        let hash = transaction.hash();
+        if self.hashes.contains(&hash) {
+            return Err(BlockchainError::Mempool(format!("Transaction {} is already pending", hash)));
+        }
+
+        if transaction.chain_id != state.chain_id() {
+            return Err(BlockchainError::Replay(format!(
+                "Transaction from {} was signed for chain {}, not {}", transaction.sender, transaction.chain_id, state.chain_id()
+            )));
+        }
+        let ledger_nonce = state.next_nonce(&transaction.sender);
+        if transaction.nonce < ledger_nonce {
+            return Err(BlockchainError::Replay(format!(
+                "Transaction from {} reuses nonce {} (next nonce is {})", transaction.sender, transaction.nonce, ledger_nonce
+            )));
+        }
+        let next_nonce = self.next_nonce(&transaction.sender, state);
+        if transaction.nonce > next_nonce {
+            return Err(BlockchainError::Mempool(format!(
+                "Transaction from {} skips ahead to nonce {} (next nonce is {})", transaction.sender, transaction.nonce, next_nonce
+            )));
+        }
+
+        let registered = state.account(&transaction.sender).is_some();
+        if transaction.kind == TxKind::WalletRegister && registered {
+            return Err(BlockchainError::Transaction(format!("Account already exists: {}", transaction.sender)));
+        }
+        if transaction.kind == TxKind::WalletRegister {
+            state.check_registration(&transaction.sender, &transaction.sender_public_key)?;
+        }
+        if transaction.kind != TxKind::WalletRegister && !registered {
+            return Err(BlockchainError::Transaction(format!("Unknown sender account: {}", transaction.sender)));
+        }
+
+        if transaction.fee < self.config.min_fee {
+            return Err(BlockchainError::Mempool(format!(
+                "Fee {} is below the minimum {}", transaction.fee, self.config.min_fee
+            )));
+        }
+        Self::check_funds(&transaction, state)?;
+
+        // Replace-by-fee
+        let replaced = self.by_sender.get(&transaction.sender)
+            .and_then(|pending| pending.get(&transaction.nonce));
+        if let Some(replaced) = replaced {
+            if transaction.fee <= replaced.transaction.fee {
+                return Err(BlockchainError::Mempool(format!(
+                    "Replacing nonce {} of {} needs a fee above {}", transaction.nonce, transaction.sender, replaced.transaction.fee
+                )));
+            }
+        } else {
+            let sender_count = self.by_sender.get(&transaction.sender).map_or(0, BTreeMap::len);
+            if sender_count >= self.config.max_per_sender {
+                return Err(BlockchainError::Mempool(format!(
+                    "{} already has {} pending transactions", transaction.sender, sender_count
+                )));
+            }
+            if self.len >= self.config.capacity {
+                self.evict_for(&transaction)?;
+            }
+        }
+
+        let pending = PendingTransaction {
+            size: codec::to_bytes(&transaction).len(),
+            hash: hash.clone(),
+            received_at: now,
+            sequence: self.next_sequence,
+            transaction,
+        };
+        self.next_sequence += 1;
+        self.hashes.insert(hash.clone());
+        let sender_pending = self.by_sender.entry(pending.transaction.sender.clone()).or_default();
+        match sender_pending.insert(pending.transaction.nonce, pending) {
+            Some(replaced) => {
+                self.hashes.remove(&replaced.hash);
+            },
+            None => self.len += 1,
+        }
+        Ok(hash)
+    }
+
+    /// The sender must hold what the transaction spends and its fee
+    fn check_funds(transaction: &Transaction, state: &State) -> Result<()> {
+        let mut required: BTreeMap<&str, Amount> = BTreeMap::new();
+        for (currency, amount) in transaction.required_funds() {
+            let total = required.entry(currency).or_default();
+            *total = total.checked_add(amount)?;
+        }
+        for (currency, amount) in required {
+            let balance = state.balance(&transaction.sender, currency);
+            if balance < amount {
+                return Err(BlockchainError::Transaction(format!(
+                    "Insufficient balance: {} {} (needed: {})", balance, currency, amount
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Evict the lowest-priority transaction that no other pending one depends on, if `incoming` outbids it
+    fn evict_for(&mut self, incoming: &Transaction) -> Result<()> {
+        let lowest = self.by_sender.iter()
+            .filter_map(|(sender, pending)| pending.iter().next_back().map(|(nonce, tx)| (tx.priority(), sender, *nonce)))
+            .min();
+        match lowest {
+            Some((priority, sender, nonce)) if priority.0 < incoming.fee => {
+                let sender = sender.clone();
+                self.remove_from(&sender, nonce);
+                Ok(())
+            },
+            _ => Err(BlockchainError::Mempool(format!(
+                "Mempool is full ({} transactions) and a fee of {} does not outbid any of them", self.len, incoming.fee
+            ))),
+        }
+    }
+
+    /// Remove the transaction of `sender` with `nonce` and every later one, which can no longer execute
+    fn remove_from(&mut self, sender: &str, nonce: u64) -> usize {
+        let Some(pending) = self.by_sender.get_mut(sender) else {
+            return 0;
+        };
+        let removed = pending.split_off(&nonce);
+        if pending.is_empty() {
+            self.by_sender.remove(sender);
+        }
+        for tx in removed.values() {
+            self.hashes.remove(&tx.hash);
+        }
+        self.len -= removed.len();
+        removed.len()
+    }
+
+    /// Drop transactions the ledger has moved past and those pending for too long,
+    /// returning how many were removed
+    ///
+    /// Call this after every applied block: it removes the included transactions.
+    pub fn prune(&mut self, state: &State, now: u64) -> usize {
+        let mut removed = 0;
+        let senders: Vec<String> = self.by_sender.keys().cloned().collect();
+        for sender in senders {
+            let ledger_nonce = state.next_nonce(&sender);
+            let Some(pending) = self.by_sender.get_mut(&sender) else {
+                continue;
+            };
+
+            // Included or superseded on chain
+            let current = pending.split_off(&ledger_nonce);
+            for tx in pending.values() {
+                self.hashes.remove(&tx.hash);
+            }
+            removed += pending.len();
+            self.len -= pending.len();
+            *pending = current;
+
+            // A transaction pending too long takes its sender's later ones with it
+            let expired = pending.values()
+                .find(|tx| now.saturating_sub(tx.received_at) >= self.config.expiry_secs)
+                .map(|tx| tx.transaction.nonce);
+            if let Some(nonce) = expired {
+                removed += self.remove_from(&sender, nonce);
+            } else if pending.is_empty() {
+                self.by_sender.remove(&sender);
+            }
+        }
+        removed
+    }
+
+    /// Choose the transactions of the next block, timestamped `timestamp` and
+    /// produced by `producer`, within `limits`
+    ///
+    /// The highest fee among every sender's next transaction goes first. Each
+    /// candidate is executed on top of the ones before it; one that fails is
+    /// dropped from the pool together with its sender's later transactions.
+    /// Included transactions stay pending until [`Mempool::prune`] sees the
+    /// applied block. Fails only if the producer cannot be paid.
+    pub fn pack(&mut self, state: &State, limits: &BlockLimits, timestamp: u64, producer: &str) -> Result<PackedBlock> {
+        let mut builder = state.block_builder(timestamp, producer);
+        let mut dropped = Vec::new();
+        let mut bytes = 0;
+
+        let mut candidates: BinaryHeap<Candidate> = self.by_sender.iter()
+            .filter_map(|(sender, pending)| {
+                let ledger_nonce = state.next_nonce(sender);
+                pending.get(&ledger_nonce).map(|tx| Candidate { priority: tx.priority(), sender: sender.clone(), nonce: ledger_nonce })
+            })
+            .collect();
+
+        while builder.len() < limits.max_transactions {
+            let Some(candidate) = candidates.pop() else {
+                break;
+            };
+            let pending = &self.by_sender[&candidate.sender][&candidate.nonce];
+
+            // Too large for what is left of this block; its sender waits for the next one
+            if bytes + pending.size > limits.max_bytes {
+                continue;
+            }
+
+            match builder.try_add(&pending.transaction) {
+                Ok(()) => {
+                    bytes += pending.size;
+                    let next_nonce = candidate.nonce + 1;
+                    if let Some(next) = self.by_sender[&candidate.sender].get(&next_nonce) {
+                        candidates.push(Candidate { priority: next.priority(), sender: candidate.sender, nonce: next_nonce });
+                    }
+                },
+                Err(e) => {
+                    dropped.push((pending.hash.clone(), e));
+                    self.remove_from(&candidate.sender, candidate.nonce);
+                },
+            }
+        }
+
+        let (transactions, diff) = builder.finish()?;
+        Ok(PackedBlock { transactions, diff, dropped })
+    }
+}
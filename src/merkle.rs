@@ -0,0 +1,206 @@
+//! Sparse Merkle tree used to commit to the ledger state
+//!
+//! Leaves live at depth 256 and are addressed by a 32-byte key (the SHA-256 of
+//! the account address). Empty subtrees hash to precomputed defaults, so only
+//! the nodes on paths to occupied leaves are stored.
+
+use std::collections::HashMap;
+
+use once_cell::sync::Lazy;
+use sha2::{Digest, Sha256};
+
+/// Depth of the tree: one level per bit of the key
+pub const TREE_DEPTH: usize = 256;
+
+/// A 32-byte hash
+pub type Hash = [u8; 32];
+
+// Domain separation prefixes so leaves can never be confused with internal nodes
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+
+/// Hashes of empty subtrees, indexed by depth (TREE_DEPTH is the leaf level)
+static DEFAULT_HASHES: Lazy<Vec<Hash>> = Lazy::new(|| {
+    let mut defaults = vec![[0u8; 32]; TREE_DEPTH + 1];
+    for depth in (0..TREE_DEPTH).rev() {
+        defaults[depth] = hash_node(&defaults[depth + 1], &defaults[depth + 1]);
+    }
+    defaults
+});
+
+/// Hash two child nodes into their parent
+pub fn hash_node(left: &Hash, right: &Hash) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update([NODE_PREFIX]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+/// Hash a leaf, binding its key to the hash of its value
+pub fn hash_leaf(key: &Hash, value_hash: &Hash) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update([LEAF_PREFIX]);
+    hasher.update(key);
+    hasher.update(value_hash);
+    hasher.finalize().into()
+}
+
+/// Derive the tree key for an arbitrary byte string
+pub fn tree_key(data: &[u8]) -> Hash {
+    Sha256::digest(data).into()
+}
+
+/// Hash of an empty subtree rooted at `depth`
+pub fn default_hash(depth: usize) -> Hash {
+    DEFAULT_HASHES[depth]
+}
+
+/// Value of bit `index` of the key, counting from the most significant bit
+fn bit(key: &Hash, index: usize) -> bool {
+    key[index / 8] & (0x80 >> (index % 8)) != 0
+}
+
+/// Keep only the first `depth` bits of the key
+fn prefix(key: &Hash, depth: usize) -> Hash {
+    let mut out = [0u8; 32];
+    let full_bytes = depth / 8;
+    out[..full_bytes].copy_from_slice(&key[..full_bytes]);
+    if !depth.is_multiple_of(8) {
+        out[full_bytes] = key[full_bytes] & !(0xffu8 >> (depth % 8));
+    }
+    out
+}
+
+/// Flip bit `index` of the key
+fn flip(key: &Hash, index: usize) -> Hash {
+    let mut out = *key;
+    out[index / 8] ^= 0x80 >> (index % 8);
+    out
+}
+
+type NodeId = (u16, Hash); // depth, key prefix
+
+/// Sibling hashes on the path from a leaf to the root
+///
+/// Siblings that are empty subtrees are omitted, so a proof only carries
+/// the handful of nodes that differ from the defaults.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SparseMerkleProof {
+    pub siblings: Vec<(u16, Hash)>, // depth of the sibling node, its hash; deepest first
+}
+
+impl SparseMerkleProof {
+    /// Root implied by `key` holding `value_hash`, or being empty if `None`
+    pub fn compute_root(&self, key: &Hash, value_hash: Option<&Hash>) -> Hash {
+        let mut current = match value_hash {
+            Some(value_hash) => hash_leaf(key, value_hash),
+            None => default_hash(TREE_DEPTH),
+        };
+
+        let mut siblings = self.siblings.iter().peekable();
+        for depth in (0..TREE_DEPTH).rev() {
+            let sibling = match siblings.peek() {
+                Some((sibling_depth, hash)) if *sibling_depth as usize == depth + 1 => {
+                    siblings.next();
+                    *hash
+                },
+                _ => default_hash(depth + 1),
+            };
+
+            current = if bit(key, depth) {
+                hash_node(&sibling, &current)
+            } else {
+                hash_node(&current, &sibling)
+            };
+        }
+
+        current
+    }
+}
+
+/// Sparse Merkle tree storing only non-empty nodes
+#[derive(Debug, Clone, Default)]
+pub struct SparseMerkleTree {
+    nodes: HashMap<NodeId, Hash>,
+}
+
+impl SparseMerkleTree {
+    /// Create an empty tree
+    pub fn new() -> Self {
+        SparseMerkleTree { nodes: HashMap::new() }
+    }
+
+    fn node(&self, overlay: &HashMap<NodeId, Hash>, depth: usize, node_prefix: &Hash) -> Hash {
+        let id = (depth as u16, *node_prefix);
+        overlay.get(&id)
+            .or_else(|| self.nodes.get(&id))
+            .copied()
+            .unwrap_or_else(|| default_hash(depth))
+    }
+
+    /// Record the path changes for one leaf update in `overlay`
+    fn update_into(&self, overlay: &mut HashMap<NodeId, Hash>, key: &Hash, value_hash: Option<&Hash>) {
+        let mut current = match value_hash {
+            Some(value_hash) => hash_leaf(key, value_hash),
+            None => default_hash(TREE_DEPTH),
+        };
+        overlay.insert((TREE_DEPTH as u16, *key), current);
+
+        for depth in (0..TREE_DEPTH).rev() {
+            let sibling_prefix = flip(&prefix(key, depth + 1), depth);
+            let sibling = self.node(overlay, depth + 1, &sibling_prefix);
+
+            current = if bit(key, depth) {
+                hash_node(&sibling, &current)
+            } else {
+                hash_node(&current, &sibling)
+            };
+            overlay.insert((depth as u16, prefix(key, depth)), current);
+        }
+    }
+
+    /// Root of the tree
+    pub fn root(&self) -> Hash {
+        self.node(&HashMap::new(), 0, &[0u8; 32])
+    }
+
+    /// Root the tree would have after the given updates, without applying them
+    ///
+    /// A value of `None` removes the leaf.
+    pub fn root_with(&self, updates: &[(Hash, Option<Hash>)]) -> Hash {
+        let mut overlay = HashMap::new();
+        for (key, value_hash) in updates {
+            self.update_into(&mut overlay, key, value_hash.as_ref());
+        }
+        self.node(&overlay, 0, &[0u8; 32])
+    }
+
+    /// Inclusion (or exclusion) proof for `key` against the current root
+    pub fn proof(&self, key: &Hash) -> SparseMerkleProof {
+        let overlay = HashMap::new();
+        let siblings = (0..TREE_DEPTH).rev()
+            .filter_map(|depth| {
+                let sibling_prefix = flip(&prefix(key, depth + 1), depth);
+                let sibling = self.node(&overlay, depth + 1, &sibling_prefix);
+                (sibling != default_hash(depth + 1)).then_some(((depth + 1) as u16, sibling))
+            })
+            .collect();
+
+        SparseMerkleProof { siblings }
+    }
+
+    /// Insert, replace or (with `None`) remove a leaf
+    pub fn update(&mut self, key: &Hash, value_hash: Option<&Hash>) {
+        let mut overlay = HashMap::new();
+        self.update_into(&mut overlay, key, value_hash);
+
+        for ((depth, node_prefix), hash) in overlay {
+            if hash == default_hash(depth as usize) {
+                self.nodes.remove(&(depth, node_prefix));
+            } else {
+                self.nodes.insert((depth, node_prefix), hash);
+            }
+        }
+    }
+}
@@ -0,0 +1,143 @@
+//! Multithreaded proof-of-work miner
+//!
+//! The miner splits the nonce range of a header into one contiguous slice per
+//! worker thread. Each worker clones a SHA-256 state that has already absorbed
+//! the rest of the header, so a nonce costs a single hash of eight bytes plus
+//! padding. When every nonce has been tried the miner rolls the block's extra
+//! nonce, which changes the header, and searches the range again. The
+//! timestamp is not rolled: the ledger executes the block at its timestamp, so
+//! changing it could change the block's receipts and state root.
+//!
+//! Workers check a cancellation flag between batches of nonces, so a miner
+//! working on a stale parent stops soon after a competing block arrives.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::block::Block;
+use crate::error::{BlockchainError, Result};
+
+// Nonces a worker tries between two checks of the stop flags
+const BATCH_SIZE: u64 = 1_024;
+
+/// How blocks are mined
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Miner {
+    pub threads: usize,   // Worker threads, at least one
+    pub nonce_range: u64, // Nonces tried per extra nonce before it is rolled
+}
+
+impl Default for Miner {
+    fn default() -> Self {
+        Miner {
+            threads: thread::available_parallelism().map(|threads| threads.get()).unwrap_or(1),
+            nonce_range: u64::MAX,
+        }
+    }
+}
+
+/// Work measured while mining one block
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MiningStats {
+    pub hashes: u64,         // Headers hashed by every worker together
+    pub elapsed: Duration,
+    pub threads: usize,
+    pub extra_nonces: u64,   // Times the nonce range was exhausted and the extra nonce rolled
+}
+
+impl MiningStats {
+    /// Measured hashes per second
+    pub fn hash_rate(&self) -> f64 {
+        self.hashes as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+/// What the workers of one pass over the nonce range found
+struct Pass {
+    solution: Option<(u64, [u8; 32])>,
+    hashes: u64,
+}
+
+impl Miner {
+    pub fn new(threads: usize) -> Self {
+        Miner { threads, ..Miner::default() }
+    }
+
+    /// Find a nonce, and extra nonce if needed, whose header hash meets the block's target
+    ///
+    /// On success the block's hash, nonce and extra nonce are set. Returns
+    /// `None` if `cancel` was raised first, leaving the block unmined.
+    pub fn mine(&self, block: &mut Block, cancel: &AtomicBool) -> Result<Option<MiningStats>> {
+        let threads = self.threads.max(1);
+        let started = Instant::now();
+        let mut hashes = 0;
+        let mut extra_nonces = 0;
+
+        loop {
+            let pass = self.search(block, threads, cancel);
+            hashes += pass.hashes;
+
+            if let Some((nonce, hash)) = pass.solution {
+                block.nonce = nonce;
+                block.hash = hex::encode(hash);
+                return Ok(Some(MiningStats { hashes, elapsed: started.elapsed(), threads, extra_nonces }));
+            }
+            if cancel.load(Ordering::Relaxed) {
+                return Ok(None);
+            }
+
+            block.extra_nonce = block.extra_nonce.checked_add(1).ok_or_else(|| {
+                BlockchainError::Block(format!("Failed to mine block #{}: every nonce and extra nonce was tried", block.id))
+            })?;
+            extra_nonces += 1;
+        }
+    }
+
+    /// Search the whole nonce range for the block's current header
+    fn search(&self, block: &Block, threads: usize, cancel: &AtomicBool) -> Pass {
+        let prefix = block.header_prefix();
+        let target = block.target;
+        let slice_len = self.nonce_range.div_ceil(threads as u64).max(1);
+        let found = AtomicBool::new(false);
+        let solution = Mutex::new(None);
+
+        let hashes = thread::scope(|scope| {
+            let workers: Vec<_> = (0..threads as u64)
+                .map(|worker| {
+                    let start = worker.saturating_mul(slice_len).min(self.nonce_range);
+                    let end = start.saturating_add(slice_len).min(self.nonce_range);
+                    let (prefix, found, solution) = (&prefix, &found, &solution);
+
+                    scope.spawn(move || {
+                        let mut hashes = 0;
+                        let mut batch_start = start;
+                        while batch_start < end {
+                            if found.load(Ordering::Relaxed) || cancel.load(Ordering::Relaxed) {
+                                break;
+                            }
+                            let batch_end = batch_start.saturating_add(BATCH_SIZE).min(end);
+                            for nonce in batch_start..batch_end {
+                                let hash = Block::hash_with_nonce(prefix, nonce);
+                                hashes += 1;
+                                if target.is_met_by(&hash) {
+                                    // Keep the first solution if two workers find one at once
+                                    solution.lock().unwrap().get_or_insert((nonce, hash));
+                                    found.store(true, Ordering::Relaxed);
+                                    return hashes;
+                                }
+                            }
+                            batch_start = batch_end;
+                        }
+                        hashes
+                    })
+                })
+                .collect();
+
+            workers.into_iter().map(|worker| worker.join().unwrap_or(0)).sum()
+        });
+
+        Pass { solution: solution.into_inner().unwrap(), hashes }
+    }
+}
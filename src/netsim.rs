@@ -0,0 +1,413 @@
+//! Deterministic in-process network simulator
+//!
+//! A `SimNetwork` connects several `Node`s through a message bus driven by a
+//! virtual clock in milliseconds. Every message is delivered after its link's
+//! latency, plus a jitter drawn from a seeded generator, or lost with the
+//! link's loss probability. Partitions split the nodes into groups that cannot
+//! reach each other until the network heals; messages crossing a partition
+//! when they arrive are dropped. Nodes get their upkeep `tick` at a fixed
+//! virtual interval.
+//!
+//! Nothing depends on the wall clock or on thread scheduling: blocks are
+//! produced only when asked, sealed with a single-threaded miner, and stamped
+//! with the virtual time, and events due at the same instant run in the order
+//! they were scheduled. The same scenario with the same seed delivers the same
+//! messages at the same times, so fork and reorganization scenarios run in
+//! tests without timing flakiness.
+
+use std::cmp::Ordering as Order;
+use std::collections::{BTreeSet, BinaryHeap, HashMap};
+use std::sync::atomic::AtomicBool;
+
+use ed25519_dalek::SigningKey;
+use log::debug;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::Serialize;
+
+use crate::block::Block;
+use crate::consensus::Sealer;
+use crate::error::{BlockchainError, Result};
+use crate::mempool::BlockLimits;
+use crate::miner::Miner;
+use crate::p2p::{Message, Node, Outbox, PeerId};
+use crate::transaction::Transaction;
+use crate::SYSTEM_WALLET_ADDRESS;
+
+/// Delivery of messages from one node to another
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LinkConfig {
+    pub latency_ms: u64,
+    pub jitter_ms: u64, // Most extra delay, drawn uniformly per message
+    pub loss: f64,      // Probability that a message is lost, from 0 to 1
+}
+
+impl Default for LinkConfig {
+    fn default() -> Self {
+        LinkConfig { latency_ms: 50, jitter_ms: 0, loss: 0.0 }
+    }
+}
+
+/// Shape of a simulated network
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SimConfig {
+    pub seed: u64,        // Seeds jitter and loss
+    pub link: LinkConfig, // Links without a configuration of their own
+    pub tick_ms: u64,     // Interval between two upkeep ticks of every node
+    pub start_time: u64,  // Unix time in seconds at virtual time zero, for block timestamps
+}
+
+impl Default for SimConfig {
+    fn default() -> Self {
+        SimConfig { seed: 0, link: LinkConfig::default(), tick_ms: 1_000, start_time: 1_700_000_000 }
+    }
+}
+
+/// A change to the network, applied at a scheduled virtual time
+#[derive(Debug, Clone, PartialEq)]
+pub enum NetworkEvent {
+    Connect(usize, usize),
+    Disconnect(usize, usize),
+    Partition(Vec<Vec<usize>>), // Groups that can only reach themselves; unlisted nodes form one more group
+    Heal,
+    SetLink(usize, usize, LinkConfig),
+    Produce(usize), // The node produces a block if it has anything to pack
+}
+
+/// Counters of the message bus
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct BusStats {
+    pub sent: u64,
+    pub delivered: u64,
+    pub lost: u64,        // Dropped by link loss
+    pub partitioned: u64, // Dropped by a partition or a closed connection
+    pub disconnects: u64, // Connections closed for a protocol violation
+}
+
+#[derive(Debug)]
+enum Event {
+    Deliver { from: usize, to: usize, message: Message },
+    Tick,
+    Network(NetworkEvent),
+}
+
+/// An event and when it is due; ordered so the heap pops the earliest, then the first scheduled
+#[derive(Debug)]
+struct Scheduled {
+    at: u64,
+    seq: u64,
+    event: Event,
+}
+
+impl PartialEq for Scheduled {
+    fn eq(&self, other: &Self) -> bool {
+        (self.at, self.seq) == (other.at, other.seq)
+    }
+}
+
+impl Eq for Scheduled {}
+
+impl PartialOrd for Scheduled {
+    fn partial_cmp(&self, other: &Self) -> Option<Order> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Scheduled {
+    fn cmp(&self, other: &Self) -> Order {
+        (other.at, other.seq).cmp(&(self.at, self.seq))
+    }
+}
+
+/// Who produces a node's blocks
+#[derive(Debug, Clone)]
+struct Producer {
+    address: String,
+    key: Option<SigningKey>, // Needed under the signed consensus engines
+}
+
+/// Nodes connected by a simulated message bus
+///
+/// Nodes are numbered in the order they were given, and each one knows the
+/// others by their number as `PeerId`.
+#[derive(Debug)]
+pub struct SimNetwork {
+    config: SimConfig,
+    now: u64,
+    nodes: Vec<Node>,
+    producers: Vec<Producer>,
+    links: HashMap<(usize, usize), LinkConfig>, // Per direction, overriding the default link
+    connected: BTreeSet<(usize, usize)>,        // Open connections, lower number first
+    groups: Option<Vec<usize>>,                 // Partition group of every node, while partitioned
+    queue: BinaryHeap<Scheduled>,
+    next_seq: u64,
+    rng: StdRng,
+    limits: BlockLimits,
+    stats: BusStats,
+}
+
+impl SimNetwork {
+    /// Network of `nodes` at virtual time zero, none of them connected yet
+    pub fn new(nodes: Vec<Node>, config: SimConfig) -> Self {
+        let producer = Producer { address: SYSTEM_WALLET_ADDRESS.to_string(), key: None };
+        let mut network = SimNetwork {
+            config,
+            now: 0,
+            producers: vec![producer; nodes.len()],
+            nodes,
+            links: HashMap::new(),
+            connected: BTreeSet::new(),
+            groups: None,
+            queue: BinaryHeap::new(),
+            next_seq: 0,
+            rng: StdRng::seed_from_u64(config.seed),
+            limits: BlockLimits::default(),
+            stats: BusStats::default(),
+        };
+        network.push(config.tick_ms, Event::Tick);
+        network
+    }
+
+    /// Current virtual time in milliseconds
+    pub fn now(&self) -> u64 {
+        self.now
+    }
+
+    /// Unix time in seconds at the current virtual time, as blocks and transactions are timestamped
+    pub fn timestamp(&self) -> u64 {
+        self.config.start_time + self.now / 1_000
+    }
+
+    pub fn node(&self, index: usize) -> &Node {
+        &self.nodes[index]
+    }
+
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    pub fn stats(&self) -> &BusStats {
+        &self.stats
+    }
+
+    /// Whether every node is on the same canonical head
+    pub fn heads_agree(&self) -> bool {
+        self.nodes.windows(2).all(|pair| pair[0].tree().head_hash() == pair[1].tree().head_hash())
+    }
+
+    /// Credit the blocks `node` produces to `address`, signing them with `key` under a signed engine
+    pub fn set_producer(&mut self, node: usize, address: &str, key: Option<SigningKey>) {
+        self.producers[node] = Producer { address: address.to_string(), key };
+    }
+
+    /// Limits of the blocks every node produces
+    pub fn set_block_limits(&mut self, limits: BlockLimits) {
+        self.limits = limits;
+    }
+
+    /// Configure the link between `a` and `b`, both ways
+    pub fn set_link(&mut self, a: usize, b: usize, link: LinkConfig) {
+        self.links.insert((a, b), link);
+        self.links.insert((b, a), link);
+    }
+
+    /// Open a connection between `a` and `b`; both greet each other
+    pub fn connect(&mut self, a: usize, b: usize) {
+        if a == b || !self.connected.insert((a.min(b), a.max(b))) {
+            return;
+        }
+        let outbox = self.nodes[a].connect(b as PeerId, self.now);
+        self.send(a, outbox);
+        let outbox = self.nodes[b].connect(a as PeerId, self.now);
+        self.send(b, outbox);
+    }
+
+    /// Connect every pair of nodes
+    pub fn connect_all(&mut self) {
+        for a in 0..self.nodes.len() {
+            for b in a + 1..self.nodes.len() {
+                self.connect(a, b);
+            }
+        }
+    }
+
+    /// Close the connection between `a` and `b`; messages on the way are dropped
+    pub fn disconnect(&mut self, a: usize, b: usize) {
+        if self.connected.remove(&(a.min(b), a.max(b))) {
+            self.nodes[a].disconnect(b as PeerId);
+            self.nodes[b].disconnect(a as PeerId);
+        }
+    }
+
+    /// Split the nodes into `groups` that cannot reach each other
+    ///
+    /// Connections stay open, as behind a failed router, but every message
+    /// between two groups is dropped until `heal`.
+    pub fn partition(&mut self, groups: &[Vec<usize>]) {
+        let mut assigned = vec![groups.len(); self.nodes.len()];
+        for (group, members) in groups.iter().enumerate() {
+            for &node in members {
+                assigned[node] = group;
+            }
+        }
+        debug!("{} ms: partitioned into {:?}", self.now, groups);
+        self.groups = Some(assigned);
+    }
+
+    /// End the partition
+    pub fn heal(&mut self) {
+        debug!("{} ms: partition healed", self.now);
+        self.groups = None;
+    }
+
+    /// Apply `event` once the virtual clock reaches `at`
+    pub fn schedule(&mut self, at: u64, event: NetworkEvent) {
+        self.push(at.max(self.now), Event::Network(event));
+    }
+
+    /// Submit a transaction to `node`, which announces it to its peers
+    pub fn submit_transaction(&mut self, node: usize, transaction: Transaction) -> Result<()> {
+        let outbox = self.nodes[node].submit_transaction(transaction, self.now)?;
+        self.send(node, outbox);
+        Ok(())
+    }
+
+    /// Have `node` produce a block on its head now, sealed by its consensus engine
+    ///
+    /// Returns `None` if the node has nothing to pack, or its producer has no
+    /// key under a signed engine.
+    pub fn produce_block(&mut self, node: usize) -> Result<Option<Block>> {
+        let timestamp = self.timestamp();
+        let producer = &self.producers[node];
+        let Some(mut block) = self.nodes[node].block_template(&producer.address, &self.limits, timestamp)? else {
+            return Ok(None);
+        };
+
+        // One worker always tries nonces in the same order
+        let miner = Miner::new(1);
+        let key = if block.producer == producer.address { producer.key.as_ref() } else { None };
+        let sealer = Sealer { miner: &miner, cancel: &AtomicBool::new(false), key };
+        let consensus = self.nodes[node].state().consensus().clone();
+        if let Err(e) = consensus.engine().seal(&mut block, &sealer) {
+            debug!("{}: cannot seal block #{}: {}", self.nodes[node].id(), block.id, e);
+            return Ok(None);
+        }
+
+        let outbox = self.nodes[node].submit_block(block.clone(), self.now);
+        self.send(node, outbox);
+        Ok(Some(block))
+    }
+
+    /// Run every event due up to `time`, then move the clock there
+    pub fn run_until(&mut self, time: u64) -> Result<()> {
+        while self.queue.peek().is_some_and(|next| next.at <= time) {
+            let Some(Scheduled { at, event, .. }) = self.queue.pop() else {
+                break;
+            };
+            self.now = at;
+            self.dispatch(event)?;
+        }
+        self.now = self.now.max(time);
+        Ok(())
+    }
+
+    /// Run for `duration` milliseconds of virtual time
+    pub fn run_for(&mut self, duration: u64) -> Result<()> {
+        self.run_until(self.now + duration)
+    }
+
+    /// Run until every node is on the same head, for at most `timeout` milliseconds
+    ///
+    /// Returns whether the heads agree.
+    pub fn run_until_agreed(&mut self, timeout: u64) -> Result<bool> {
+        let deadline = self.now + timeout;
+        while !self.heads_agree() && self.now < deadline {
+            let next = self.queue.peek().map_or(deadline, |next| next.at.min(deadline));
+            self.run_until(next)?;
+        }
+        Ok(self.heads_agree())
+    }
+
+    fn push(&mut self, at: u64, event: Event) {
+        self.queue.push(Scheduled { at, seq: self.next_seq, event });
+        self.next_seq += 1;
+    }
+
+    /// Whether a message from `from` can reach `to` right now
+    fn reachable(&self, from: usize, to: usize) -> bool {
+        self.connected.contains(&(from.min(to), from.max(to)))
+            && self.groups.as_ref().is_none_or(|groups| groups[from] == groups[to])
+    }
+
+    /// Put the messages `from` produced on the bus
+    fn send(&mut self, from: usize, outbox: Outbox) {
+        for (peer, message) in outbox {
+            let to = peer as usize;
+            let link = self.links.get(&(from, to)).copied().unwrap_or(self.config.link);
+            self.stats.sent += 1;
+            if link.loss > 0.0 && self.rng.gen_bool(link.loss.min(1.0)) {
+                self.stats.lost += 1;
+                continue;
+            }
+            let jitter = if link.jitter_ms > 0 { self.rng.gen_range(0..=link.jitter_ms) } else { 0 };
+            self.push(self.now + link.latency_ms + jitter, Event::Deliver { from, to, message });
+        }
+    }
+
+    fn dispatch(&mut self, event: Event) -> Result<()> {
+        match event {
+            Event::Deliver { from, to, message } => {
+                if !self.reachable(from, to) {
+                    self.stats.partitioned += 1;
+                    return Ok(());
+                }
+                self.stats.delivered += 1;
+                match self.nodes[to].handle(from as PeerId, message, self.now) {
+                    Ok(outbox) => self.send(to, outbox),
+                    Err(e) => {
+                        debug!("{}: disconnecting {}: {}", self.nodes[to].id(), self.nodes[from].id(), e);
+                        self.stats.disconnects += 1;
+                        self.disconnect(from, to);
+                    },
+                }
+            },
+            Event::Tick => {
+                for node in 0..self.nodes.len() {
+                    let outbox = self.nodes[node].tick(self.now);
+                    self.send(node, outbox);
+                }
+                self.push(self.now + self.config.tick_ms.max(1), Event::Tick);
+            },
+            Event::Network(event) => self.apply(event)?,
+        }
+        Ok(())
+    }
+
+    fn apply(&mut self, event: NetworkEvent) -> Result<()> {
+        let count = self.nodes.len();
+        let check = |node: usize| if node < count {
+            Ok(())
+        } else {
+            Err(BlockchainError::Network(format!("No node {} in a network of {}", node, count)))
+        };
+        match event {
+            NetworkEvent::Connect(a, b) => {
+                check(a)?;
+                check(b)?;
+                self.connect(a, b);
+            },
+            NetworkEvent::Disconnect(a, b) => self.disconnect(a, b),
+            NetworkEvent::Partition(groups) => {
+                groups.iter().flatten().try_for_each(|&node| check(node))?;
+                self.partition(&groups);
+            },
+            NetworkEvent::Heal => self.heal(),
+            NetworkEvent::SetLink(a, b, link) => self.set_link(a, b, link),
+            NetworkEvent::Produce(node) => {
+                check(node)?;
+                self.produce_block(node)?;
+            },
+        }
+        Ok(())
+    }
+}
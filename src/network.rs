@@ -0,0 +1,238 @@
+//! Local multi-node network run over loopback TCP
+//!
+//! Several `TcpNode`s on 127.0.0.1 mine the same proof-of-work chain from
+//! genesis. The early nodes form a ring and start mining at once; the last
+//! node joins halfway through and syncs the chain from genesis. New wallets
+//! register through random nodes and the system wallet credits them, so every
+//! node relays transactions and blocks. Nodes mine concurrently, so blocks
+//! found at nearly the same time fork the chain until the heavier branch wins.
+//!
+//! Blocks must take much longer to mine than to propagate, or every node
+//! mines each transaction at once and ties never settle, so the network starts
+//! from a harder target than the single-node simulation. The run ends once the
+//! first node reaches the requested height and every node is on the same head.
+//! It reports how long blocks took to reach the other nodes and the orphans,
+//! side-chain blocks and reorganizations every node saw.
+
+use std::collections::HashMap;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use log::{debug, info, warn};
+use rand::Rng;
+
+use crate::address::UniqueCodeGenerator;
+use crate::amount::Amount;
+use crate::clock::{Clock, SystemClock};
+use crate::consensus::ConsensusRule;
+use crate::difficulty::{RetargetRule, Target};
+use crate::error::{BlockchainError, Result};
+use crate::mempool::{BlockLimits, MempoolConfig};
+use crate::miner::Miner;
+use crate::p2p::Node;
+use crate::simulation::genesis_state_with;
+use crate::tcp::TcpNode;
+use crate::transaction::{create_transaction, create_wallet_registration};
+use crate::wallet::{create_system_wallet, create_wallet_without_balance, Wallet};
+use crate::SYSTEM_WALLET_ADDRESS;
+
+// Pause between two rounds of transactions
+const TRAFFIC_INTERVAL: Duration = Duration::from_millis(20);
+
+// Longest the nodes get to agree on a head once the requested height is reached
+const SETTLE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Shape of a local network run
+#[derive(Debug, Clone)]
+pub struct NetworkOptions {
+    pub nodes: usize,   // Nodes on the loopback interface, at least two; the last one joins late
+    pub blocks: u64,    // Height the first node must reach
+    pub timeout: Duration,
+    pub retarget: RetargetRule, // Difficulty of the network's chain
+}
+
+impl Default for NetworkOptions {
+    fn default() -> Self {
+        NetworkOptions {
+            nodes: 4,
+            blocks: 200,
+            timeout: Duration::from_secs(300),
+            retarget: RetargetRule {
+                target_block_time_ms: 500,
+                retarget_interval: 20,
+                max_adjustment: 4,
+                pow_limit: Target::from_leading_zero_bits(18),
+            },
+        }
+    }
+}
+
+/// Block propagation measured over the first node's canonical chain
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Propagation {
+    pub blocks: usize,     // Blocks every early node saw
+    pub mean_ms: f64,      // Mean delay from the first node seeing a block to another one seeing it
+    pub max_ms: u64,
+}
+
+/// Delay from the first of `nodes` to see each canonical block of the first node to every other one seeing it
+pub fn measure_propagation(nodes: &[&Node]) -> Propagation {
+    let Some(first) = nodes.first() else {
+        return Propagation::default();
+    };
+    let mut propagation = Propagation::default();
+    let mut total_ms = 0;
+    let mut delays = 0;
+    for block in first.tree().canonical_blocks() {
+        let seen: Option<Vec<u64>> = nodes.iter().map(|node| node.block_seen_at(&block.hash)).collect();
+        let Some(seen) = seen else {
+            continue;
+        };
+        let origin = seen.iter().min().copied().unwrap_or_default();
+        for at in seen {
+            if at != origin {
+                total_ms += at - origin;
+                delays += 1;
+            }
+            propagation.max_ms = propagation.max_ms.max(at - origin);
+        }
+        propagation.blocks += 1;
+    }
+    propagation.mean_ms = total_ms as f64 / delays.max(1) as f64;
+    propagation
+}
+
+/// Run a network of `options.nodes` nodes on the loopback interface
+///
+/// Progress is logged through the `log` facade; the caller installs a logger.
+pub fn run_network_simulation(options: &NetworkOptions) -> Result<()> {
+    if options.nodes < 2 {
+        return Err(BlockchainError::System(format!("A network needs at least 2 nodes, not {}", options.nodes)));
+    }
+
+    let mut rng = rand::thread_rng();
+    let mut code_generator = UniqueCodeGenerator::new(&mut rng)?;
+    let mut system_wallet = create_system_wallet(&mut code_generator, &mut rng)?;
+    let miners: Vec<Wallet> = (0..options.nodes)
+        .map(|_| create_wallet_without_balance(&mut code_generator, &mut rng))
+        .collect::<Result<_>>()?;
+
+    let genesis = genesis_state_with(ConsensusRule::default(), options.retarget, &system_wallet.public_key);
+    let mut nodes = Vec::new();
+    for index in 0..options.nodes {
+        let node = Node::new(&format!("node{}", index), genesis.clone(), MempoolConfig::default());
+        let node = TcpNode::start(node, "127.0.0.1:0")?;
+        info!("node{} listening on {}", index, node.local_addr());
+        nodes.push(node);
+    }
+
+    // The early nodes form a ring and mine from the start
+    let late = options.nodes - 1;
+    for index in 1..late {
+        nodes[index].connect(nodes[index - 1].local_addr())?;
+    }
+    if late > 2 {
+        nodes[0].connect(nodes[late - 1].local_addr())?;
+    }
+    for (node, miner) in nodes.iter().zip(&miners).take(late) {
+        node.start_mining(Miner::new(1), &miner.address, HashMap::new(), BlockLimits::default())?;
+    }
+
+    // The system wallet and the miners register first; a miner is paid once its account exists
+    let chain_id = nodes[0].node().state().chain_id().to_string();
+    for wallet in std::iter::once(&system_wallet).chain(&miners) {
+        nodes[0].submit_transaction(create_wallet_registration(wallet, &chain_id, SystemClock.now())?)?;
+    }
+    info!("Started {} nodes; node{} joins at block #{}", late, late, options.blocks / 2);
+
+    let started = Instant::now();
+    let mut registered = Vec::new();
+    let mut joined: Option<(Instant, u64)> = None;
+    let mut synced = false;
+    let mut settle_started: Option<Instant> = None;
+    let converged = loop {
+        // Transactions keep coming until a block settles any tie at the top
+        let height = nodes[0].node().tree().height();
+        if height >= options.blocks {
+            let head = nodes[0].node().tree().head_hash().to_string();
+            if nodes.iter().all(|node| node.node().tree().head_hash() == head) {
+                break true;
+            }
+            if settle_started.get_or_insert_with(Instant::now).elapsed() > SETTLE_TIMEOUT {
+                break false;
+            }
+        }
+        if started.elapsed() > options.timeout {
+            warn!("Stopping at block #{}: the run took longer than {:.0}s", height, options.timeout.as_secs_f64());
+            break false;
+        }
+
+        // The last node joins halfway and syncs from genesis
+        if joined.is_none() && height >= options.blocks / 2 {
+            nodes[late].connect(nodes[0].local_addr())?;
+            nodes[late].start_mining(Miner::new(1), &miners[late].address, HashMap::new(), BlockLimits::default())?;
+            info!("node{} joined at block #{}", late, height);
+            joined = Some((Instant::now(), height));
+        }
+        if let Some((joined_at, join_height)) = joined.filter(|_| !synced) {
+            if nodes[late].node().tree().height() >= join_height {
+                info!("node{} synced {} blocks from genesis in {} ms", late, join_height, joined_at.elapsed().as_millis());
+                synced = true;
+            }
+        }
+
+        // A new wallet registers through a random node
+        let wallet = create_wallet_without_balance(&mut code_generator, &mut rng)?;
+        let via = rng.gen_range(0..if joined.is_some() { options.nodes } else { late });
+        match nodes[via].submit_transaction(create_wallet_registration(&wallet, &chain_id, SystemClock.now())?) {
+            Ok(()) => registered.push(wallet.address),
+            Err(e) => debug!("node{} rejected a registration: {}", via, e),
+        }
+
+        // The system wallet credits a wallet that is on chain
+        let recipient = {
+            let node = nodes[0].node();
+            system_wallet.nonce = node.mempool().next_nonce(SYSTEM_WALLET_ADDRESS, node.state());
+            let recipient = registered.get(rng.gen_range(0..registered.len().max(1)));
+            recipient.filter(|address| node.state().account(address).is_some() && node.state().account(SYSTEM_WALLET_ADDRESS).is_some())
+                .cloned()
+        };
+        if let Some(recipient) = recipient {
+            let credit = create_transaction(
+                &system_wallet, &chain_id, &recipient, Amount::from_tokens(rng.gen_range(1..=100)), "ZUX", Amount::ZERO, &HashMap::new(), SystemClock.now()
+            )?;
+            if let Err(e) = nodes[0].submit_transaction(credit) {
+                debug!("node0 rejected a credit: {}", e);
+            }
+        }
+
+        thread::sleep(TRAFFIC_INTERVAL);
+    };
+
+    let guards: Vec<_> = nodes.iter().map(|node| node.node()).collect();
+    let early: Vec<&Node> = guards[..late].iter().map(|guard| &**guard).collect();
+    let propagation = measure_propagation(&early);
+
+    info!("\nLocal network results after {:.1}s:", started.elapsed().as_secs_f64());
+    for node in &guards {
+        let stats = node.stats();
+        info!("  {} at block #{} ({}…), {} peer(s): produced {}, received {}, orphans {}, side-chain {}, reorgs {} (deepest {}), invalid {}",
+              node.id(), node.tree().height(), &node.tree().head_hash()[..12], node.peer_ids().len(), stats.blocks_produced,
+              stats.blocks_received, stats.orphan_blocks, stats.side_chain_blocks, stats.reorgs, stats.deepest_reorg, stats.invalid_blocks);
+    }
+    let stale_blocks = guards[0].tree().len() as u64 - guards[0].tree().height();
+    info!("  - Blocks left off node0's canonical chain: {}", stale_blocks);
+    info!("  - Block propagation over {} blocks: {:.1} ms mean, {} ms max", propagation.blocks, propagation.mean_ms, propagation.max_ms);
+    if converged {
+        info!("  - All {} nodes agree on block #{} {}", guards.len(), guards[0].tree().height(), guards[0].tree().head_hash());
+    } else {
+        warn!("  - The nodes did not agree on a head within {:.0}s", SETTLE_TIMEOUT.as_secs_f64());
+    }
+    drop(early);
+    drop(guards);
+
+    for node in nodes {
+        node.shutdown();
+    }
+    Ok(())
+}
@@ -0,0 +1,543 @@
+//! Peer-to-peer protocol and node logic
+//!
+//! A `Node` is one peer of the network: it owns a `BlockTree` and a `Mempool`
+//! and turns every message a peer sends it into the messages it sends back. It
+//! does no I/O and reads no clock, so the same node runs over TCP, see `tcp`,
+//! or any other transport that delivers `Message`s.
+//!
+//! The protocol:
+//!
+//! - **Handshake**: each side opens with `Hello`. Peers must speak the same
+//!   protocol version, on the same chain, started from the same ledger.
+//!   Anything else a peer sends before it is ignored.
+//! - **Announcements**: new transactions and new canonical blocks are
+//!   announced with `Inventory`; a peer asks for what it lacks with `GetData`
+//!   and receives `Block` and `Transaction` messages.
+//! - **Chain sync**: `GetBlocks` carries a locator of the requester's chain,
+//!   and the answer is an `Inventory` of up to `MAX_INVENTORY` canonical blocks
+//!   following the last block both share. A node syncs when a peer's head is
+//!   unknown to it and when a block arrives before its parent, which is kept
+//!   as an orphan until the parent is connected.
+//! - **Upkeep**: the transport calls `tick` regularly. A request left
+//!   unanswered for `REQUEST_TIMEOUT_MS` may be made again, an unanswered
+//!   handshake is repeated, and every `SYNC_INTERVAL_MS` each peer is asked for
+//!   the blocks past this node's head, so messages lost on the way only delay
+//!   a node. A peer repeating its `Hello` has not heard this node's yet, and
+//!   gets it again.
+
+use std::collections::{BTreeMap, HashMap};
+
+use log::debug;
+use serde::Serialize;
+
+use crate::block::{Block, BLOCK_VERSION, INCEPTION_YEAR};
+use crate::codec::{Decode, Decoder, Encode, Encoder};
+use crate::error::{BlockchainError, Result};
+use crate::fork::{BlockTree, Imported};
+use crate::mempool::{BlockLimits, Mempool, MempoolConfig};
+use crate::state::State;
+use crate::transaction::Transaction;
+use crate::SYSTEM_WALLET_ADDRESS;
+
+/// Version of the protocol, exchanged in the handshake
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Most block hashes announced in one `Inventory` in answer to `GetBlocks`
+pub const MAX_INVENTORY: usize = 500;
+
+/// How long a block or transaction asked for is awaited before it may be asked for again, in milliseconds
+pub const REQUEST_TIMEOUT_MS: u64 = 2_000;
+
+/// Interval between two rounds of `GetBlocks` to every peer, in milliseconds
+pub const SYNC_INTERVAL_MS: u64 = 5_000;
+
+// Most blocks kept while their parent is unknown; beyond it the highest is dropped, being the furthest from connecting
+const MAX_ORPHANS: usize = 256;
+
+/// Identifies a connection; assigned by the transport
+pub type PeerId = u64;
+
+/// Messages for peers, each with the peer it goes to
+pub type Outbox = Vec<(PeerId, Message)>;
+
+/// A protocol message
+#[derive(Debug, Clone, PartialEq)]
+pub enum Message {
+    Hello {
+        version: u32,
+        node_id: String,
+        chain_id: String,
+        base_root: String, // State root of the ledger the chain starts from
+        height: u64,
+        head_hash: String,
+    },
+    Inventory { blocks: Vec<String>, transactions: Vec<String> },
+    GetBlocks { locator: Vec<String> }, // Canonical hashes of the requester, newest first
+    GetData { blocks: Vec<String>, transactions: Vec<String> },
+    Block(Box<Block>),
+    Transaction(Box<Transaction>),
+}
+
+impl Message {
+    /// Short name of the message kind, for logs
+    pub fn name(&self) -> &'static str {
+        match self {
+            Message::Hello { .. } => "hello",
+            Message::Inventory { .. } => "inventory",
+            Message::GetBlocks { .. } => "getblocks",
+            Message::GetData { .. } => "getdata",
+            Message::Block(_) => "block",
+            Message::Transaction(_) => "transaction",
+        }
+    }
+}
+
+fn put_hashes(out: &mut Encoder, hashes: &[String]) {
+    out.put_seq_len(hashes.len());
+    for hash in hashes {
+        out.put_str(hash);
+    }
+}
+
+fn hashes(input: &mut Decoder) -> Result<Vec<String>> {
+    let count = input.seq_len()?;
+    (0..count).map(|_| input.string()).collect()
+}
+
+impl Encode for Message {
+    fn encode(&self, out: &mut Encoder) {
+        match self {
+            Message::Hello { version, node_id, chain_id, base_root, height, head_hash } => {
+                out.put_u8(0);
+                out.put_u32(*version);
+                out.put_str(node_id);
+                out.put_str(chain_id);
+                out.put_str(base_root);
+                out.put_u64(*height);
+                out.put_str(head_hash);
+            },
+            Message::Inventory { blocks, transactions } => {
+                out.put_u8(1);
+                put_hashes(out, blocks);
+                put_hashes(out, transactions);
+            },
+            Message::GetBlocks { locator } => {
+                out.put_u8(2);
+                put_hashes(out, locator);
+            },
+            Message::GetData { blocks, transactions } => {
+                out.put_u8(3);
+                put_hashes(out, blocks);
+                put_hashes(out, transactions);
+            },
+            Message::Block(block) => {
+                out.put_u8(4);
+                block.encode(out);
+            },
+            Message::Transaction(transaction) => {
+                out.put_u8(5);
+                transaction.encode(out);
+            },
+        }
+    }
+}
+
+impl Decode for Message {
+    fn decode(input: &mut Decoder) -> Result<Self> {
+        Ok(match input.u8()? {
+            0 => Message::Hello {
+                version: input.u32()?,
+                node_id: input.string()?,
+                chain_id: input.string()?,
+                base_root: input.string()?,
+                height: input.u64()?,
+                head_hash: input.string()?,
+            },
+            1 => Message::Inventory { blocks: hashes(input)?, transactions: hashes(input)? },
+            2 => Message::GetBlocks { locator: hashes(input)? },
+            3 => Message::GetData { blocks: hashes(input)?, transactions: hashes(input)? },
+            4 => Message::Block(Box::new(Block::decode(input)?)),
+            5 => Message::Transaction(Box::new(Transaction::decode(input)?)),
+            tag => return Err(BlockchainError::Encoding(format!("Unknown message tag {}", tag))),
+        })
+    }
+}
+
+/// What a node has seen of the network
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct NodeStats {
+    pub blocks_produced: u64,
+    pub blocks_received: u64,   // New blocks received from peers
+    pub duplicate_blocks: u64,  // Blocks received again
+    pub orphan_blocks: u64,     // Blocks received before their parent
+    pub side_chain_blocks: u64, // Blocks that did not extend the heaviest branch when they were connected
+    pub invalid_blocks: u64,
+    pub reorgs: u64,
+    pub deepest_reorg: u64,     // Most canonical blocks one reorganization undid
+    pub transactions_received: u64,
+    pub transactions_rejected: u64,
+}
+
+/// A connected peer
+#[derive(Debug)]
+struct Peer {
+    node_id: Option<String>, // Set once its `Hello` arrived
+    greeted_at: u64,         // When this node last sent it `Hello`, in milliseconds
+}
+
+/// Protocol state of one peer of the network
+#[derive(Debug)]
+pub struct Node {
+    id: String,
+    base_root: String,
+    tree: BlockTree,
+    mempool: Mempool,
+    peers: BTreeMap<PeerId, Peer>,
+    orphans: HashMap<String, Block>, // Blocks waiting for their parent, by hash
+    requested: HashMap<String, u64>, // Blocks and transactions asked for and not received yet, with when they were
+    seen_at: HashMap<String, u64>,   // When each block was first seen, in milliseconds
+    next_sync: u64,                  // When `tick` next asks every peer for blocks
+    stats: NodeStats,
+}
+
+impl Node {
+    /// Node named `id` whose chain continues from `state`
+    pub fn new(id: &str, state: State, mempool: MempoolConfig) -> Self {
+        Node {
+            id: id.to_string(),
+            base_root: state.state_root(),
+            tree: BlockTree::new(state),
+            mempool: Mempool::new(mempool),
+            peers: BTreeMap::new(),
+            orphans: HashMap::new(),
+            requested: HashMap::new(),
+            seen_at: HashMap::new(),
+            next_sync: 0,
+            stats: NodeStats::default(),
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn tree(&self) -> &BlockTree {
+        &self.tree
+    }
+
+    /// Ledger at the canonical head
+    pub fn state(&self) -> &State {
+        self.tree.state()
+    }
+
+    pub fn mempool(&self) -> &Mempool {
+        &self.mempool
+    }
+
+    pub fn stats(&self) -> &NodeStats {
+        &self.stats
+    }
+
+    /// Number of blocks waiting for their parent
+    pub fn orphan_count(&self) -> usize {
+        self.orphans.len()
+    }
+
+    /// Node ids of the peers that completed the handshake
+    pub fn peer_ids(&self) -> Vec<&str> {
+        self.peers.values().filter_map(|known| known.node_id.as_deref()).collect()
+    }
+
+    /// When the block with this hash was first seen, in milliseconds, if it was
+    pub fn block_seen_at(&self, hash: &str) -> Option<u64> {
+        self.seen_at.get(hash).copied()
+    }
+
+    fn hello(&self) -> Message {
+        Message::Hello {
+            version: PROTOCOL_VERSION,
+            node_id: self.id.clone(),
+            chain_id: self.state().chain_id().to_string(),
+            base_root: self.base_root.clone(),
+            height: self.tree.height(),
+            head_hash: self.tree.head_hash().to_string(),
+        }
+    }
+
+    /// A connection to `peer` was opened at `now` milliseconds; the node greets it
+    pub fn connect(&mut self, peer: PeerId, now: u64) -> Outbox {
+        self.peers.insert(peer, Peer { node_id: None, greeted_at: now });
+        vec![(peer, self.hello())]
+    }
+
+    /// The connection to `peer` was closed
+    pub fn disconnect(&mut self, peer: PeerId) {
+        self.peers.remove(&peer);
+        // Whatever it was asked for may be asked of another peer
+        self.requested.clear();
+    }
+
+    /// Periodic upkeep at `now` milliseconds: expire requests, repeat unanswered handshakes and sync with every peer
+    pub fn tick(&mut self, now: u64) -> Outbox {
+        self.requested.retain(|_, asked_at| now.saturating_sub(*asked_at) < REQUEST_TIMEOUT_MS);
+
+        let hello = self.hello();
+        let mut outbox = Vec::new();
+        for (id, known) in &mut self.peers {
+            if known.node_id.is_none() && now.saturating_sub(known.greeted_at) >= REQUEST_TIMEOUT_MS {
+                known.greeted_at = now;
+                outbox.push((*id, hello.clone()));
+            }
+        }
+        if now >= self.next_sync {
+            self.next_sync = now + SYNC_INTERVAL_MS;
+            let locator = self.tree.locator();
+            outbox.extend(self.broadcast(Message::GetBlocks { locator }, None));
+        }
+        outbox
+    }
+
+    /// Send `message` to every peer that completed the handshake, except `origin`
+    fn broadcast(&self, message: Message, origin: Option<PeerId>) -> Outbox {
+        self.peers.iter()
+            .filter(|(id, known)| known.node_id.is_some() && Some(**id) != origin)
+            .map(|(id, _)| (*id, message.clone()))
+            .collect()
+    }
+
+    /// Handle a message from `peer`, received at `now` milliseconds
+    ///
+    /// An error means the peer broke the protocol and should be disconnected.
+    /// Invalid blocks and transactions are not errors: they are counted and
+    /// dropped, since a peer may relay them in good faith.
+    pub fn handle(&mut self, peer: PeerId, message: Message, now: u64) -> Result<Outbox> {
+        let Some(known) = self.peers.get_mut(&peer) else {
+            return Err(BlockchainError::Network(format!("Message from unknown peer {}", peer)));
+        };
+
+        if let Message::Hello { version, node_id, chain_id, base_root, height, head_hash } = message {
+            // A repeated greeting means the peer missed this node's; answer it, though not over and over
+            if let Some(previous) = &known.node_id {
+                if *previous != node_id {
+                    return Err(BlockchainError::Network(format!("Peer {} repeated its handshake as {}", previous, node_id)));
+                }
+                if now.saturating_sub(known.greeted_at) < REQUEST_TIMEOUT_MS {
+                    return Ok(Vec::new());
+                }
+                known.greeted_at = now;
+                return Ok(vec![(peer, self.hello())]);
+            }
+            if version != PROTOCOL_VERSION {
+                return Err(BlockchainError::Network(
+                    format!("Peer {} speaks protocol version {}, not {}", node_id, version, PROTOCOL_VERSION)
+                ));
+            }
+            if chain_id != self.tree.state().chain_id() || base_root != self.base_root {
+                return Err(BlockchainError::Network(format!("Peer {} is on another chain ({})", node_id, chain_id)));
+            }
+            debug!("{}: connected to {} at block #{}", self.id, node_id, height);
+            known.node_id = Some(node_id);
+
+            // Catch up with a peer whose head is unknown here
+            if self.tree.contains(&head_hash) {
+                return Ok(Vec::new());
+            }
+            return Ok(vec![(peer, Message::GetBlocks { locator: self.tree.locator() })]);
+        }
+        // The peer's greeting may have been lost on the way; it is repeated on the next tick
+        if known.node_id.is_none() {
+            debug!("{}: ignored {} from peer {} before its handshake", self.id, message.name(), peer);
+            return Ok(Vec::new());
+        }
+
+        match message {
+            Message::Hello { .. } => unreachable!("handled above"),
+            Message::Inventory { blocks, transactions } => Ok(self.inventory(peer, blocks, transactions, now)),
+            Message::GetBlocks { locator } => Ok(self.get_blocks(peer, &locator)),
+            Message::GetData { blocks, transactions } => Ok(self.get_data(peer, &blocks, &transactions)),
+            Message::Block(block) => Ok(self.receive_block(Some(peer), *block, now)),
+            Message::Transaction(transaction) => Ok(self.receive_transaction(Some(peer), *transaction, now)),
+        }
+    }
+
+    /// Ask `peer` for the announced blocks and transactions this node lacks
+    fn inventory(&mut self, peer: PeerId, blocks: Vec<String>, transactions: Vec<String>, now: u64) -> Outbox {
+        let mut outbox = Vec::new();
+
+        // A full batch of a sync; ask for the next one right away
+        if blocks.len() >= MAX_INVENTORY {
+            if let Some(last) = blocks.last() {
+                outbox.push((peer, Message::GetBlocks { locator: vec![last.clone()] }));
+            }
+        }
+
+        let blocks: Vec<String> = blocks.into_iter()
+            .filter(|hash| !self.tree.contains(hash) && !self.orphans.contains_key(hash) && !self.requested.contains_key(hash))
+            .collect();
+        let transactions: Vec<String> = transactions.into_iter()
+            .filter(|hash| !self.mempool.contains(hash) && !self.requested.contains_key(hash))
+            .collect();
+        if blocks.is_empty() && transactions.is_empty() {
+            return outbox;
+        }
+        self.requested.extend(blocks.iter().chain(&transactions).map(|hash| (hash.clone(), now)));
+        outbox.push((peer, Message::GetData { blocks, transactions }));
+        outbox
+    }
+
+    /// Announce the canonical blocks following the last block of `locator` this node knows
+    fn get_blocks(&self, peer: PeerId, locator: &[String]) -> Outbox {
+        let (base_height, _) = self.tree.base();
+        let fork_height = locator.iter()
+            .find(|hash| self.tree.is_canonical(hash))
+            .and_then(|hash| self.tree.height_of(hash))
+            .unwrap_or(base_height);
+
+        let blocks: Vec<String> = (fork_height + 1..=self.tree.height())
+            .take(MAX_INVENTORY)
+            .filter_map(|height| self.tree.canonical_hash(height).map(str::to_string))
+            .collect();
+        if blocks.is_empty() {
+            return Vec::new();
+        }
+        vec![(peer, Message::Inventory { blocks, transactions: Vec::new() })]
+    }
+
+    /// Send `peer` the requested blocks and transactions this node has
+    fn get_data(&self, peer: PeerId, blocks: &[String], transactions: &[String]) -> Outbox {
+        let blocks = blocks.iter()
+            .filter_map(|hash| self.tree.block(hash).or_else(|| self.orphans.get(hash)))
+            .map(|block| (peer, Message::Block(Box::new(block.clone()))));
+        let transactions = transactions.iter()
+            .filter_map(|hash| self.mempool.get(hash))
+            .map(|transaction| (peer, Message::Transaction(Box::new(transaction.clone()))));
+        blocks.chain(transactions).collect()
+    }
+
+    /// Add a transaction created on this node to the mempool and announce it
+    pub fn submit_transaction(&mut self, transaction: Transaction, now: u64) -> Result<Outbox> {
+        let hash = self.mempool.submit(transaction, self.tree.state(), now / 1_000)?;
+        Ok(self.broadcast(Message::Inventory { blocks: Vec::new(), transactions: vec![hash] }, None))
+    }
+
+    fn receive_transaction(&mut self, origin: Option<PeerId>, transaction: Transaction, now: u64) -> Outbox {
+        let hash = transaction.hash();
+        self.requested.remove(&hash);
+        if self.mempool.contains(&hash) {
+            return Vec::new();
+        }
+
+        self.stats.transactions_received += 1;
+        match self.mempool.submit(transaction, self.tree.state(), now / 1_000) {
+            Ok(hash) => self.broadcast(Message::Inventory { blocks: Vec::new(), transactions: vec![hash] }, origin),
+            Err(e) => {
+                debug!("{}: rejected transaction {}: {}", self.id, hash, e);
+                self.stats.transactions_rejected += 1;
+                Vec::new()
+            },
+        }
+    }
+
+    /// Unsealed block on top of the canonical head, packed from the mempool, or
+    /// `None` if there is nothing to pack
+    ///
+    /// The block goes to the producer the consensus engine schedules, if any;
+    /// otherwise to `producer` once its account exists, and to the system
+    /// wallet until then. Only the genesis block carries no transactions.
+    pub fn block_template(&mut self, producer: &str, limits: &BlockLimits, timestamp: u64) -> Result<Option<Block>> {
+        let state = self.tree.state();
+        let producer = match state.consensus().engine().scheduled_producer(state)? {
+            Some(scheduled) => scheduled,
+            None if state.account(producer).is_some() => producer.to_string(),
+            None => SYSTEM_WALLET_ADDRESS.to_string(),
+        };
+
+        let (transactions, diff) = if state.height() == 0 {
+            (Vec::new(), state.preview(&[], timestamp, &producer)?)
+        } else {
+            let packed = self.mempool.pack(state, limits, timestamp, &producer)?;
+            if packed.transactions.is_empty() {
+                return Ok(None);
+            }
+            (packed.transactions, packed.diff)
+        };
+
+        Block::new(
+            state.height() + 1, state.tip_hash(), &transactions, state.chain_id(), BLOCK_VERSION, INCEPTION_YEAR,
+            &producer, &diff.receipts, &diff.state_root, state.next_target(), timestamp
+        ).map(Some)
+    }
+
+    /// Add a block sealed on this node and announce it if it extends the heaviest branch
+    pub fn submit_block(&mut self, block: Block, now: u64) -> Outbox {
+        self.stats.blocks_produced += 1;
+        self.receive_block(None, block, now)
+    }
+
+    /// Connect a block and any orphans waiting for it, announcing the new canonical blocks
+    fn receive_block(&mut self, origin: Option<PeerId>, block: Block, now: u64) -> Outbox {
+        let hash = block.hash.clone();
+        self.requested.remove(&hash);
+        if self.tree.contains(&hash) || self.orphans.contains_key(&hash) {
+            self.stats.duplicate_blocks += 1;
+            return Vec::new();
+        }
+        self.seen_at.entry(hash.clone()).or_insert(now);
+        if origin.is_some() {
+            self.stats.blocks_received += 1;
+        }
+
+        // Its parent is missing: keep it and sync with the peer that sent it
+        if !self.tree.contains(&block.parent_hash) {
+            self.stats.orphan_blocks += 1;
+            if self.orphans.len() >= MAX_ORPHANS {
+                let highest = self.orphans.values().max_by_key(|orphan| orphan.id).map(|orphan| orphan.hash.clone());
+                highest.and_then(|highest| self.orphans.remove(&highest));
+            }
+            self.orphans.insert(hash, block);
+            return origin.map(|peer| (peer, Message::GetBlocks { locator: self.tree.locator() })).into_iter().collect();
+        }
+
+        let mut announce = Vec::new();
+        let mut ready = vec![block];
+        while let Some(block) = ready.pop() {
+            let (id, hash) = (block.id, block.hash.clone());
+            match self.tree.import(block) {
+                Ok(Imported::Extended(_)) => announce.push(hash.clone()),
+                Ok(Imported::Reorganized { reverted, applied }) => {
+                    self.stats.reorgs += 1;
+                    self.stats.deepest_reorg = self.stats.deepest_reorg.max(reverted.len() as u64);
+                    debug!("{}: reorganized to #{} {}, undoing {} block(s)", self.id, self.tree.height(), hash, reverted.len());
+
+                    // Transactions of the undone blocks that the new branch lacks go back to the mempool
+                    for diff in &reverted {
+                        let transactions = self.tree.block(&diff.block_hash).map(|block| block.transactions.clone()).unwrap_or_default();
+                        for transaction in transactions {
+                            let _ = self.mempool.submit(transaction, self.tree.state(), now / 1_000);
+                        }
+                    }
+                    announce.retain(|announced| self.tree.is_canonical(announced));
+                    announce.extend(applied.into_iter().map(|diff| diff.block_hash));
+                },
+                Ok(Imported::SideChain) => self.stats.side_chain_blocks += 1,
+                Ok(Imported::Known) => {},
+                Err(e) => {
+                    debug!("{}: rejected block #{} {}: {}", self.id, id, hash, e);
+                    self.stats.invalid_blocks += 1;
+                },
+            }
+
+            let children: Vec<String> = self.orphans.values()
+                .filter(|orphan| orphan.parent_hash == hash)
+                .map(|orphan| orphan.hash.clone())
+                .collect();
+            ready.extend(children.iter().filter_map(|child| self.orphans.remove(child)));
+        }
+
+        if announce.is_empty() {
+            return Vec::new();
+        }
+        self.mempool.prune(self.tree.state(), now / 1_000);
+        self.broadcast(Message::Inventory { blocks: announce, transactions: Vec::new() }, origin)
+    }
+}
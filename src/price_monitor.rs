@@ -1,13 +1,15 @@
 use std::collections::VecDeque;
 use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant};
 use crossterm::{execute, terminal::{EnterAlternateScreen, LeaveAlternateScreen}, cursor::{Hide, Show}, event::{self, Event, KeyCode}};
 use tui::{backend::CrosstermBackend, Terminal, widgets::{Block, Borders, Chart, Dataset, Axis, GraphType, Paragraph, Row, Table, Cell}, symbols, layout::{Layout, Constraint, Direction, Alignment, Rect}, style::{Style, Modifier, Color}};
+use zux::clock::{Clock, SystemClock};
 
 // Lightweight price data structure for maximum performance
 #[derive(Clone, Debug)]
 struct FastPriceData {
+    #[allow(dead_code)]
     timestamp: u64,
     price: f64,
 }
@@ -35,7 +37,8 @@ struct BlockchainMarketMetrics {
     
     // Trading data (REAL from blockchain)
     trades_count: u64,
-    fees_collected: f64,
+    fees_zux: f64,   // Swap fees paid in ZUX
+    fees_usdz: f64,  // Swap fees paid in USDZ
     avg_trade_size: f64,
     
     // Network data (REAL from blockchain)
@@ -101,7 +104,8 @@ impl BlockchainMarketData {
                 market_cap: 8500000.0,
                 circulating_supply: 8500000.0,
                 trades_count: 0,
-                fees_collected: 0.0,
+                fees_zux: 0.0,
+                fees_usdz: 0.0,
                 avg_trade_size: 0.0,
                 total_blocks: 0,
                 total_transactions: 0,
@@ -127,10 +131,11 @@ impl BlockchainMarketData {
 
     // Update with COMPREHENSIVE blockchain data - extract ALL real metrics
     fn update_from_blockchain_data(&mut self, json_content: &str) {
-        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        // Points are timed by the simulation's clock, which need not be the wall clock
+        let now = self.extract_json_field(json_content, "last_update").map_or_else(|| SystemClock.now(), |time| time as u64);
         
         // Extract ALL real blockchain metrics efficiently
-        if let Some(price) = self.extract_json_field(&json_content, "current_price") {
+        if let Some(price) = self.extract_json_field(json_content, "current_price") {
             // Add to price history
             self.price_history.push_back(FastPriceData {
                 timestamp: now,
@@ -145,59 +150,73 @@ impl BlockchainMarketData {
         }
         
         // Extract ALL volume data (REAL from blockchain) - realistic timeframes for fast blockchain
-        if let Some(vol_1m) = self.extract_json_field(&json_content, "volume_1m") {
+        if let Some(vol_1m) = self.extract_json_field(json_content, "volume_1m") {
             self.metrics.volume_1m = vol_1m;
         }
-        if let Some(vol_10s) = self.extract_json_field(&json_content, "volume_10s") {
+        if let Some(vol_10s) = self.extract_json_field(json_content, "volume_10s") {
             self.metrics.volume_10s = vol_10s;
         }
-        if let Some(vol_5s) = self.extract_json_field(&json_content, "volume_5s") {
+        if let Some(vol_5s) = self.extract_json_field(json_content, "volume_5s") {
             self.metrics.volume_5s = vol_5s;
         }
         
         // Extract price changes (REAL from blockchain) - realistic timeframes
-        if let Some(change_1m) = self.extract_json_field(&json_content, "price_change_1m") {
+        if let Some(change_1m) = self.extract_json_field(json_content, "price_change_1m") {
             self.metrics.price_change_1m = change_1m;
         }
-        if let Some(change_10s) = self.extract_json_field(&json_content, "price_change_10s") {
+        if let Some(change_10s) = self.extract_json_field(json_content, "price_change_10s") {
             self.metrics.price_change_10s = change_10s;
         }
-        if let Some(change_5s) = self.extract_json_field(&json_content, "price_change_5s") {
+        if let Some(change_5s) = self.extract_json_field(json_content, "price_change_5s") {
             self.metrics.price_change_5s = change_5s;
         }
         
         // Extract high/low data (REAL from blockchain) - realistic timeframes
-        if let Some(high) = self.extract_json_field(&json_content, "high_1m") {
+        if let Some(high) = self.extract_json_field(json_content, "high_1m") {
             self.metrics.high_1m = high;
         }
-        if let Some(low) = self.extract_json_field(&json_content, "low_1m") {
+        if let Some(low) = self.extract_json_field(json_content, "low_1m") {
             self.metrics.low_1m = low;
         }
         
         // Extract market data (REAL from blockchain)
-        if let Some(liquidity) = self.extract_json_field(&json_content, "total_liquidity") {
+        if let Some(liquidity) = self.extract_json_field(json_content, "total_liquidity") {
             self.metrics.total_liquidity = liquidity;
         }
-        if let Some(mcap) = self.extract_json_field(&json_content, "market_cap") {
+        if let Some(mcap) = self.extract_json_field(json_content, "market_cap") {
             self.metrics.market_cap = mcap;
         }
-        if let Some(supply) = self.extract_json_field(&json_content, "circulating_supply") {
+        if let Some(supply) = self.extract_json_field(json_content, "circulating_supply") {
             self.metrics.circulating_supply = supply;
         }
         
         // Extract trading data (REAL from blockchain)
-        if let Some(trades) = self.extract_json_field(&json_content, "trades_count") {
+        if let Some(trades) = self.extract_json_field(json_content, "trades_count") {
             self.metrics.trades_count = trades as u64;
         }
+        if let Some(fees) = self.extract_nested_json_field(json_content, "fees_collected", "zux") {
+            self.metrics.fees_zux = fees;
+        }
+        if let Some(fees) = self.extract_nested_json_field(json_content, "fees_collected", "usdz") {
+            self.metrics.fees_usdz = fees;
+        }
+        
+        // Extract network data (REAL from blockchain)
+        if let Some(blocks) = self.extract_json_field(json_content, "total_blocks") {
+            self.metrics.total_blocks = blocks as u64;
+        }
+        if let Some(hash_rate) = self.extract_json_field(json_content, "network_hash_rate") {
+            self.metrics.network_hash_rate = hash_rate;
+        }
         
         // Extract ZUX and USDZ reserves for REAL pool utilization calculation
-        if let Some(zux_reserve) = self.extract_json_field(&json_content, "zux_reserve") {
+        if let Some(zux_reserve) = self.extract_json_field(json_content, "zux_reserve") {
             self.metrics.zux_reserve = zux_reserve;
         }
-        if let Some(usd_reserve) = self.extract_json_field(&json_content, "usd_reserve") {
+        if let Some(usd_reserve) = self.extract_json_field(json_content, "usd_reserve") {
             self.metrics.usd_reserve = usd_reserve;
         }
-        if let Some(k_constant) = self.extract_json_field(&json_content, "k_constant") {
+        if let Some(k_constant) = self.extract_json_field(json_content, "k_constant") {
             self.metrics.k_constant = k_constant;
         }
         
@@ -216,42 +235,23 @@ impl BlockchainMarketData {
         self.metrics.last_update = Instant::now();
     }
     
-    // Simple price update for fallback simulation
-    fn update_price_simple(&mut self, new_price: f64) {
-        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
-        
-        self.price_history.push_back(FastPriceData {
-            timestamp: now,
-            price: new_price,
-        });
-        
-        if self.price_history.len() > 200 {
-            self.price_history.pop_front();
-        }
-        
-        self.metrics.current_price = new_price;
-        self.generate_fast_orderbook();
-        self.add_fast_trade(new_price);
-        self.metrics.last_update = Instant::now();
-    }
-    
     // Fast JSON field extraction without full parsing
     fn extract_json_field(&self, json_content: &str, field_name: &str) -> Option<f64> {
         let search_pattern = format!("\"{}\":", field_name);
-        if let Some(start) = json_content.find(&search_pattern) {
-            let start_pos = start + search_pattern.len();
-            if let Some(end) = json_content[start_pos..].find(',') {
-                let value_str = &json_content[start_pos..start_pos+end].trim();
-                value_str.parse::<f64>().ok()
-            } else if let Some(end) = json_content[start_pos..].find('}') {
-                let value_str = &json_content[start_pos..start_pos+end].trim();
-                value_str.parse::<f64>().ok()
-            } else {
-                None
-            }
-        } else {
-            None
-        }
+        let start_pos = json_content.find(&search_pattern)? + search_pattern.len();
+        // The value ends at the next field or at the end of its object
+        let end = json_content[start_pos..].find([',', '}'])?;
+        // Amounts are written as decimal strings
+        let value_str = json_content[start_pos..start_pos+end].trim().trim_matches('"');
+        value_str.parse::<f64>().ok()
+    }
+    
+    // Same as `extract_json_field`, for a field of the object `object_name`
+    fn extract_nested_json_field(&self, json_content: &str, object_name: &str, field_name: &str) -> Option<f64> {
+        let search_pattern = format!("\"{}\":", object_name);
+        let start_pos = json_content.find(&search_pattern)? + search_pattern.len();
+        let end = json_content[start_pos..].find('}')?;
+        self.extract_json_field(&json_content[start_pos..start_pos+end+1], field_name)
     }
     
     
@@ -720,9 +720,10 @@ fn render_volume_panel(f: &mut tui::Frame<CrosstermBackend<std::io::Stdout>>, ar
 // Trading panel with real blockchain metrics and 9 decimals
 fn render_trading_panel(f: &mut tui::Frame<CrosstermBackend<std::io::Stdout>>, area: Rect, data: &BlockchainMarketData) {
     let content = format!(
-        "Total Trades: {}\nFees Collected: {:.9}\nAvg Size: {:.9}\nPool Util: {:.6}%\nLiquidity: {:.9}\nK Constant: {:.9}",
+        "Total Trades: {}\nFees (ZUX): {:.9}\nFees (USDZ): {:.9}\nAvg Size: {:.9}\nPool Util: {:.6}%\nLiquidity: {:.9}\nK Constant: {:.9}",
         data.metrics.trades_count,
-        data.metrics.fees_collected,
+        data.metrics.fees_zux,
+        data.metrics.fees_usdz,
         data.metrics.avg_trade_size,
         data.metrics.pool_utilization,
         data.metrics.total_liquidity,
@@ -0,0 +1,201 @@
+//! Merkle inclusion proofs for transactions and account balances
+//!
+//! A `TransactionProof` shows that a transaction is committed to by a block's
+//! `tx_root`; a `BalanceProof` shows that an account held given balances in
+//! the ledger state committed to by a block's `state_root`. Both are plain
+//! serializable data so the explorer can verify them without a ledger.
+//!
+//! The ledger only keeps the tree of its current tip, so balance proofs are
+//! built against the latest block; a proof for an earlier block has to be
+//! kept from when that block was the tip.
+
+use serde::{Deserialize, Serialize};
+
+use crate::amount::Amount;
+use crate::block::Block;
+use crate::error::{BlockchainError, Result};
+use crate::merkle::{Hash, SparseMerkleProof};
+use crate::state::{account_key, account_value_hash, Account};
+use crate::transaction::Transaction;
+use crate::SUPPORTED_CURRENCIES;
+
+/// One level of a transaction proof
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ProofStep {
+    pub sibling: String,
+    pub sibling_on_left: bool,
+}
+
+/// Proof that a transaction is included in a block
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TransactionProof {
+    pub block_id: u64,
+    pub tx_index: usize,
+    pub leaf_count: usize, // Leaves of the block's tree: its transactions, then its events
+    pub tx_hash: String,   // Merkle leaf of the transaction
+    pub steps: Vec<ProofStep>,
+}
+
+impl TransactionProof {
+    /// Transaction root implied by the leaf and its path
+    pub fn compute_root(&self) -> String {
+        self.steps.iter().fold(self.tx_hash.clone(), |current, step| {
+            if step.sibling_on_left {
+                Block::hash_pair(&step.sibling, &current)
+            } else {
+                Block::hash_pair(&current, &step.sibling)
+            }
+        })
+    }
+
+    /// Check that the path has a step, on the right side, at every level where
+    /// the leaf at `tx_index` has a sibling, and none elsewhere
+    fn check_path(&self) -> Result<()> {
+        let invalid = || BlockchainError::Proof(format!(
+            "Transaction proof for block #{} does not follow the path of leaf {} of {}", self.block_id, self.tx_index, self.leaf_count
+        ));
+        if self.tx_index >= self.leaf_count {
+            return Err(invalid());
+        }
+
+        let mut steps = self.steps.iter();
+        let (mut index, mut width) = (self.tx_index, self.leaf_count);
+        while width > 1 {
+            // The last node of an odd level is promoted without a sibling
+            if index ^ 1 < width {
+                match steps.next() {
+                    Some(step) if step.sibling_on_left == (index % 2 == 1) => {},
+                    _ => return Err(invalid()),
+                }
+            }
+            index /= 2;
+            width = width.div_ceil(2);
+        }
+        if steps.next().is_some() {
+            return Err(invalid());
+        }
+        Ok(())
+    }
+
+    /// Check the proof against the `tx_root` of a trusted block header
+    pub fn verify(&self, tx_root: &str) -> Result<()> {
+        self.check_path()?;
+        let computed = self.compute_root();
+        if computed != tx_root {
+            return Err(BlockchainError::Proof(
+                format!("Transaction proof for block #{} leads to {}, expected {}", self.block_id, computed, tx_root)
+            ));
+        }
+        Ok(())
+    }
+
+    /// Check that the proof is for `transaction` and that it is included under `tx_root`
+    pub fn verify_transaction(&self, transaction: &Transaction, tx_root: &str) -> Result<()> {
+        if Block::transaction_leaf(transaction) != self.tx_hash {
+            return Err(BlockchainError::Proof("Transaction proof is for a different transaction".to_string()));
+        }
+        self.verify(tx_root)
+    }
+}
+
+/// A non-empty sibling on the path from an account leaf to the state root
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ProofSibling {
+    pub depth: u16,
+    pub hash: String,
+}
+
+/// Proof that an account held the listed balances at a given block
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BalanceProof {
+    pub block_id: u64,
+    pub address: String,
+    pub public_key: Option<String>, // Hex encoded bound key, if any
+    pub nonce: u64,
+    pub balances: Vec<(String, Amount)>, // Currency code, balance
+    pub siblings: Vec<ProofSibling>,
+}
+
+impl BalanceProof {
+    /// Build a proof from an account leaf and its tree path
+    ///
+    /// The proof lists the balance of every supported currency, as the leaf commits to them.
+    pub fn new(block_id: u64, address: &str, account: &Account, path: &SparseMerkleProof) -> Self {
+        let mut balances: Vec<(String, Amount)> = SUPPORTED_CURRENCIES.iter()
+            .map(|currency| ((*currency).to_string(), account.balances.get(*currency).copied().unwrap_or_default()))
+            .collect();
+        balances.sort_by(|a, b| a.0.cmp(&b.0));
+
+        BalanceProof {
+            block_id,
+            address: address.to_string(),
+            public_key: account.public_key.as_ref().map(hex::encode),
+            nonce: account.nonce,
+            balances,
+            siblings: path.siblings.iter()
+                .map(|(depth, hash)| ProofSibling { depth: *depth, hash: hex::encode(hash) })
+                .collect(),
+        }
+    }
+
+    /// Balance of `currency` asserted by this proof; only meaningful once the proof verifies
+    pub fn balance(&self, currency: &str) -> Amount {
+        self.balances.iter()
+            .find(|(code, _)| code == currency)
+            .map(|(_, balance)| *balance)
+            .unwrap_or_default()
+    }
+
+    /// State root implied by the account leaf and its path
+    pub fn compute_root(&self) -> Result<String> {
+        let public_key = self.public_key.as_ref()
+            .map(hex::decode)
+            .transpose()
+            .map_err(|e| BlockchainError::Proof(format!("Invalid public key in balance proof: {}", e)))?;
+
+        // Exactly one balance per supported currency, so the proof cannot assert one it does not commit to
+        for (index, (currency, _)) in self.balances.iter().enumerate() {
+            if !SUPPORTED_CURRENCIES.contains(&currency.as_str()) {
+                return Err(BlockchainError::Proof(format!("Balance proof lists unsupported currency {}", currency)));
+            }
+            if self.balances[..index].iter().any(|(earlier, _)| earlier == currency) {
+                return Err(BlockchainError::Proof(format!("Balance proof lists {} more than once", currency)));
+            }
+        }
+        if self.balances.len() != SUPPORTED_CURRENCIES.len() {
+            return Err(BlockchainError::Proof(format!(
+                "Balance proof lists {} currencies, not the {} supported ones", self.balances.len(), SUPPORTED_CURRENCIES.len()
+            )));
+        }
+
+        let account = Account {
+            public_key,
+            nonce: self.nonce,
+            balances: self.balances.iter().cloned().collect(),
+        };
+
+        let siblings = self.siblings.iter()
+            .map(|sibling| {
+                let hash: Hash = hex::decode(&sibling.hash).ok()
+                    .and_then(|bytes| bytes.try_into().ok())
+                    .ok_or_else(|| BlockchainError::Proof(format!("Invalid sibling hash: {}", sibling.hash)))?;
+                Ok((sibling.depth, hash))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let path = SparseMerkleProof { siblings };
+        let root = path.compute_root(&account_key(&self.address), Some(&account_value_hash(&self.address, &account)));
+        Ok(hex::encode(root))
+    }
+
+    /// Check the proof against the `state_root` of a trusted block header
+    pub fn verify(&self, state_root: &str) -> Result<()> {
+        let computed = self.compute_root()?;
+        if computed != state_root {
+            return Err(BlockchainError::Proof(
+                format!("Balance proof for {} at block #{} leads to {}, expected {}", self.address, self.block_id, computed, state_root)
+            ));
+        }
+        Ok(())
+    }
+}
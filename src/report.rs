@@ -0,0 +1,222 @@
+//! Machine-readable summary of a finished run
+//!
+//! `run_simulation` returns a `RunReport` with the final pool, statistics over
+//! the price path of the swap phase, how the trading wallets fared and how
+//! they took part, and can write it as JSON or as a one-row CSV table for CI
+//! jobs and parameter sweeps.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::amm::SwapFees;
+use crate::amount::Amount;
+use crate::config::SimulationConfig;
+use crate::error::{BlockchainError, Result};
+
+/// The AMM pool at the end of a run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PoolReport {
+    pub zux_reserve: Amount,
+    pub usd_reserve: Amount,
+    pub opening_zux_reserve: Amount, // When trading started
+    pub opening_usd_reserve: Amount,
+    pub price: f64,                  // USDZ per ZUX
+    pub fees_collected: SwapFees,
+    pub network_fees: Amount,        // Paid by the swaps to block producers, in ZUX
+    pub lp_return_percent: f64,      // Pool value against holding the opening reserves, both at the final price
+}
+
+impl PoolReport {
+    /// Return of providing the opening reserves as liquidity rather than holding
+    /// them, valued at `price`: swap fees earned less impermanent loss
+    pub fn lp_return(opening: (Amount, Amount), closing: (Amount, Amount), price: f64) -> f64 {
+        let held = opening.0.to_f64() * price + opening.1.to_f64();
+        let pooled = closing.0.to_f64() * price + closing.1.to_f64();
+        if held > 0.0 { (pooled / held - 1.0) * 100.0 } else { 0.0 }
+    }
+}
+
+/// Pool price after every swap block, starting from the price trading began at
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PriceStats {
+    pub initial: f64,
+    pub last: f64,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub change_percent: f64,       // From the initial to the last price
+    pub volatility_percent: f64,   // Standard deviation of the price change per block
+    pub max_drawdown_percent: f64, // Largest fall from a running high
+}
+
+impl PriceStats {
+    /// Statistics of a price path; an empty path has none
+    pub fn from_path(prices: &[f64]) -> Self {
+        let (Some(&initial), Some(&last)) = (prices.first(), prices.last()) else {
+            return PriceStats::default();
+        };
+        let count = prices.len() as f64;
+        let mean = prices.iter().sum::<f64>() / count;
+
+        // Per-block returns; a zero price has no return
+        let returns: Vec<f64> = prices.windows(2)
+            .filter(|pair| pair[0] > 0.0)
+            .map(|pair| pair[1] / pair[0] - 1.0)
+            .collect();
+        let volatility = if returns.len() > 1 {
+            let mean_return = returns.iter().sum::<f64>() / returns.len() as f64;
+            let variance = returns.iter().map(|r| (r - mean_return).powi(2)).sum::<f64>() / (returns.len() - 1) as f64;
+            variance.sqrt()
+        } else {
+            0.0
+        };
+
+        let mut high = f64::NEG_INFINITY;
+        let mut max_drawdown: f64 = 0.0;
+        for &price in prices {
+            high = high.max(price);
+            if high > 0.0 {
+                max_drawdown = max_drawdown.max(1.0 - price / high);
+            }
+        }
+
+        PriceStats {
+            initial,
+            last,
+            min: prices.iter().copied().fold(f64::INFINITY, f64::min),
+            max: prices.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            mean,
+            change_percent: if initial > 0.0 { (last / initial - 1.0) * 100.0 } else { 0.0 },
+            volatility_percent: volatility * 100.0,
+            max_drawdown_percent: max_drawdown * 100.0,
+        }
+    }
+}
+
+/// How one trading wallet fared, valued in USDZ at the final pool price
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WalletPerformance {
+    pub address: String,
+    pub performance_percent: f64,
+    pub pnl_usdz: f64,
+    pub initial_zux: Amount,
+    pub final_zux: Amount,
+    pub initial_usdz: Amount,
+    pub final_usdz: Amount,
+}
+
+/// How the trading wallets fared and took part
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WalletReport {
+    pub total: usize,
+    pub profitable: usize,
+    pub profitable_ratio: f64,
+    pub total_pnl_usdz: f64,            // Summed over every trading wallet
+    pub participating: usize,           // Wallets with at least one executed swap
+    pub participation_rate: f64,
+    pub average_trades: f64,            // Per participating wallet
+    pub max_trades: usize,
+    pub min_trades: usize,              // Of a participating wallet
+    pub top: Vec<WalletPerformance>,    // Best first
+    pub bottom: Vec<WalletPerformance>, // Worst first
+}
+
+/// Summary of a finished run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunReport {
+    pub seed: Option<u64>,
+    pub consensus: String,
+    pub config: SimulationConfig,
+    pub height: u64,
+    pub tip_hash: String,
+    pub swaps: u64,
+    pub swap_blocks: u64,
+    pub rejected_transactions: u64, // Refused by the mempool or dropped when packing
+    pub zux_traded: Amount,
+    pub usdz_traded: Amount,
+    pub elapsed_secs: f64,          // Wall time of the swap phase
+    pub pool: PoolReport,
+    pub price: PriceStats,
+    pub wallets: WalletReport,
+}
+
+// Scalar fields of a report, in CSV column order
+const CSV_COLUMNS: [&str; 34] = [
+    "seed", "consensus", "wallet_count", "whale_probability", "mega_whale_probability", "fee_percent", "liquidity_fraction",
+    "target_price", "total_transactions",
+    "height", "tip_hash", "swaps", "swap_blocks", "rejected_transactions", "zux_traded", "usdz_traded", "elapsed_secs",
+    "zux_reserve", "usd_reserve", "final_price", "swap_fees_zux", "swap_fees_usdz", "network_fees", "lp_return_percent",
+    "price_min", "price_max", "price_change_percent", "volatility_percent", "max_drawdown_percent",
+    "profitable_wallets", "profitable_ratio", "total_pnl_usdz", "participation_rate", "average_trades",
+];
+
+/// Quote a CSV field if it needs it
+fn csv_field(value: String) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+impl RunReport {
+    /// Header of the CSV table `csv_row` fills
+    pub fn csv_header() -> String {
+        CSV_COLUMNS.join(",")
+    }
+
+    /// The report's scalar fields as one CSV row
+    pub fn csv_row(&self) -> String {
+        let values = [
+            self.seed.map(|seed| seed.to_string()).unwrap_or_default(),
+            self.consensus.clone(),
+            self.config.wallet_count.to_string(),
+            self.config.whale_probability.to_string(),
+            self.config.mega_whale_probability.to_string(),
+            self.config.fee_percent.to_string(),
+            self.config.liquidity_fraction.to_string(),
+            self.config.target_price.to_string(),
+            self.config.total_transactions.to_string(),
+            self.height.to_string(),
+            self.tip_hash.clone(),
+            self.swaps.to_string(),
+            self.swap_blocks.to_string(),
+            self.rejected_transactions.to_string(),
+            self.zux_traded.to_string(),
+            self.usdz_traded.to_string(),
+            format!("{:.3}", self.elapsed_secs),
+            self.pool.zux_reserve.to_string(),
+            self.pool.usd_reserve.to_string(),
+            self.pool.price.to_string(),
+            self.pool.fees_collected.zux.to_string(),
+            self.pool.fees_collected.usdz.to_string(),
+            self.pool.network_fees.to_string(),
+            self.pool.lp_return_percent.to_string(),
+            self.price.min.to_string(),
+            self.price.max.to_string(),
+            self.price.change_percent.to_string(),
+            self.price.volatility_percent.to_string(),
+            self.price.max_drawdown_percent.to_string(),
+            self.wallets.profitable.to_string(),
+            self.wallets.profitable_ratio.to_string(),
+            self.wallets.total_pnl_usdz.to_string(),
+            self.wallets.participation_rate.to_string(),
+            self.wallets.average_trades.to_string(),
+        ];
+        values.into_iter().map(csv_field).collect::<Vec<_>>().join(",")
+    }
+
+    /// Write the report to `path`, as CSV if its extension is `.csv` and as JSON otherwise
+    pub fn write_to(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let contents = if path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("csv")) {
+            format!("{}\n{}\n", Self::csv_header(), self.csv_row())
+        } else {
+            serde_json::to_string_pretty(self)
+                .map_err(|e| BlockchainError::System(format!("Failed to serialize run report: {}", e)))?
+        };
+        std::fs::write(path, contents)?;
+        Ok(())
+    }
+}
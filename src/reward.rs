@@ -0,0 +1,82 @@
+//! Block reward schedule
+//!
+//! Every block after genesis issues new ZUX to its producer, on top of the
+//! network fees paid by the transactions it includes. The reward halves every
+//! `halving_interval` blocks until it rounds down to nothing, so the total
+//! ever issued is bounded.
+
+use serde::{Deserialize, Serialize};
+
+use crate::amount::Amount;
+use crate::error::Result;
+
+/// How much new ZUX each block issues to its producer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RewardSchedule {
+    pub initial_reward: Amount, // Reward of block #2, the first one after genesis
+    pub halving_interval: u64,  // Blocks between two halvings, 0 never halves
+}
+
+impl Default for RewardSchedule {
+    fn default() -> Self {
+        RewardSchedule {
+            initial_reward: Amount::from_tokens(50),
+            halving_interval: 10_000,
+        }
+    }
+}
+
+impl RewardSchedule {
+    pub fn new(initial_reward: Amount, halving_interval: u64) -> Self {
+        RewardSchedule { initial_reward, halving_interval }
+    }
+
+    /// Halvings applied to the reward of block `height`
+    fn halvings(&self, height: u64) -> u64 {
+        match self.halving_interval {
+            0 => 0,
+            interval => (height - 2) / interval,
+        }
+    }
+
+    /// New ZUX issued to the producer of block `height`; genesis issues nothing
+    pub fn reward_at(&self, height: u64) -> Amount {
+        if height < 2 {
+            return Amount::ZERO;
+        }
+        match self.halvings(height) {
+            halvings if halvings >= u128::BITS as u64 => Amount::ZERO,
+            halvings => Amount::from_base_units(self.initial_reward.base_units() >> halvings),
+        }
+    }
+
+    /// Total issued by the blocks up to and including `height`
+    pub fn total_issued(&self, height: u64) -> Result<Amount> {
+        if height < 2 || self.initial_reward.is_zero() {
+            return Ok(Amount::ZERO);
+        }
+
+        let rewarded_blocks = height - 1;
+        let interval = match self.halving_interval {
+            0 => return self.initial_reward.mul_div(u128::from(rewarded_blocks), 1),
+            interval => interval,
+        };
+
+        // Whole eras at a constant reward, until it reaches zero or the height is passed
+        let mut total = Amount::ZERO;
+        let mut first = 2;
+        while first <= height {
+            let reward = self.reward_at(first);
+            if reward.is_zero() {
+                break;
+            }
+            let blocks = interval.min(height - first + 1);
+            total = total.checked_add(reward.mul_div(u128::from(blocks), 1)?)?;
+            first = match first.checked_add(interval) {
+                Some(next) => next,
+                None => break,
+            };
+        }
+        Ok(total)
+    }
+}
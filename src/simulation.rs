@@ -0,0 +1,1195 @@
+//! End-to-end simulation driver used by the `practicerust2` binary
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use ed25519_dalek::SigningKey;
+use log::{debug, info, warn};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+
+use crate::address::UniqueCodeGenerator;
+use crate::agent::create_intelligent_swap;
+use crate::amount::Amount;
+use crate::clock::{Clock, SimulatedClock, SystemClock};
+use crate::config::SimulationConfig;
+use crate::block::{Block, BlockEvent, BLOCK_VERSION};
+use crate::consensus::{Authority, ConsensusRule, EngineKind, ProofOfAuthority, ProofOfStake, ProofOfWork, Sealer};
+use crate::difficulty::{estimate_hash_rate, RetargetRule, HASH_RATE_WINDOW};
+use crate::miner::Miner;
+use crate::state::{State, StateDiff};
+use crate::store::{verify_chain, ChainStore, FileStore};
+use crate::transaction::{create_wallet_registration, Transaction, TxKind};
+use crate::error::{BlockchainError, Result};
+use crate::explorer::update_explorer_data;
+use crate::launcher::{Companion, LaunchMode, Launcher};
+use crate::mempool::{BlockLimits, Mempool, MempoolConfig};
+use crate::market::{run_price_monitor, NetworkStats};
+use crate::report::{PoolReport, PriceStats, RunReport, WalletPerformance, WalletReport};
+use crate::reward::RewardSchedule;
+use crate::snapshot::Snapshot;
+use crate::transaction::create_transaction;
+use crate::wallet::{create_authority_wallet, create_system_wallet, create_wallet_without_balance, Wallet};
+use crate::{SUPPORTED_CURRENCIES, SYSTEM_WALLET_ADDRESS};
+
+/// Directory the chain store is kept in unless another one is given
+pub const DEFAULT_DATA_DIR: &str = "zux_chain";
+
+// Swaps the trading wallets submit to the mempool between two blocks; more
+// than a block holds, so the pool backs up and fees decide who gets in
+const SWAPS_SUBMITTED_PER_BLOCK: usize = 30;
+
+// Rounds in a row without an executed swap after which a run gives up
+const MAX_EMPTY_ROUNDS: u32 = 100;
+
+// Wallet checkpoint written next to the chain store
+const CHECKPOINT_FILE: &str = "wallets.json";
+
+/// Snapshot written once the setup blocks are produced, to start later runs from
+pub const SETUP_SNAPSHOT_FILE: &str = "setup.snapshot";
+
+// Copy of the snapshot a chain was continued from, needed to replay it
+const BASE_SNAPSHOT_FILE: &str = "base.snapshot";
+
+// Consensus rule of a chain started from genesis, needed to replay it
+const CONSENSUS_FILE: &str = "consensus.json";
+
+// Scenario a chain was started from genesis with, needed to replay it
+const SCENARIO_FILE: &str = "scenario.json";
+
+// Key the system account of a chain started from genesis is bound to, needed to replay it
+const SYSTEM_KEY_FILE: &str = "system_key.json";
+
+/// Report a headless run writes next to its chain unless another path is given
+pub const REPORT_FILE: &str = "report.json";
+
+// Authorities taking turns under proof-of-authority
+const AUTHORITY_COUNT: usize = 3;
+
+// Time a simulated clock starts from, 2025-01-01T00:00:00Z, in milliseconds
+const SIMULATED_CLOCK_START_MS: u64 = 1_735_689_600_000;
+
+// Simulated time between two blocks of a seeded run unless another interval is given
+const SEEDED_BLOCK_INTERVAL_MS: u64 = 1_000;
+
+/// Where a run keeps its chain and whether it continues an earlier run
+#[derive(Debug, Clone)]
+pub struct SimulationOptions {
+    pub data_dir: PathBuf,               // Chain store and wallet checkpoint
+    pub resume: bool,                    // Continue from the last stored block instead of starting a new chain
+    pub from_snapshot: Option<PathBuf>,  // Start a new chain from this snapshot instead of producing the setup blocks
+    pub consensus: EngineKind,           // Engine a new chain runs under; existing chains keep theirs
+    pub block_interval_ms: Option<u64>,  // Simulated time between two blocks; the wall clock times the run when unset
+    pub seed: Option<u64>,               // Seeds every random decision, for a run that can be replayed block for block
+    pub config: SimulationConfig,        // Economy of a new chain and length of the trading phase
+    pub launch: LaunchMode,              // Where the price monitor and explorer are opened
+    pub headless: bool,                  // Batch run: no block printing, feeds, companions or delays
+    pub report: Option<PathBuf>,         // Write the run report here, as JSON or CSV by extension
+}
+
+impl Default for SimulationOptions {
+    fn default() -> Self {
+        SimulationOptions {
+            data_dir: PathBuf::from(DEFAULT_DATA_DIR),
+            resume: false,
+            from_snapshot: None,
+            consensus: EngineKind::default(),
+            block_interval_ms: None,
+            seed: None,
+            config: SimulationConfig::default(),
+            launch: LaunchMode::default(),
+            headless: false,
+            report: None,
+        }
+    }
+}
+
+/// Ledger the simulated chain starts from
+///
+/// The system wallet's supply is allocated at genesis and released when its
+/// account is created, which only the holder of `system_key` can do; blocks
+/// then issue rewards on the default schedule and follow the default retarget
+/// rule, sealed under `consensus`.
+pub fn genesis_state(consensus: ConsensusRule, system_key: &[u8]) -> State {
+    genesis_state_with(consensus, RetargetRule::default(), system_key)
+}
+
+/// Ledger the simulated chain starts from, retargeting by `retarget`
+pub fn genesis_state_with(consensus: ConsensusRule, retarget: RetargetRule, system_key: &[u8]) -> State {
+    genesis_state_from(&SimulationConfig::default(), consensus, retarget, system_key)
+}
+
+/// Ledger a chain of the scenario `config` starts from
+pub fn genesis_state_from(config: &SimulationConfig, consensus: ConsensusRule, retarget: RetargetRule, system_key: &[u8]) -> State {
+    State::new(
+        &config.network_name,
+        &[(SYSTEM_WALLET_ADDRESS, "ZUX", config.zux_supply), (SYSTEM_WALLET_ADDRESS, "USDZ", config.usdz_supply)],
+        &[(SYSTEM_WALLET_ADDRESS, system_key)],
+        config.fee_bps(),
+        RewardSchedule::default(),
+        retarget,
+        consensus
+    )
+}
+
+/// Wallets of the block authorities a new chain under `kind` needs
+///
+/// Proof-of-authority takes turns between several, proof-of-stake needs one to
+/// produce blocks until some account holds ZUX, and proof-of-work needs none.
+fn create_authorities(kind: EngineKind, rng: &mut StdRng) -> Vec<Wallet> {
+    let count = match kind {
+        EngineKind::ProofOfWork => 0,
+        EngineKind::ProofOfAuthority => AUTHORITY_COUNT,
+        EngineKind::ProofOfStake => 1,
+    };
+    (1..=count).map(|index| create_authority_wallet(index, rng)).collect()
+}
+
+/// Consensus rule of a new chain under `kind`, sealed by `authorities`
+fn consensus_rule(kind: EngineKind, authorities: &[Wallet]) -> ConsensusRule {
+    let mut authorities = authorities.iter().map(|wallet| Authority {
+        address: wallet.address.clone(),
+        public_key: wallet.public_key.clone(),
+    });
+    match (kind, authorities.next()) {
+        (EngineKind::ProofOfAuthority, Some(first)) => ConsensusRule::ProofOfAuthority(ProofOfAuthority {
+            authorities: std::iter::once(first).chain(authorities).collect(),
+        }),
+        (EngineKind::ProofOfStake, Some(bootstrap)) => ConsensusRule::ProofOfStake(ProofOfStake { bootstrap }),
+        _ => ConsensusRule::ProofOfWork(ProofOfWork),
+    }
+}
+
+/// Ledger the chain stored in `data_dir` continues from
+///
+/// This is the genesis state of the chain's recorded scenario under its
+/// recorded consensus rule and system key, or the snapshot ledger for a chain
+/// started with `--from-snapshot`.
+pub fn base_state(data_dir: &Path) -> Result<State> {
+    match Snapshot::read_from(data_dir.join(BASE_SNAPSHOT_FILE)) {
+        Ok(snapshot) => State::from_snapshot(&snapshot.ledger),
+        Err(BlockchainError::Io(e)) if e.kind() == std::io::ErrorKind::NotFound =>
+            Ok(genesis_state_from(&read_scenario(data_dir)?, read_consensus(data_dir)?, RetargetRule::default(), &read_system_key(data_dir)?)),
+        Err(e) => Err(e),
+    }
+}
+
+/// Record the consensus rule of a chain started from genesis
+fn write_consensus(data_dir: &Path, consensus: &ConsensusRule) -> Result<()> {
+    let consensus_json = serde_json::to_vec_pretty(consensus)
+        .map_err(|e| BlockchainError::System(format!("Failed to serialize consensus rule: {}", e)))?;
+    std::fs::write(data_dir.join(CONSENSUS_FILE), consensus_json)?;
+    Ok(())
+}
+
+/// Consensus rule recorded for the chain in `data_dir`; chains without one are proof-of-work
+fn read_consensus(data_dir: &Path) -> Result<ConsensusRule> {
+    let path = data_dir.join(CONSENSUS_FILE);
+    match std::fs::read(&path) {
+        Ok(bytes) => serde_json::from_slice(&bytes)
+            .map_err(|e| BlockchainError::System(format!("Invalid consensus rule {}: {}", path.display(), e))),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(ConsensusRule::default()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Record the key the system account of a chain started from genesis is bound to
+fn write_system_key(data_dir: &Path, public_key: &[u8]) -> Result<()> {
+    let key_json = serde_json::to_vec_pretty(&hex::encode(public_key))
+        .map_err(|e| BlockchainError::System(format!("Failed to serialize system key: {}", e)))?;
+    std::fs::write(data_dir.join(SYSTEM_KEY_FILE), key_json)?;
+    Ok(())
+}
+
+/// Key the system account of the chain in `data_dir` is bound to
+fn read_system_key(data_dir: &Path) -> Result<Vec<u8>> {
+    let path = data_dir.join(SYSTEM_KEY_FILE);
+    let key_hex: String = serde_json::from_slice(&std::fs::read(&path)?)
+        .map_err(|e| BlockchainError::System(format!("Invalid system key {}: {}", path.display(), e)))?;
+    hex::decode(&key_hex).map_err(|e| BlockchainError::System(format!("Invalid system key {}: {}", path.display(), e)))
+}
+
+/// Record the scenario of a chain started from genesis
+fn write_scenario(data_dir: &Path, config: &SimulationConfig) -> Result<()> {
+    let scenario_json = serde_json::to_vec_pretty(config)
+        .map_err(|e| BlockchainError::System(format!("Failed to serialize scenario: {}", e)))?;
+    std::fs::write(data_dir.join(SCENARIO_FILE), scenario_json)?;
+    Ok(())
+}
+
+/// Scenario recorded for the chain in `data_dir`; chains without one ran the default scenario
+fn read_scenario(data_dir: &Path) -> Result<SimulationConfig> {
+    match SimulationConfig::read_from(data_dir.join(SCENARIO_FILE)) {
+        Err(BlockchainError::Io(e)) if e.kind() == std::io::ErrorKind::NotFound => Ok(SimulationConfig::default()),
+        scenario => scenario,
+    }
+}
+
+/// Verify the chain stored in `data_dir` from its base to its tip, returning the number of blocks checked
+pub fn verify_stored_chain(data_dir: &Path) -> Result<u64> {
+    let store = FileStore::open_read_only(data_dir)?;
+    verify_chain(&store, &mut base_state(data_dir)?)
+}
+
+/// Delete `path`, which may not exist
+fn remove_stale(path: &Path) -> Result<()> {
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+/// Wallet keys and trading profiles of a run; they are not on chain but are needed to resume it
+///
+/// The file holds private keys, so it stays inside the data directory.
+#[derive(Serialize, Deserialize)]
+struct Checkpoint {
+    swap_start_height: u64, // Height of the last setup block
+    wallets: Vec<Wallet>,
+    authorities: Vec<Wallet>, // Block authorities of a signed consensus engine
+}
+
+impl Checkpoint {
+    fn write(data_dir: &Path, swap_start_height: u64, wallets: &HashMap<String, Wallet>, authorities: &[Wallet]) -> Result<()> {
+        let checkpoint = Checkpoint {
+            swap_start_height,
+            wallets: wallets.values().cloned().collect(),
+            authorities: authorities.to_vec(),
+        };
+        let checkpoint_json = serde_json::to_vec(&checkpoint)
+            .map_err(|e| BlockchainError::System(format!("Failed to serialize checkpoint: {}", e)))?;
+        std::fs::write(data_dir.join(CHECKPOINT_FILE), checkpoint_json)?;
+        Ok(())
+    }
+}
+
+// Wallets, their balances when trading started, the swap blocks already produced and the block authorities
+type ResumedRun = (HashMap<String, Wallet>, HashMap<String, (Amount, Amount)>, Vec<Block>, Vec<Wallet>);
+
+/// Running totals over the swap phase of a run
+#[derive(Default)]
+struct SwapStats {
+    swap_count: u64,
+    network_fees: Amount, // Paid by the swaps to the block producer
+    total_zux_traded: Amount,
+    total_usdz_traded: Amount,
+    wallet_trade_counts: HashMap<String, usize>,
+    swap_blocks: u64,
+    rejected_transactions: u64, // Refused by the mempool or dropped when they failed to execute
+}
+
+impl SwapStats {
+    /// Account for the swaps of one block
+    fn record(&mut self, block: &Block) -> Result<()> {
+        for event in &block.events {
+            let (wallet_address, is_zux_to_usd, input_amount, output_amount) = match event {
+                BlockEvent::Swap(address, is_zux_to_usd, input_amount, output_amount) =>
+                    (address, *is_zux_to_usd, *input_amount, *output_amount),
+                _ => return Err(BlockchainError::Block(format!("Block #{} executed a non-swap {}", block.id, event.name()))),
+            };
+            
+            // Track wallet participation
+            *self.wallet_trade_counts.entry(wallet_address.clone()).or_insert(0) += 1;
+            
+            // Track trading volume
+            let (zux_amount, usdz_amount) = if is_zux_to_usd {
+                (input_amount, output_amount)
+            } else {
+                (output_amount, input_amount)
+            };
+            self.total_zux_traded = self.total_zux_traded.checked_add(zux_amount)?;
+            self.total_usdz_traded = self.total_usdz_traded.checked_add(usdz_amount)?;
+            
+            self.swap_count += 1;
+        }
+        let block_fees = Amount::checked_sum(block.transactions.iter().map(|tx| tx.fee))?;
+        self.network_fees = self.network_fees.checked_add(block_fees)?;
+        self.swap_blocks += 1;
+        Ok(())
+    }
+}
+
+/// Seals blocks on top of the ledger tip, applies them and stores them
+///
+/// Each block is produced by the account the chain's consensus engine
+/// schedules, or by the system wallet if any account may produce it, and is
+/// timestamped by the run's clock, which ticks once it is sealed.
+struct BlockProducer<'a> {
+    block_ver: &'a str,
+    inception_year: u16,
+    store: &'a mut dyn ChainStore,
+    clock: Arc<dyn Clock>,
+    miner: Miner,
+    cancel: AtomicBool,     // Abandons the block being mined; a single node never receives a competing one
+    keys: HashMap<String, SigningKey>, // Keys of the accounts this node can sign blocks for
+    hashes: u64,            // Hashes computed by the miner so far
+    mining_time: Duration,  // Time spent mining so far
+    print_blocks: bool,     // Print every block as it is sealed
+}
+
+impl<'a> BlockProducer<'a> {
+    /// Producer sealing blocks with `miner` and no keys yet, dated `inception_year`
+    fn new(store: &'a mut dyn ChainStore, clock: Arc<dyn Clock>, miner: Miner, inception_year: u16) -> Self {
+        BlockProducer {
+            block_ver: BLOCK_VERSION,
+            inception_year,
+            store,
+            clock,
+            miner,
+            cancel: AtomicBool::new(false),
+            keys: HashMap::new(),
+            hashes: 0,
+            mining_time: Duration::ZERO,
+            print_blocks: true,
+        }
+    }
+    
+    /// Let this node sign blocks for `wallets`
+    fn add_keys<'w>(&mut self, wallets: impl IntoIterator<Item = &'w Wallet>) -> Result<()> {
+        for wallet in wallets {
+            self.keys.insert(wallet.address.clone(), wallet.get_signing_key()?);
+        }
+        Ok(())
+    }
+    
+    /// Account producing the block on top of `state`
+    fn producer_for(state: &State) -> Result<String> {
+        Ok(state.consensus().engine().scheduled_producer(state)?
+            .unwrap_or_else(|| SYSTEM_WALLET_ADDRESS.to_string()))
+    }
+    
+    /// Hashes per second measured over every block mined so far
+    fn hash_rate(&self) -> f64 {
+        self.hashes as f64 / self.mining_time.as_secs_f64().max(f64::EPSILON)
+    }
+    
+    /// Produce a block for the given transactions (none for genesis); its events
+    /// are the receipts the ledger derives by executing them
+    fn produce(&mut self, state: &mut State, transactions: &[Transaction]) -> Result<(Block, StateDiff)> {
+        let timestamp = self.clock.now();
+        let producer = Self::producer_for(state)?;
+        let diff = state.preview(transactions, timestamp, &producer)?;
+        self.seal(state, &producer, transactions, &diff.receipts, &diff.state_root, timestamp)
+    }
+    
+    /// Produce a block from the best-paying pending transactions that execute,
+    /// or nothing if none do; returns the number of transactions dropped as well
+    fn produce_from(&mut self, state: &mut State, mempool: &mut Mempool, limits: &BlockLimits)
+            -> Result<(Option<(Block, StateDiff)>, usize)> {
+        let timestamp = self.clock.now();
+        let producer = Self::producer_for(state)?;
+        let packed = mempool.pack(state, limits, timestamp, &producer)?;
+        for (hash, error) in &packed.dropped {
+            debug!("Dropped transaction {}: {}", hash, error);
+        }
+        if packed.transactions.is_empty() {
+            return Ok((None, packed.dropped.len()));
+        }
+        
+        let produced = self.seal(state, &producer, &packed.transactions, &packed.diff.receipts, &packed.diff.state_root, timestamp)?;
+        mempool.prune(state, timestamp);
+        Ok((Some(produced), packed.dropped.len()))
+    }
+    
+    /// Seal the next block, apply it to the ledger, store it and print it unless told not to
+    fn seal(&mut self, state: &mut State, producer: &str, transactions: &[Transaction], events: &[BlockEvent],
+            state_root: &str, timestamp: u64) -> Result<(Block, StateDiff)> {
+        let mut block = Block::new(
+            state.height() + 1,
+            state.tip_hash(),
+            transactions,
+            state.chain_id(),
+            self.block_ver,
+            self.inception_year,
+            producer,
+            events,
+            state_root,
+            state.next_target(),
+            timestamp
+        )?;
+        let sealer = Sealer { miner: &self.miner, cancel: &self.cancel, key: self.keys.get(producer) };
+        let sealed = state.consensus().engine().seal(&mut block, &sealer)?
+            .ok_or_else(|| BlockchainError::Block(format!("Sealing of block #{} was cancelled", block.id)))?;
+        self.hashes += sealed.hashes;
+        self.mining_time += sealed.elapsed;
+        debug!("Sealed block #{} in {} hashes ({:.0} H/s on {} thread(s))", block.id, sealed.hashes, sealed.hash_rate(), sealed.threads);
+        
+        // Never store a block that would not verify for a reader
+        block.verify().map_err(|e| BlockchainError::Block(format!("Produced block #{} failed verification: {}", block.id, e)))?;
+        let diff = state.apply_block(&block)?;
+        self.store.append(&block)?;
+        self.clock.tick();
+        
+        // Print block information
+        if self.print_blocks {
+            block.print();
+        }
+        
+        Ok((block, diff))
+    }
+}
+
+/// Refresh the local wallet views with the balances and nonces a block changed
+fn sync_wallets(wallets: &mut HashMap<String, Wallet>, diff: &StateDiff) {
+    for change in &diff.balance_changes {
+        if let Some(wallet) = wallets.get_mut(&change.address) {
+            wallet.set_balance(&change.currency, change.after);
+        }
+    }
+    for (address, _, nonce) in &diff.nonce_changes {
+        if let Some(wallet) = wallets.get_mut(address) {
+            wallet.nonce = *nonce;
+        }
+    }
+}
+
+/// Produce the setup blocks: genesis, the system wallet, the AMM pool, the
+/// trading wallets and their initial credits, and the pool's liquidity, as
+/// `config` describes them
+///
+/// `system_wallet` holds the key the genesis of `state` binds the system
+/// account to; the block authorities of a signed consensus engine are
+/// registered together with it. Keys, addresses and trading strategies are
+/// drawn from `rng`, addresses through `code_generator`. Returns the wallets
+/// and the address generator, which a setup snapshot keeps.
+fn setup_chain(producer: &mut BlockProducer, state: &mut State, config: &SimulationConfig, system_wallet: Wallet,
+               authorities: &[Wallet], mut code_generator: UniqueCodeGenerator, rng: &mut StdRng)
+        -> Result<(HashMap<String, Wallet>, UniqueCodeGenerator)> {
+    let wallet_count = config.wallet_count;
+    let setup_blocks = 3 * wallet_count + 3;
+    info!("Starting ZUX Blockchain simulation...");
+    info!("This simulation will create exactly {} blocks initially:", setup_blocks);
+    info!("  - 1 Genesis block");
+    info!("  - 1 System Wallet registration block");
+    info!("  - {} blocks for wallet registrations", wallet_count);
+    info!("  - {} blocks for token transfers ({} USDZ and {} ZUX per wallet)", 2 * wallet_count, config.usdz_credit, config.zux_credit);
+    info!("  - 1 block creating and funding the AMM Pool");
+    info!("After block {}, the simulation will continue with {} swap transactions.", setup_blocks, config.total_transactions);
+    
+    info!("Block generation is event-triggered based on computation completion");
+    
+    // Create the genesis block
+    producer.produce(state, &[])?;
+    info!("Genesis block created successfully! Block ID: {}", state.height());
+
+    producer.add_keys([&system_wallet])?;
+    
+    // Create a block registering the System Wallet, which releases its genesis allocation, and the authorities
+    let mut registrations = vec![create_wallet_registration(&system_wallet, state.chain_id(), producer.clock.now())?];
+    for authority in authorities {
+        registrations.push(create_wallet_registration(authority, state.chain_id(), producer.clock.now())?);
+    }
+    producer.produce(state, &registrations)?;
+    info!("System Wallet created successfully! Block ID: {}", state.height());
+    if !authorities.is_empty() {
+        info!("Registered {} block authorities in the same block", authorities.len());
+    }
+    info!("System Wallet Address: {}", system_wallet.address);
+    info!("System Wallet Balance: {} ZUX, {} USDZ", 
+         state.balance(SYSTEM_WALLET_ADDRESS, "ZUX"),
+         state.balance(SYSTEM_WALLET_ADDRESS, "USDZ"));
+         
+    // Create the trading wallets with individual blocks for each wallet creation
+    info!("Creating {} wallets...", wallet_count);
+    let mut wallets = HashMap::new();
+    
+    // No file operations - everything stays in memory
+    info!("Creating {} wallets in memory...", wallet_count);
+    
+    info!("Generated at: {} (UNIX Epoch Seconds)", producer.clock.now());
+    info!("Each wallet will be credited with: {} ZUX and {} USDZ", config.zux_credit, config.usdz_credit);
+    
+    // Create the wallets
+    for i in 1..=wallet_count {
+        // Create a wallet without initial balance
+        let wallet = create_wallet_without_balance(&mut code_generator, rng)?;
+        
+        // Create a block for this wallet's registration
+        let registration = create_wallet_registration(&wallet, state.chain_id(), producer.clock.now())?;
+        let (_, diff) = producer.produce(state, &[registration])?;
+        producer.add_keys([&wallet])?;
+        
+        // Add the wallet to our collection
+        wallets.insert(wallet.address.clone(), wallet);
+        sync_wallets(&mut wallets, &diff);
+        
+        // Print progress every 100 wallets
+        if i % 100 == 0 || i == 1 {
+            info!("Created {} wallets so far...", i);
+        }
+    }
+    
+    // Add the system wallet to the wallets map, with its balances and nonce as seen by the ledger
+    let mut system_wallet = system_wallet;
+    system_wallet.set_balance("ZUX", state.balance(SYSTEM_WALLET_ADDRESS, "ZUX"));
+    system_wallet.set_balance("USDZ", state.balance(SYSTEM_WALLET_ADDRESS, "USDZ"));
+    system_wallet.nonce = state.next_nonce(SYSTEM_WALLET_ADDRESS);
+    wallets.insert(system_wallet.address.clone(), system_wallet);
+    
+    info!("\nAll wallet creation blocks have been generated.");
+    info!("Current block count: {}", state.height());
+    info!("Now crediting each wallet with initial balance from System Wallet...");
+    
+    // Credit each wallet with ZUX and USDZ tokens, in address order so a seeded run credits them alike
+    let mut wallet_addresses: Vec<String> = wallets.keys()
+        .filter(|&addr| addr != SYSTEM_WALLET_ADDRESS) // Exclude the system wallet
+        .cloned()
+        .collect();
+    wallet_addresses.sort_unstable();
+    
+    // Define credit amounts for each currency
+    let zux_credit_amount = config.zux_credit;
+    let usdz_credit_amount = config.usdz_credit;
+    
+    // Now create transactions and blocks
+    for (i, address) in wallet_addresses.iter().enumerate() {
+        // Create ZUX transaction and a block for it
+        let system_wallet = &wallets[SYSTEM_WALLET_ADDRESS];
+        let zux_tx = create_transaction(system_wallet, state.chain_id(), address, zux_credit_amount, "ZUX", Amount::ZERO, &wallets, producer.clock.now())?;
+        let (_, diff) = producer.produce(state, &[zux_tx])?;
+        sync_wallets(&mut wallets, &diff);
+        
+        // Print progress every 100 transactions
+        if (i + 1) % 100 == 0 || i == 0 {
+            info!("Processed ZUX credits for {} wallets so far...", i + 1);
+        }
+        
+        // Create USDZ transaction and a block for it
+        let system_wallet = &wallets[SYSTEM_WALLET_ADDRESS];
+        let usdz_tx = create_transaction(system_wallet, state.chain_id(), address, usdz_credit_amount, "USDZ", Amount::ZERO, &wallets, producer.clock.now())?;
+        let (_, diff) = producer.produce(state, &[usdz_tx])?;
+        sync_wallets(&mut wallets, &diff);
+        
+        // Print progress every 100 transactions
+        if (i + 1) % 100 == 0 || i == 0 {
+            info!("Processed USDZ credits for {} wallets so far...", i + 1);
+        }
+    }
+    
+    info!("\nSystem Wallet transferred a total of {} ZUX and {} USDZ to {} wallets.", 
+         zux_credit_amount.mul_div(wallet_addresses.len() as u128, 1)?,
+         usdz_credit_amount.mul_div(wallet_addresses.len() as u128, 1)?,
+         wallet_addresses.len());
+    info!("System Wallet remaining balance: {} ZUX, {} USDZ", 
+         state.balance(SYSTEM_WALLET_ADDRESS, "ZUX"),
+         state.balance(SYSTEM_WALLET_ADDRESS, "USDZ"));
+    
+    // Transfer part of the remaining balance from system wallet to AMM pool
+    info!("\nTransferring liquidity from System Wallet to AMM Pool...");
+    
+    // Get the remaining balances
+    let remaining_zux = state.balance(SYSTEM_WALLET_ADDRESS, "ZUX");
+    
+    // Deposit the scenario's fraction of the remaining ZUX, with USDZ at the target price
+    let (adjusted_zux, adjusted_usdz) = config.pool_deposit(remaining_zux)?;
+    
+    // Create a block for the first deposit, which creates the pool when the ledger applies it
+    let liquidity_tx = Transaction::signed(
+        &wallets[SYSTEM_WALLET_ADDRESS],
+        state.chain_id(),
+        Amount::ZERO,
+        TxKind::AddLiquidity { zux_amount: adjusted_zux, usd_amount: adjusted_usdz },
+        producer.clock.now(),
+    )?;
+    let (_, diff) = producer.produce(state, &[liquidity_tx])?;
+    sync_wallets(&mut wallets, &diff);
+    
+    let current_price = state.amm_pool().get_zux_price();
+    
+    info!("Transferred {} ZUX and {} USDZ from System Wallet to AMM Pool", adjusted_zux, adjusted_usdz);
+    info!("AMM Pool now has {} ZUX and {} USDZ", state.amm_pool().zux_reserve, state.amm_pool().usd_reserve);
+    info!("Initial ZUX Price: {:.6} USDZ per ZUX", current_price);
+    
+    info!("\nInitial blockchain setup completed!");
+    info!("Total blocks created so far: {}", state.height());
+    info!("  - 1 Genesis block");
+    info!("  - 1 System Wallet registration block");
+    info!("  - {} Wallet registration blocks", wallet_count);
+    info!("  - {} Token Credit blocks ({} for ZUX and {} for USDZ)", 2 * wallet_count, wallet_count, wallet_count);
+    info!("  - 1 AMM Pool liquidity block");
+    info!("\nAll wallets have been created and credited with:");
+    info!("  - {} ZUX tokens from System Wallet", zux_credit_amount);
+    info!("  - {} USDZ tokens from System Wallet", usdz_credit_amount);
+    info!("\nTotal ZUX in circulation: {:.2} (genesis supply plus {:.2} of block rewards)",
+          state.total_supply("ZUX")?, state.rewards().total_issued(state.height())?);
+    
+    info!("\nAll wallet addresses are guaranteed to be unique using the base-62 encoding system.");
+    
+    // Initialize trading strategies for all wallets
+    let initial_price = state.amm_pool().get_zux_price();
+    info!("\nInitializing trading strategies for all wallets with initial price: {:.6} USDZ", initial_price);
+    
+    for address in &wallet_addresses {
+        if let Some(wallet) = wallets.get_mut(address) {
+            wallet.initialize_trading_strategy_with(initial_price, &config.agent_mix(), rng);
+        }
+    }
+    
+    Ok((wallets, code_generator))
+}
+
+/// Rebuild the ledger and the wallets of an interrupted run from its chain store and checkpoint
+///
+/// `state` must be the ledger at the store's base, see [`base_state`].
+fn resume_chain(store: &dyn ChainStore, state: &mut State, data_dir: &Path) -> Result<ResumedRun> {
+    let (base_height, base_hash) = store.base();
+    if state.height() != base_height || state.tip_hash() != base_hash {
+        return Err(BlockchainError::System(format!(
+            "Cannot resume: the chain in {} continues from block #{} but {} is at block #{}",
+            data_dir.display(), base_height, BASE_SNAPSHOT_FILE, state.height()
+        )));
+    }
+    
+    let checkpoint_path = data_dir.join(CHECKPOINT_FILE);
+    let checkpoint: Checkpoint = std::fs::read(&checkpoint_path)
+        .map_err(BlockchainError::Io)
+        .and_then(|bytes| serde_json::from_slice(&bytes)
+            .map_err(|e| BlockchainError::System(format!("Invalid checkpoint {}: {}", checkpoint_path.display(), e))))
+        .map_err(|e| BlockchainError::System(
+            format!("Cannot resume: no usable wallet checkpoint ({}); the previous run did not finish its setup", e)
+        ))?;
+    
+    info!("Replaying {} stored blocks from {}...", store.height() - base_height, data_dir.display());
+    let mut initial_balances = HashMap::new();
+    if state.height() == checkpoint.swap_start_height {
+        initial_balances = trading_balances(state);
+    }
+    let mut swap_blocks = Vec::new();
+    for block in store.blocks_from(base_height + 1)? {
+        state.apply_block(&block)?;
+        if block.id == checkpoint.swap_start_height {
+            initial_balances = trading_balances(state);
+        } else if block.id > checkpoint.swap_start_height {
+            swap_blocks.push(block);
+        }
+    }
+    
+    // The local wallet views are refreshed from the ledger
+    let mut wallets = HashMap::new();
+    for mut wallet in checkpoint.wallets {
+        for currency in SUPPORTED_CURRENCIES.iter() {
+            wallet.set_balance(currency, state.balance(&wallet.address, currency));
+        }
+        wallet.nonce = state.next_nonce(&wallet.address);
+        wallets.insert(wallet.address.clone(), wallet);
+    }
+    
+    info!("Resumed at block #{} ({} swap blocks already produced)", state.height(), swap_blocks.len());
+    Ok((wallets, initial_balances, swap_blocks, checkpoint.authorities))
+}
+
+/// ZUX and USDZ balances of every trading wallet, for performance tracking
+fn trading_balances(state: &State) -> HashMap<String, (Amount, Amount)> {
+    state.accounts().keys()
+        .filter(|addr| *addr != SYSTEM_WALLET_ADDRESS)
+        .map(|addr| (addr.clone(), (state.balance(addr, "ZUX"), state.balance(addr, "USDZ"))))
+        .collect()
+}
+
+/// Run the blockchain simulation and summarize it
+///
+/// Progress is logged through the `log` facade; the caller installs a logger.
+pub fn run_simulation(options: &SimulationOptions) -> Result<RunReport> {
+    info!("Initializing ZUX Blockchain simulation...");
+    
+    // A new chain started from a snapshot skips the setup blocks; read it
+    // before the data directory is cleared, as it may live there
+    let snapshot = match &options.from_snapshot {
+        Some(path) if !options.resume => {
+            let snapshot = Snapshot::read_from(path)?;
+            info!("Loaded snapshot {} at block #{}", path.display(), snapshot.height());
+            Some(snapshot)
+        },
+        _ => None,
+    };
+    
+    // Every produced block is appended to the chain store
+    let mut store = if options.resume {
+        FileStore::open(&options.data_dir)?
+    } else {
+        // A checkpoint or base snapshot left by an earlier run does not belong to the new chain
+        remove_stale(&options.data_dir.join(CHECKPOINT_FILE))?;
+        remove_stale(&options.data_dir.join(BASE_SNAPSHOT_FILE))?;
+        remove_stale(&options.data_dir.join(CONSENSUS_FILE))?;
+        remove_stale(&options.data_dir.join(SYSTEM_KEY_FILE))?;
+        remove_stale(&options.data_dir.join(SCENARIO_FILE))?;
+        match &snapshot {
+            Some(snapshot) => FileStore::create_with_base(&options.data_dir, snapshot.height(), snapshot.tip_hash())?,
+            None => FileStore::create(&options.data_dir)?,
+        }
+    };
+    info!("Chain store: {} ({} blocks)", options.data_dir.display(), store.height());
+    
+    // A seeded run draws keys, addresses and trading decisions from one generator
+    // and mines on a single thread, so the same seed gives the same chain
+    let mut rng = match options.seed {
+        Some(seed) => {
+            info!("Seed: {}", seed);
+            StdRng::seed_from_u64(seed)
+        },
+        None => StdRng::from_entropy(),
+    };
+    let miner = if options.seed.is_some() { Miner::new(1) } else { Miner::default() };
+    
+    // Blocks, transactions and market data are timed by the wall clock, or by a
+    // simulated one that moves a fixed interval per block so runs can be replayed
+    let block_interval_ms = options.block_interval_ms.or(options.seed.map(|_| SEEDED_BLOCK_INTERVAL_MS));
+    let simulated_clock = block_interval_ms
+        .map(|interval| Arc::new(SimulatedClock::new(SIMULATED_CLOCK_START_MS, interval)));
+    let clock: Arc<dyn Clock> = match &simulated_clock {
+        Some(simulated_clock) => {
+            info!("Simulated clock: {} ms per block", simulated_clock.step_millis());
+            simulated_clock.clone()
+        },
+        None => Arc::new(SystemClock),
+    };
+    
+    // The ledger owns every balance; the system wallet's supply is allocated at genesis
+    let mut state: State;
+    
+    // A continued chain keeps the year its blocks were dated with
+    let inception_year = if options.resume { read_scenario(&options.data_dir)?.inception_year } else { options.config.inception_year };
+    
+    // Replay an interrupted run, restore a snapshot, or produce the setup blocks of a new chain
+    let (mut wallets, initial_balances, mut all_blocks, authorities) = if options.resume && store.height() > 0 {
+        state = base_state(&options.data_dir)?;
+        resume_chain(&store, &mut state, &options.data_dir)?
+    } else if let Some(snapshot) = snapshot {
+        // The store only holds blocks after the snapshot; keep a copy of it to replay them from
+        snapshot.write_to(options.data_dir.join(BASE_SNAPSHOT_FILE))?;
+        
+        let authorities = snapshot.authorities.clone();
+        let (restored_state, wallets, _) = snapshot.restore()?;
+        state = restored_state;
+        info!("Restored {} wallets at block #{}, skipping the setup blocks", wallets.len(), state.height());
+        
+        Checkpoint::write(&options.data_dir, state.height(), &wallets, &authorities)?;
+        (wallets, trading_balances(&state), Vec::new(), authorities)
+    } else {
+        if options.resume {
+            info!("Nothing to resume in {}; starting a new chain.", options.data_dir.display());
+        }
+        
+        // The block authorities and the system wallet of a new chain hold fresh keys, fixed at genesis;
+        // the rule, system key and scenario are recorded to replay the chain
+        let authorities = create_authorities(options.consensus, &mut rng);
+        let mut code_generator = UniqueCodeGenerator::new(&mut rng)?;
+        let system_wallet = create_system_wallet(&mut code_generator, &mut rng)?;
+        state = genesis_state_from(
+            &options.config, consensus_rule(options.consensus, &authorities), RetargetRule::default(), &system_wallet.public_key
+        );
+        write_consensus(&options.data_dir, state.consensus())?;
+        write_system_key(&options.data_dir, &system_wallet.public_key)?;
+        write_scenario(&options.data_dir, &options.config)?;
+        
+        let mut producer = BlockProducer::new(&mut store, Arc::clone(&clock), miner, inception_year);
+        producer.print_blocks = !options.headless;
+        producer.add_keys(&authorities)?;
+        let (wallets, code_generator) =
+            setup_chain(&mut producer, &mut state, &options.config, system_wallet, &authorities, code_generator, &mut rng)?;
+        
+        // Wallet keys and trading profiles are not on chain; keep them so the run can be resumed
+        Checkpoint::write(&options.data_dir, state.height(), &wallets, &authorities)?;
+        
+        // Later runs can start from here with `--from-snapshot` instead of producing the setup blocks again
+        let snapshot_path = options.data_dir.join(SETUP_SNAPSHOT_FILE);
+        let mut snapshot = state.snapshot(&wallets, &code_generator);
+        snapshot.authorities = authorities.clone();
+        snapshot.write_to(&snapshot_path)?;
+        info!("Wrote setup snapshot to {}", snapshot_path.display());
+        
+        (wallets, trading_balances(&state), Vec::new(), authorities)
+    };
+    
+    // A simulated clock picks up one interval after the last block or pool trade of a continued chain
+    if let Some(simulated_clock) = &simulated_clock {
+        let last_time = match store.block_at(store.height())? {
+            Some(block) => Some(block.timestamp),
+            None => state.amm_pool().price_history.last().map(|point| point.timestamp),
+        };
+        if let Some(last_time) = last_time {
+            simulated_clock.advance_to(last_time * 1_000 + simulated_clock.step_millis());
+        }
+    }
+    
+    let mut producer = BlockProducer::new(&mut store, Arc::clone(&clock), miner, inception_year);
+    producer.print_blocks = !options.headless;
+    producer.add_keys(authorities.iter().chain(wallets.values()))?;
+    info!("Consensus: {}", state.consensus().engine().name());
+    if state.consensus().kind() == EngineKind::ProofOfWork {
+        info!("Mining on {} thread(s).", producer.miner.threads);
+    }
+    
+    // Wrap a copy of the AMM pool in an Arc<Mutex> for the price monitor thread;
+    // it is republished from the ledger after every block
+    let amm_pool = Arc::new(Mutex::new(state.amm_pool().clone()));
+    let network_stats = Arc::new(Mutex::new(NetworkStats { total_blocks: state.height(), network_hash_rate: 0.0 }));
+    
+    // Create a stop signal for the price monitor thread
+    let stop_signal = Arc::new(Mutex::new(false));
+    
+    // A headless run writes no feeds for the companions and does not start them
+    if options.headless {
+        info!("\nHeadless run: no market feed, explorer data or companion windows.");
+    } else {
+        // Publish the market feed from a separate thread
+        info!("\nStarting ZUX/USDZ market feed...");
+        run_price_monitor(Arc::clone(&amm_pool), Arc::clone(&network_stats), Arc::clone(&stop_signal), Arc::clone(&clock))?;
+        
+        // Open the price monitor and the explorer next to the run, if there is somewhere to open them
+        let launcher = Launcher::new(options.launch);
+        if options.launch == LaunchMode::Headless {
+            info!("The price monitor and blockchain explorer are not started.");
+        }
+        launcher.launch(Companion::PriceMonitor);
+        launcher.launch(Companion::BlockchainExplorer);
+    }
+    
+    // Now start the transaction simulation after the setup blocks
+    let total_transactions = options.config.total_transactions;
+    info!("\nStarting transaction simulation after block {}...", state.height());
+    info!("Will simulate {} intelligent transactions with price-aware trading strategies.", total_transactions);
+    
+    // Only block rewards add ZUX from here on
+    let start_zux = state.total_supply("ZUX")?;
+    let start_issued = state.rewards().total_issued(state.height())?;
+    
+    // Track wallet participation statistics, including swaps replayed from the store
+    let mut stats = SwapStats::default();
+    for block in &all_blocks {
+        stats.record(block)?;
+    }
+    
+    // The price path of the swap phase, one point per swap block, and the reserves it starts from
+    let mut price_path = vec![state.amm_pool().get_zux_price()];
+    let opening_reserves = (state.amm_pool().zux_reserve, state.amm_pool().usd_reserve);
+    let swap_phase_start = Instant::now();
+    
+    // Initial explorer data update
+    if !options.headless {
+        update_explorer_data(
+            &all_blocks,
+            &state,
+            &wallets,
+            state.height(),
+            stats.swap_count,
+            clock.now(),
+        )?;
+    }
+    
+    // Agents submit signed swaps to the mempool; each block packs the best-paying ones
+    let mut mempool = Mempool::new(MempoolConfig::default());
+    let block_limits = BlockLimits::default();
+    info!("Blocks carry up to {} transactions ({} bytes), {} swaps are submitted between blocks.",
+          block_limits.max_transactions, block_limits.max_bytes, SWAPS_SUBMITTED_PER_BLOCK);
+    let mut next_progress_report = (stats.swap_count / 250 + 1) * 250;
+    let mut empty_rounds = 0;
+    
+    while stats.swap_count < total_transactions {
+        // Never more swaps pending or packed than the run has left to execute
+        let remaining = usize::try_from(total_transactions - stats.swap_count).unwrap_or(usize::MAX);
+        let submissions = SWAPS_SUBMITTED_PER_BLOCK.min(remaining.saturating_sub(mempool.len()));
+        let limits = BlockLimits {
+            max_transactions: block_limits.max_transactions.min(remaining),
+            ..block_limits.clone()
+        };
+        
+        // Create intelligent swaps based on trading strategies, priced off the last block
+        for _ in 0..submissions {
+            let now = clock.now();
+            let (address, _, _, transaction) = create_intelligent_swap(&mut wallets, state.amm_pool(), state.chain_id(), now, &mut rng)?;
+            match mempool.submit(transaction, &state, now) {
+                Ok(_) => {
+                    // The wallet's next swap follows the pending one
+                    if let Some(wallet) = wallets.get_mut(&address) {
+                        wallet.nonce += 1;
+                    }
+                },
+                Err(e) => {
+                    debug!("Mempool rejected a swap from {}: {}", address, e);
+                    stats.rejected_transactions += 1;
+                },
+            }
+        }
+        
+        // Create and apply a block from the mempool; its events are the ledger's receipts
+        let (produced, dropped) = producer.produce_from(&mut state, &mut mempool, &limits)?;
+        stats.rejected_transactions += dropped as u64;
+        
+        if let Some((_, diff)) = &produced {
+            sync_wallets(&mut wallets, diff);
+        }
+        
+        // Swaps still pending keep their nonces; dropped ones free theirs
+        for wallet in wallets.values_mut() {
+            wallet.nonce = mempool.next_nonce(&wallet.address, &state);
+        }
+        let Some((block, _)) = produced else {
+            empty_rounds += 1;
+            if empty_rounds >= MAX_EMPTY_ROUNDS {
+                return Err(BlockchainError::System(format!(
+                    "Trading stalled: no swap executed in {} rounds in a row, after {} of {} swaps",
+                    empty_rounds, stats.swap_count, total_transactions
+                )));
+            }
+            continue;
+        };
+        empty_rounds = 0;
+        *amm_pool.lock().unwrap() = state.amm_pool().clone();
+        price_path.push(state.amm_pool().get_zux_price());
+        
+        // Track fees, volume and wallet participation, then store the block for the explorer
+        stats.record(&block)?;
+        all_blocks.push(block);
+        
+        let recent_blocks: Vec<_> = all_blocks[all_blocks.len().saturating_sub(HASH_RATE_WINDOW)..].iter()
+            .map(|block| (block.timestamp, block.target))
+            .collect();
+        *network_stats.lock().unwrap() = NetworkStats {
+            total_blocks: state.height(),
+            network_hash_rate: estimate_hash_rate(&recent_blocks),
+        };
+        
+        // Print progress every 250 transactions to reduce log clutter with increased transaction count
+        if stats.swap_count >= next_progress_report {
+            next_progress_report = (stats.swap_count / 250 + 1) * 250;
+            let current_price = state.amm_pool().get_zux_price();
+            info!("Processed {} intelligent swaps ({:.1}% complete). Current ZUX price: {:.6} USDZ",
+                  stats.swap_count, (stats.swap_count as f64 / total_transactions as f64) * 100.0, current_price);
+            
+            // Update explorer data every 250 transactions
+            if !options.headless {
+                if let Err(e) = update_explorer_data(
+                    &all_blocks,
+                    &state,
+                    &wallets,
+                    state.height(),
+                    stats.swap_count,
+                    clock.now(),
+                ) {
+                    warn!("Failed to update explorer data: {}", e);
+                }
+            }
+        }
+        
+        // Add a minimal delay to avoid overwhelming the system while allowing more transactions;
+        // a headless run goes as fast as it can
+        if options.config.block_delay_ms > 0 && !options.headless {
+            thread::sleep(Duration::from_millis(options.config.block_delay_ms));
+        }
+    }
+    let elapsed = swap_phase_start.elapsed();
+    
+    // Verify total ZUX in circulation only grew by the block rewards
+    let total_zux = state.total_supply("ZUX")?;
+    let expected_zux = start_zux.checked_add(state.rewards().total_issued(state.height())?.checked_sub(start_issued)?)?;
+    if total_zux != expected_zux {
+        warn!("ZUX supply changed during the run: {} instead of {}", total_zux, expected_zux);
+    }
+    
+    // Now this code is reachable since we have a bounded loop
+    *stop_signal.lock().unwrap() = true;
+    
+    // Final explorer data update
+    if !options.headless {
+        if let Err(e) = update_explorer_data(
+            &all_blocks,
+            &state,
+            &wallets,
+            state.height(),
+            stats.swap_count,
+            clock.now(),
+        ) {
+            warn!("Failed to update final explorer data: {}", e);
+        }
+    }
+    
+    info!("\nBlockchain simulation completed with {} transactions!", stats.swap_count);
+    info!("  - {} intelligent swaps with enhanced trading strategies", stats.swap_count);
+    info!("  - All wallets actively participated with increased trading frequency");
+    info!("  - Chain tip: block #{} {}", state.height(), state.tip_hash());
+    info!("\nTotal ZUX in circulation: {:.2} (should be {:.2})", total_zux, expected_zux);
+    if state.consensus().kind() == EngineKind::ProofOfWork {
+        info!("Mined {} hashes in {:.1}s on {} thread(s): {:.0} H/s measured",
+              producer.hashes, producer.mining_time.as_secs_f64(), producer.miner.threads, producer.hash_rate());
+    }
+    
+    let report = RunReport {
+        seed: options.seed,
+        consensus: state.consensus().kind().to_string(),
+        config: options.config.clone(),
+        height: state.height(),
+        tip_hash: state.tip_hash().to_string(),
+        swaps: stats.swap_count,
+        swap_blocks: stats.swap_blocks,
+        rejected_transactions: stats.rejected_transactions,
+        zux_traded: stats.total_zux_traded,
+        usdz_traded: stats.total_usdz_traded,
+        elapsed_secs: elapsed.as_secs_f64(),
+        pool: PoolReport {
+            zux_reserve: state.amm_pool().zux_reserve,
+            usd_reserve: state.amm_pool().usd_reserve,
+            opening_zux_reserve: opening_reserves.0,
+            opening_usd_reserve: opening_reserves.1,
+            price: state.amm_pool().get_zux_price(),
+            fees_collected: state.amm_pool().fees_collected,
+            network_fees: stats.network_fees,
+            lp_return_percent: PoolReport::lp_return(
+                opening_reserves,
+                (state.amm_pool().zux_reserve, state.amm_pool().usd_reserve),
+                state.amm_pool().get_zux_price()
+            ),
+        },
+        price: PriceStats::from_path(&price_path),
+        wallets: wallet_report(&wallets, &initial_balances, &stats, state.amm_pool().get_zux_price()),
+    };
+    log_report(&report);
+    
+    // Re-check the whole stored chain from its base before declaring the run good
+    let verified = verify_chain(&store, &mut base_state(&options.data_dir)?)?;
+    info!("\nVerified all {} stored blocks: hashes, seals, signatures and state roots match.", verified);
+    
+    // A headless run always leaves a report behind, next to the chain unless told otherwise
+    let report_path = options.report.clone()
+        .or_else(|| options.headless.then(|| options.data_dir.join(REPORT_FILE)));
+    if let Some(report_path) = report_path {
+        report.write_to(&report_path)?;
+        info!("Wrote run report to {}", report_path.display());
+    }
+    
+    if !options.headless {
+        // Clean up temporary files for privacy and security
+        info!("\nCleaning up temporary files...");
+        
+        // Remove explorer data file
+        if let Err(e) = std::fs::remove_file("explorer_data.json") {
+            warn!("Failed to remove explorer data file: {}", e);
+        }
+        
+        info!("All temporary files have been removed for privacy and security.");
+    }
+    info!("The chain ({} blocks) is kept in {}; browse it with `blockchain_explorer --chain {}` or continue it with `--resume`.",
+          state.height(), options.data_dir.display(), options.data_dir.display());
+    info!("Blockchain simulation completed successfully!");
+    
+    Ok(report)
+}
+
+/// How every trading wallet fared since trading started, valued at `price`, and how they took part
+fn wallet_report(wallets: &HashMap<String, Wallet>, initial_balances: &HashMap<String, (Amount, Amount)>,
+                 stats: &SwapStats, price: f64) -> WalletReport {
+    // Calculate performance for each wallet
+    let mut performances: Vec<WalletPerformance> = wallets.iter()
+        .filter(|(addr, _)| *addr != SYSTEM_WALLET_ADDRESS)
+        .filter_map(|(addr, wallet)| {
+            let &(initial_zux, initial_usdz) = initial_balances.get(addr)?;
+            let final_zux = wallet.get_balance("ZUX");
+            let final_usdz = wallet.get_balance("USDZ");
+            
+            // Calculate total value in USDZ (initial and final)
+            let initial_value = initial_zux.to_f64() * price + initial_usdz.to_f64();
+            let final_value = final_zux.to_f64() * price + final_usdz.to_f64();
+            
+            Some(WalletPerformance {
+                address: addr.clone(),
+                performance_percent: ((final_value / initial_value) - 1.0) * 100.0,
+                pnl_usdz: final_value - initial_value,
+                initial_zux,
+                final_zux,
+                initial_usdz,
+                final_usdz,
+            })
+        })
+        .collect();
+    
+    // Sort by performance (descending), then by address so ties are listed alike in every run
+    performances.sort_by(|a, b| b.performance_percent.partial_cmp(&a.performance_percent)
+        .unwrap_or(std::cmp::Ordering::Equal)
+        .then_with(|| a.address.cmp(&b.address)));
+    
+    let total = performances.len();
+    let profitable = performances.iter().filter(|performance| performance.performance_percent > 0.0).count();
+    let participating = stats.wallet_trade_counts.len();
+    let ratio = |count: usize| if total > 0 { count as f64 / total as f64 } else { 0.0 };
+    WalletReport {
+        total,
+        profitable,
+        profitable_ratio: ratio(profitable),
+        total_pnl_usdz: performances.iter().map(|performance| performance.pnl_usdz).sum(),
+        participating,
+        participation_rate: ratio(participating),
+        average_trades: if participating > 0 { stats.swap_count as f64 / participating as f64 } else { 0.0 },
+        max_trades: stats.wallet_trade_counts.values().copied().max().unwrap_or(0),
+        min_trades: stats.wallet_trade_counts.values().copied().min().unwrap_or(0),
+        top: performances.iter().take(5).cloned().collect(),
+        bottom: performances.iter().rev().take(5).cloned().collect(),
+    }
+}
+
+/// Log the pool and wallet sections of a run's summary
+fn log_report(report: &RunReport) {
+    // Final AMM pool status
+    let pool = &report.pool;
+    info!("\nFinal AMM Pool Status:");
+    info!("  - ZUX Reserve: {:.2}", pool.zux_reserve);
+    info!("  - USDZ Reserve: {:.2}", pool.usd_reserve);
+    info!("  - ZUX Price: {:.6} USDZ per ZUX", pool.price);
+    info!("  - Swap fees kept: {} ZUX, {} USDZ", pool.fees_collected.zux, pool.fees_collected.usdz);
+    info!("  - Network fees paid by swaps: {} ZUX", pool.network_fees);
+    info!("  - Liquidity provider return against holding: {:+.2}%", pool.lp_return_percent);
+    
+    let price = &report.price;
+    info!("\nPrice Path over {} swap blocks:", report.swap_blocks);
+    info!("  - From {:.6} to {:.6} USDZ ({:+.2}%), between {:.6} and {:.6}", price.initial, price.last, price.change_percent, price.min, price.max);
+    info!("  - Volatility: {:.2}% per block, maximum drawdown {:.2}%", price.volatility_percent, price.max_drawdown_percent);
+    
+    // Display overall performance statistics
+    let wallets = &report.wallets;
+    info!("\nWallet Trading Performance:");
+    info!("  - Profitable wallets: {} out of {} ({:.1}%)",
+          wallets.profitable, wallets.total, wallets.profitable_ratio * 100.0);
+    if let (Some(best), Some(worst)) = (wallets.top.first(), wallets.bottom.first()) {
+        info!("  - Best performing wallet: {} with {:.2}% gain", best.address, best.performance_percent);
+        info!("  - Worst performing wallet: {} with {:.2}% change", worst.address, worst.performance_percent);
+    }
+    
+    // Display participation statistics
+    info!("\nWallet Participation Statistics:");
+    info!("  - Wallets that participated in trading: {} out of {} ({:.1}%)",
+          wallets.participating, wallets.total, wallets.participation_rate * 100.0);
+    info!("  - Average trades per wallet: {:.1}", wallets.average_trades);
+    info!("  - Maximum trades by a single wallet: {}", wallets.max_trades);
+    info!("  - Minimum trades by a participating wallet: {}", wallets.min_trades);
+    info!("  - Total ZUX traded: {:.2}", report.zux_traded);
+    info!("  - Total USDZ traded: {:.2}", report.usdz_traded);
+    info!("  - Swap blocks: {} ({:.1} swaps per block)", report.swap_blocks,
+          report.swaps as f64 / report.swap_blocks.max(1) as f64);
+    info!("  - Swaps rejected by the mempool or dropped when packing: {}", report.rejected_transactions);
+    
+    // Display some individual wallet performances
+    info!("\nSample of Individual Wallet Performances:");
+    let log_performance = |rank: usize, performance: &WalletPerformance| {
+        let zux_change = performance.final_zux.to_f64() - performance.initial_zux.to_f64();
+        let usdz_change = performance.final_usdz.to_f64() - performance.initial_usdz.to_f64();
+        info!("  #{} Wallet {} (Performance: {:+.2}%):", rank, performance.address, performance.performance_percent);
+        info!("    - ZUX: {:.2} → {:.2} ({:+.2})", performance.initial_zux, performance.final_zux, zux_change);
+        info!("    - USDZ: {:.2} → {:.2} ({:+.2})", performance.initial_usdz, performance.final_usdz, usdz_change);
+    };
+    
+    // Display top 5 performers
+    info!("  Top 5 Performing Wallets:");
+    for (i, performance) in wallets.top.iter().enumerate() {
+        log_performance(i + 1, performance);
+    }
+    
+    // Display bottom 5 performers
+    info!("\n  Bottom 5 Performing Wallets:");
+    for (i, performance) in wallets.bottom.iter().enumerate() {
+        log_performance(wallets.total - i, performance);
+    }
+}
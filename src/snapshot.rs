@@ -0,0 +1,116 @@
+//! Versioned, checksummed state snapshots
+//!
+//! A snapshot holds the ledger at one height plus the off-chain simulation
+//! state that goes with it (wallet keys, trading-strategy state, the address
+//! generator and the block authorities' keys), so a run can start from it
+//! instead of replaying or re-mining every earlier block.
+//!
+//! File layout: the 8-byte magic `ZUXSNAP\0`, the format version (u32), the
+//! payload length (u64), the JSON payload and the SHA-256 of the payload. All
+//! integers are big-endian.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::address::UniqueCodeGenerator;
+use crate::error::{BlockchainError, Result};
+use crate::state::{LedgerSnapshot, State};
+use crate::wallet::Wallet;
+
+/// Current snapshot format version
+pub const SNAPSHOT_VERSION: u32 = 7;
+
+const MAGIC: &[u8; 8] = b"ZUXSNAP\0";
+const HEADER_LEN: usize = 8 + 4 + 8;
+const CHECKSUM_LEN: usize = 32;
+
+/// Everything needed to carry on a simulation from a given height
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub ledger: LedgerSnapshot,
+    pub wallets: Vec<Wallet>, // Keys, local balances and trading-strategy state
+    pub code_generator: UniqueCodeGenerator,
+    pub authorities: Vec<Wallet>, // Keys the block authorities of a signed consensus engine seal with
+}
+
+impl Snapshot {
+    /// Bundle a ledger snapshot with the simulation state that goes with it
+    pub fn new(ledger: LedgerSnapshot, wallets: Vec<Wallet>, code_generator: &UniqueCodeGenerator) -> Self {
+        Snapshot {
+            ledger,
+            wallets,
+            code_generator: code_generator.clone(),
+            authorities: Vec::new(),
+        }
+    }
+
+    /// Height of the last block included in the snapshot
+    pub fn height(&self) -> u64 {
+        self.ledger.height
+    }
+
+    /// Hash of the last block included in the snapshot
+    pub fn tip_hash(&self) -> &str {
+        &self.ledger.tip_hash
+    }
+
+    /// Write the snapshot to `path`, replacing any existing file only once the new one is complete
+    pub fn write_to(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let payload = serde_json::to_vec(self)
+            .map_err(|e| BlockchainError::System(format!("Failed to serialize snapshot: {}", e)))?;
+
+        let mut bytes = Vec::with_capacity(HEADER_LEN + payload.len() + CHECKSUM_LEN);
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&SNAPSHOT_VERSION.to_be_bytes());
+        bytes.extend_from_slice(&(payload.len() as u64).to_be_bytes());
+        bytes.extend_from_slice(&payload);
+        bytes.extend_from_slice(&Sha256::digest(&payload));
+
+        let temp_path = path.with_extension("tmp");
+        fs::write(&temp_path, bytes)?;
+        fs::rename(&temp_path, path)?;
+        Ok(())
+    }
+
+    /// Read a snapshot, rejecting unknown versions and corrupted files
+    pub fn read_from(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let bytes = fs::read(path)?;
+        let invalid = |reason: &str| BlockchainError::System(format!("Invalid snapshot {}: {}", path.display(), reason));
+
+        if bytes.len() < HEADER_LEN + CHECKSUM_LEN || &bytes[..8] != MAGIC {
+            return Err(invalid("not a ZUX snapshot"));
+        }
+
+        let version = u32::from_be_bytes(bytes[8..12].try_into().unwrap());
+        if version != SNAPSHOT_VERSION {
+            return Err(invalid(&format!("unsupported version {} (expected {})", version, SNAPSHOT_VERSION)));
+        }
+
+        let payload_len = u64::from_be_bytes(bytes[12..20].try_into().unwrap());
+        if payload_len != (bytes.len() - HEADER_LEN - CHECKSUM_LEN) as u64 {
+            return Err(invalid("truncated"));
+        }
+
+        let (payload, checksum) = bytes[HEADER_LEN..].split_at(payload_len as usize);
+        if Sha256::digest(payload).as_slice() != checksum {
+            return Err(invalid("checksum mismatch"));
+        }
+
+        serde_json::from_slice(payload).map_err(|e| invalid(&e.to_string()))
+    }
+
+    /// Rebuild the ledger, the wallets and the address generator
+    pub fn restore(self) -> Result<(State, HashMap<String, Wallet>, UniqueCodeGenerator)> {
+        let state = State::from_snapshot(&self.ledger)?;
+        let wallets = self.wallets.into_iter()
+            .map(|wallet| (wallet.address.clone(), wallet))
+            .collect();
+        Ok((state, wallets, self.code_generator))
+    }
+}
@@ -0,0 +1,1034 @@
+//! Ledger state machine that applies blocks to account balances and the AMM pool
+//!
+//! The `State` is the single source of truth for balances: blocks are only
+//! accepted if every transaction in them verifies and executes, and the block
+//! events must match the receipts derived from executing its transactions.
+//!
+//! Every account has a nonce: a transaction is only executed if it was signed
+//! for this chain and carries the sender's next nonce, so signed transactions
+//! cannot be replayed or reordered.
+//!
+//! Every transaction pays its network fee in ZUX. The fees of a block and the
+//! block reward from the chain's `RewardSchedule` are credited to the producer
+//! recorded in the block.
+//!
+//! The ledger also follows the proof-of-work difficulty: every block must carry
+//! the target its `RetargetRule` gives for the chain so far. Its seal is
+//! checked by the engine of the chain's `ConsensusRule`.
+//!
+//! Accounts and the AMM pool reserves are committed to in a sparse Merkle tree
+//! whose root is recorded in every block header as `state_root`.
+
+use std::collections::{BTreeSet, HashMap};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::address::UniqueCodeGenerator;
+use crate::amm::{AmmPool, BASIS_POINTS};
+use crate::amount::Amount;
+use crate::block::{Block, BlockEvent};
+use crate::codec::{Encode, Encoder};
+use crate::consensus::ConsensusRule;
+use crate::difficulty::{Difficulty, RetargetRule, Target};
+use crate::error::{BlockchainError, Result};
+use crate::merkle::{tree_key, Hash, SparseMerkleTree};
+use crate::proof::BalanceProof;
+use crate::reward::RewardSchedule;
+use crate::snapshot::Snapshot;
+use crate::transaction::{Transaction, TxKind};
+use crate::wallet::Wallet;
+use crate::{AMM_POOL_ADDRESS, SUPPORTED_CURRENCIES, SYSTEM_WALLET_ADDRESS};
+
+// Largest difference, in USDZ base units, between a deposit and the amount that keeps the pool price
+const LIQUIDITY_ROUNDING_TOLERANCE: Amount = Amount::BASE_UNIT;
+
+/// An account known to the ledger
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Account {
+    pub public_key: Option<Vec<u8>>, // Bound by the first signed transaction from this account
+    pub nonce: u64,                  // Nonce the next transaction from this account must carry
+    pub balances: HashMap<String, Amount>,
+}
+
+/// A single balance change caused by a block
+#[derive(Debug, Clone, PartialEq)]
+pub struct BalanceChange {
+    pub address: String,
+    pub currency: String,
+    pub before: Amount,
+    pub after: Amount,
+}
+
+/// Everything a block changed, kept so it can be inspected or reverted
+///
+/// Diffs returned by `State::preview` have an empty `block_hash`.
+#[derive(Debug, Clone)]
+pub struct StateDiff {
+    pub block_id: u64,
+    pub block_hash: String,
+    pub parent_hash: String,
+    pub balance_changes: Vec<BalanceChange>,
+    pub registered_accounts: Vec<String>,
+    pub bound_keys: Vec<(String, Vec<u8>)>,
+    pub nonce_changes: Vec<(String, u64, u64)>, // Address, next nonce before and after
+    pub pool_before: (Amount, Amount), // ZUX reserve, USDZ reserve
+    pub pool_after: (Amount, Amount),
+    pub creates_pool: bool,      // Whether the block made the first deposit into the AMM pool
+    pub receipts: Vec<BlockEvent>,
+    pub producer: String,
+    pub fees: Amount,   // Network fees paid by the transactions, credited to the producer
+    pub reward: Amount, // New ZUX issued to the producer
+    pub difficulty_before: Difficulty,
+    pub difficulty_after: Difficulty,
+    pub timestamp: u64,     // Block timestamp, the time pool statistics are recorded at
+    pub state_root: String, // State root after the block
+}
+
+impl StateDiff {
+    /// Addresses whose account leaf changes with this diff
+    fn touched_accounts(&self) -> BTreeSet<String> {
+        self.registered_accounts.iter()
+            .chain(self.bound_keys.iter().map(|(address, _)| address))
+            .chain(self.nonce_changes.iter().map(|(address, _, _)| address))
+            .chain(self.balance_changes.iter().map(|change| &change.address))
+            .cloned()
+            .collect()
+    }
+
+    /// Whether this diff changes the AMM pool leaf
+    fn touches_pool(&self) -> bool {
+        self.pool_before != self.pool_after || self.creates_pool
+    }
+}
+
+/// Tree key of an account leaf
+pub fn account_key(address: &str) -> Hash {
+    let mut out = Encoder::new();
+    out.put_str("account");
+    out.put_str(address);
+    tree_key(out.as_bytes())
+}
+
+/// Tree key of the AMM pool leaf
+pub fn pool_key() -> Hash {
+    let mut out = Encoder::new();
+    out.put_str("pool");
+    out.put_str(AMM_POOL_ADDRESS);
+    tree_key(out.as_bytes())
+}
+
+/// Hash of an account leaf value: address, bound key, nonce and balances of every supported currency
+pub fn account_value_hash(address: &str, account: &Account) -> Hash {
+    let mut out = Encoder::new();
+    out.put_str(address);
+    out.put_bytes(account.public_key.as_deref().unwrap_or(&[]));
+    out.put_u64(account.nonce);
+    for currency in SUPPORTED_CURRENCIES.iter() {
+        out.put_str(currency);
+        account.balances.get(*currency).copied().unwrap_or_default().encode(&mut out);
+    }
+    Sha256::digest(out.as_bytes()).into()
+}
+
+/// Hash of the AMM pool leaf value: creation flag and both reserves
+pub fn pool_value_hash(pool_created: bool, zux_reserve: Amount, usd_reserve: Amount) -> Hash {
+    let mut out = Encoder::new();
+    out.put_str(AMM_POOL_ADDRESS);
+    out.put_bool(pool_created);
+    zux_reserve.encode(&mut out);
+    usd_reserve.encode(&mut out);
+    Sha256::digest(out.as_bytes()).into()
+}
+
+/// Serializable copy of the ledger at one height
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LedgerSnapshot {
+    pub chain_id: String,
+    pub height: u64,
+    pub tip_hash: String,
+    pub state_root: String,
+    pub accounts: Vec<(String, Account)>, // Sorted by address
+    pub amm_pool: AmmPool,
+    pub pool_created: bool,
+    pub genesis_allocations: Vec<(String, Vec<(String, Amount)>)>, // Not yet released, sorted by address
+    pub genesis_keys: Vec<(String, Vec<u8>)>, // Of the accounts not yet registered, sorted by address
+    pub rewards: RewardSchedule,
+    pub retarget: RetargetRule,
+    pub difficulty: Difficulty,
+    pub consensus: ConsensusRule,
+}
+
+/// Ledger state: all account balances plus the AMM pool reserves
+#[derive(Debug, Clone)]
+pub struct State {
+    chain_id: String,
+    accounts: HashMap<String, Account>,
+    amm_pool: AmmPool,
+    pool_created: bool,
+    genesis_allocations: HashMap<String, Vec<(String, Amount)>>,
+    genesis_keys: HashMap<String, Vec<u8>>,
+    rewards: RewardSchedule,
+    retarget: RetargetRule,
+    difficulty: Difficulty,
+    consensus: ConsensusRule,
+    height: u64,
+    tip_hash: String,
+    tree: SparseMerkleTree,
+}
+
+/// Dry run of a list of transactions against a read-only state
+#[derive(Clone)]
+struct Execution<'a> {
+    state: &'a State,
+    timestamp: u64, // Timestamp of the block being executed, for swap deadlines
+    producer: String,
+    balances: HashMap<(String, String), Amount>,
+    changes: Vec<(String, String)>,
+    registered: Vec<String>,
+    bound_keys: Vec<(String, Vec<u8>)>,
+    nonces: Vec<(String, u64)>, // Next nonce of each sender in this execution
+    zux_reserve: Amount,
+    usd_reserve: Amount,
+    creates_pool: bool,
+    receipts: Vec<BlockEvent>,
+    fees: Amount,
+    reward: Amount,
+}
+
+impl<'a> Execution<'a> {
+    fn new(state: &'a State, timestamp: u64, producer: &str) -> Self {
+        Execution {
+            state,
+            timestamp,
+            producer: producer.to_string(),
+            balances: HashMap::new(),
+            changes: Vec::new(),
+            registered: Vec::new(),
+            bound_keys: Vec::new(),
+            nonces: Vec::new(),
+            zux_reserve: state.amm_pool.zux_reserve,
+            usd_reserve: state.amm_pool.usd_reserve,
+            creates_pool: false,
+            receipts: Vec::new(),
+            fees: Amount::ZERO,
+            reward: Amount::ZERO,
+        }
+    }
+
+    fn account_exists(&self, address: &str) -> bool {
+        self.state.accounts.contains_key(address) || self.registered.iter().any(|registered| registered == address)
+    }
+
+    fn balance(&self, address: &str, currency: &str) -> Amount {
+        self.balances.get(&(address.to_string(), currency.to_string()))
+            .copied()
+            .unwrap_or_else(|| self.state.balance(address, currency))
+    }
+
+    fn set_balance(&mut self, address: &str, currency: &str, amount: Amount) {
+        let key = (address.to_string(), currency.to_string());
+        if !self.balances.contains_key(&key) {
+            self.changes.push(key.clone());
+        }
+        self.balances.insert(key, amount);
+    }
+
+    fn debit(&mut self, address: &str, currency: &str, amount: Amount) -> Result<()> {
+        let current = self.balance(address, currency);
+        if current < amount {
+            return Err(BlockchainError::Transaction(
+                format!("Overdraft by {}: has {} {}, needs {} {}", address, current, currency, amount, currency)
+            ));
+        }
+        self.set_balance(address, currency, current.checked_sub(amount)?);
+        Ok(())
+    }
+
+    fn credit(&mut self, address: &str, currency: &str, amount: Amount) -> Result<()> {
+        let current = self.balance(address, currency);
+        self.set_balance(address, currency, current.checked_add(amount)?);
+        Ok(())
+    }
+
+    /// Check that the signing key belongs to the sender account, registering it for a `WalletRegister`
+    fn check_sender_key(&mut self, tx: &Transaction) -> Result<()> {
+        if tx.kind == TxKind::WalletRegister {
+            if tx.sender.is_empty() || self.account_exists(&tx.sender) {
+                return Err(BlockchainError::Transaction(format!("Account already exists: {}", tx.sender)));
+            }
+            self.state.check_registration(&tx.sender, &tx.sender_public_key)?;
+            self.registered.push(tx.sender.clone());
+            self.bound_keys.push((tx.sender.clone(), tx.sender_public_key.clone()));
+            return Ok(());
+        }
+
+        if !self.account_exists(&tx.sender) {
+            return Err(BlockchainError::Transaction(format!("Unknown sender account: {}", tx.sender)));
+        }
+
+        let bound_key = self.state.accounts.get(&tx.sender).and_then(|account| account.public_key.as_ref()).or_else(|| {
+            self.bound_keys.iter().find(|(address, _)| *address == tx.sender).map(|(_, key)| key)
+        });
+
+        match bound_key {
+            Some(key) if *key != tx.sender_public_key => Err(BlockchainError::Transaction(
+                format!("Transaction from {} is signed with a key that does not own the account", tx.sender)
+            )),
+            Some(_) => Ok(()),
+            None => {
+                self.bound_keys.push((tx.sender.clone(), tx.sender_public_key.clone()));
+                Ok(())
+            }
+        }
+    }
+
+    fn next_nonce(&self, address: &str) -> u64 {
+        self.nonces.iter()
+            .find(|(sender, _)| sender == address)
+            .map(|(_, nonce)| *nonce)
+            .unwrap_or_else(|| self.state.next_nonce(address))
+    }
+
+    /// Accept a transaction only on the chain it was signed for and with the sender's next nonce
+    fn check_replay(&mut self, tx: &Transaction) -> Result<()> {
+        if tx.chain_id != self.state.chain_id {
+            return Err(BlockchainError::Replay(format!(
+                "Transaction from {} was signed for chain {}, not {}", tx.sender, tx.chain_id, self.state.chain_id
+            )));
+        }
+
+        let expected = self.next_nonce(&tx.sender);
+        if tx.nonce < expected {
+            return Err(BlockchainError::Replay(format!(
+                "Transaction from {} reuses nonce {} (next nonce is {})", tx.sender, tx.nonce, expected
+            )));
+        }
+        if tx.nonce > expected {
+            return Err(BlockchainError::Replay(format!(
+                "Transaction from {} has out-of-order nonce {} (next nonce is {})", tx.sender, tx.nonce, expected
+            )));
+        }
+
+        match self.nonces.iter_mut().find(|(sender, _)| *sender == tx.sender) {
+            Some((_, nonce)) => *nonce += 1,
+            None => self.nonces.push((tx.sender.clone(), expected + 1)),
+        }
+        Ok(())
+    }
+
+    /// Minting and pool liquidity are reserved for the system wallet
+    fn require_system(&self, tx: &Transaction) -> Result<()> {
+        if tx.sender != SYSTEM_WALLET_ADDRESS {
+            return Err(BlockchainError::Transaction(
+                format!("{} transactions can only be sent by the system wallet, not {}", tx.kind.name(), tx.sender)
+            ));
+        }
+        Ok(())
+    }
+
+    fn require_recipient(&self, recipient: &str) -> Result<()> {
+        if !self.account_exists(recipient) {
+            return Err(BlockchainError::Transaction(format!("Unknown recipient account: {}", recipient)));
+        }
+        Ok(())
+    }
+
+    fn execute(&mut self, tx: &Transaction) -> Result<()> {
+        // Kind checks and signature
+        tx.verify()?;
+        self.check_sender_key(tx)?;
+        self.check_replay(tx)?;
+
+        let receipt = match &tx.kind {
+            TxKind::WalletRegister => {
+                // An address's genesis allocation is released when it registers
+                if let Some(allocations) = self.state.genesis_allocations.get(&tx.sender) {
+                    for (currency, amount) in allocations {
+                        self.credit(&tx.sender, currency, *amount)?;
+                    }
+                }
+                BlockEvent::WalletCreation(tx.sender.clone())
+            },
+            TxKind::Transfer { recipient, currency, amount } => {
+                self.require_recipient(recipient)?;
+                self.debit(&tx.sender, currency, *amount)?;
+                self.credit(recipient, currency, *amount)?;
+                BlockEvent::TokenCredit(recipient.clone(), currency.clone(), *amount)
+            },
+            TxKind::Mint { recipient, currency, amount } => {
+                self.require_system(tx)?;
+                self.require_recipient(recipient)?;
+                self.credit(recipient, currency, *amount)?;
+                BlockEvent::TokenCredit(recipient.clone(), currency.clone(), *amount)
+            },
+            TxKind::Swap { is_zux_to_usd, amount_in, min_out, deadline } => {
+                if self.timestamp > *deadline {
+                    return Err(BlockchainError::Transaction(
+                        format!("Swap deadline {} has passed (block time {})", deadline, self.timestamp)
+                    ));
+                }
+                if self.zux_reserve.is_zero() || self.usd_reserve.is_zero() {
+                    return Err(BlockchainError::Transaction("AMM pool has no liquidity".to_string()));
+                }
+
+                let (input_currency, output_currency) = if *is_zux_to_usd { ("ZUX", "USDZ") } else { ("USDZ", "ZUX") };
+                self.debit(&tx.sender, input_currency, *amount_in)?;
+
+                let fee_bps = self.state.amm_pool.fee_bps;
+                let output_amount = if *is_zux_to_usd {
+                    AmmPool::output_for_reserves(*amount_in, self.zux_reserve, self.usd_reserve, fee_bps)?
+                } else {
+                    AmmPool::output_for_reserves(*amount_in, self.usd_reserve, self.zux_reserve, fee_bps)?
+                };
+
+                if output_amount.is_zero() {
+                    return Err(BlockchainError::Transaction("Swap would result in too small output".to_string()));
+                }
+                if output_amount < *min_out {
+                    return Err(BlockchainError::Transaction(
+                        format!("Swap output {} {} is below the minimum {}", output_amount, output_currency, min_out)
+                    ));
+                }
+
+                if *is_zux_to_usd {
+                    self.zux_reserve = self.zux_reserve.checked_add(*amount_in)?;
+                    self.usd_reserve = self.usd_reserve.checked_sub(output_amount)?;
+                } else {
+                    self.usd_reserve = self.usd_reserve.checked_add(*amount_in)?;
+                    self.zux_reserve = self.zux_reserve.checked_sub(output_amount)?;
+                }
+
+                self.credit(&tx.sender, output_currency, output_amount)?;
+                BlockEvent::Swap(tx.sender.clone(), *is_zux_to_usd, *amount_in, output_amount)
+            },
+            TxKind::AddLiquidity { zux_amount, usd_amount } => {
+                self.require_system(tx)?;
+
+                // Deposits into a funded pool must not move its price beyond rounding
+                if !self.zux_reserve.is_zero() && !self.usd_reserve.is_zero() {
+                    let matching_usd = zux_amount.mul_div(self.usd_reserve.base_units(), self.zux_reserve.base_units())?;
+                    let difference = usd_amount.base_units().abs_diff(matching_usd.base_units());
+                    if difference > LIQUIDITY_ROUNDING_TOLERANCE.base_units() {
+                        return Err(BlockchainError::Transaction(format!(
+                            "Depositing {} ZUX with {} USDZ would move the pool price; {} USDZ keeps it",
+                            zux_amount, usd_amount, matching_usd
+                        )));
+                    }
+                }
+
+                self.debit(&tx.sender, "ZUX", *zux_amount)?;
+                self.debit(&tx.sender, "USDZ", *usd_amount)?;
+                self.zux_reserve = self.zux_reserve.checked_add(*zux_amount)?;
+                self.usd_reserve = self.usd_reserve.checked_add(*usd_amount)?;
+                if !self.state.pool_created {
+                    self.creates_pool = true;
+                }
+                BlockEvent::LiquidityAdded(tx.sender.clone(), *zux_amount, *usd_amount)
+            },
+            TxKind::RemoveLiquidity { share_bps } => {
+                self.require_system(tx)?;
+                if self.zux_reserve.is_zero() || self.usd_reserve.is_zero() {
+                    return Err(BlockchainError::Transaction("AMM pool has no liquidity".to_string()));
+                }
+
+                let zux_amount = self.zux_reserve.mul_div(u128::from(*share_bps), u128::from(BASIS_POINTS))?;
+                let usd_amount = self.usd_reserve.mul_div(u128::from(*share_bps), u128::from(BASIS_POINTS))?;
+                self.zux_reserve = self.zux_reserve.checked_sub(zux_amount)?;
+                self.usd_reserve = self.usd_reserve.checked_sub(usd_amount)?;
+                self.credit(&tx.sender, "ZUX", zux_amount)?;
+                self.credit(&tx.sender, "USDZ", usd_amount)?;
+                BlockEvent::LiquidityRemoved(tx.sender.clone(), zux_amount, usd_amount)
+            },
+        };
+
+        // The network fee is paid last, so a registration can pay it from its genesis allocation
+        if !tx.fee.is_zero() {
+            self.debit(&tx.sender, "ZUX", tx.fee)?;
+            self.fees = self.fees.checked_add(tx.fee)?;
+        }
+
+        self.receipts.push(receipt);
+        Ok(())
+    }
+
+    /// Credit the producer with the block reward and the fees, once every transaction has executed
+    ///
+    /// The genesis block has no producer reward. For any other block the
+    /// producer must have an account, possibly registered by the block itself.
+    fn reward_producer(&mut self) -> Result<()> {
+        let height = self.state.height + 1;
+        if height == 1 {
+            return Ok(());
+        }
+        if !self.account_exists(&self.producer) {
+            return Err(BlockchainError::Block(format!("Block #{} producer {} has no account", height, self.producer)));
+        }
+
+        self.reward = self.state.rewards.reward_at(height);
+        let payout = self.reward.checked_add(self.fees)?;
+        if !payout.is_zero() {
+            let producer = self.producer.clone();
+            self.credit(&producer, "ZUX", payout)?;
+        }
+        Ok(())
+    }
+
+    fn into_diff(self) -> StateDiff {
+        let balance_changes = self.changes.iter()
+            .map(|(address, currency)| BalanceChange {
+                address: address.clone(),
+                currency: currency.clone(),
+                before: self.state.balance(address, currency),
+                after: self.balances[&(address.clone(), currency.clone())],
+            })
+            .collect();
+
+        let nonce_changes = self.nonces.iter()
+            .map(|(address, after)| (address.clone(), self.state.next_nonce(address), *after))
+            .collect();
+
+        let block_id = self.state.height + 1;
+        StateDiff {
+            block_id,
+            block_hash: String::new(),
+            parent_hash: self.state.tip_hash.clone(),
+            balance_changes,
+            registered_accounts: self.registered,
+            bound_keys: self.bound_keys,
+            nonce_changes,
+            pool_before: (self.state.amm_pool.zux_reserve, self.state.amm_pool.usd_reserve),
+            pool_after: (self.zux_reserve, self.usd_reserve),
+            creates_pool: self.creates_pool,
+            receipts: self.receipts,
+            producer: self.producer,
+            fees: self.fees,
+            reward: self.reward,
+            difficulty_before: self.state.difficulty,
+            difficulty_after: self.state.difficulty.after_block(&self.state.retarget, block_id, self.timestamp),
+            timestamp: self.timestamp,
+            state_root: String::new(),
+        }
+    }
+}
+
+/// The next block being packed: transactions are executed one at a time on
+/// top of the ledger and only kept if they succeed
+pub struct BlockBuilder<'a> {
+    execution: Execution<'a>,
+    transactions: Vec<Transaction>,
+}
+
+impl BlockBuilder<'_> {
+    /// Execute `transaction` after those already added, leaving the block unchanged if it fails
+    pub fn try_add(&mut self, transaction: &Transaction) -> Result<()> {
+        let mut attempt = self.execution.clone();
+        attempt.execute(transaction)?;
+        self.execution = attempt;
+        self.transactions.push(transaction.clone());
+        Ok(())
+    }
+
+    /// Transactions added so far, in execution order
+    pub fn transactions(&self) -> &[Transaction] {
+        &self.transactions
+    }
+
+    pub fn len(&self) -> usize {
+        self.transactions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.transactions.is_empty()
+    }
+
+    /// The added transactions and what executing them and paying the producer
+    /// changes, as `State::preview` would return it
+    pub fn finish(mut self) -> Result<(Vec<Transaction>, StateDiff)> {
+        self.execution.reward_producer()?;
+        let state = self.execution.state;
+        Ok((self.transactions, state.with_state_root(self.execution.into_diff())))
+    }
+}
+
+impl State {
+    /// Create an empty ledger for the chain `chain_id`
+    ///
+    /// `genesis_allocations` lists (address, currency, amount) balances that are
+    /// credited when the corresponding account is registered on chain, and
+    /// `genesis_keys` the (address, public key) pairs that alone may register
+    /// those accounts and the system account.
+    /// `rewards` sets the new ZUX issued to the producer of each block,
+    /// `retarget` how the proof-of-work target follows the block time and
+    /// `consensus` who produces blocks and how they are sealed.
+    pub fn new(chain_id: &str, genesis_allocations: &[(&str, &str, Amount)], genesis_keys: &[(&str, &[u8])], fee_bps: u32,
+               rewards: RewardSchedule, retarget: RetargetRule, consensus: ConsensusRule) -> Self {
+        let mut allocations: HashMap<String, Vec<(String, Amount)>> = HashMap::new();
+        for (address, currency, amount) in genesis_allocations {
+            allocations.entry(address.to_string())
+                .or_default()
+                .push((currency.to_string(), *amount));
+        }
+
+        State {
+            chain_id: chain_id.to_string(),
+            accounts: HashMap::new(),
+            amm_pool: AmmPool::empty(fee_bps),
+            pool_created: false,
+            genesis_allocations: allocations,
+            genesis_keys: genesis_keys.iter().map(|(address, key)| (address.to_string(), key.to_vec())).collect(),
+            rewards,
+            retarget,
+            difficulty: Difficulty::initial(&retarget),
+            consensus,
+            height: 0,
+            tip_hash: "0".repeat(64),
+            tree: SparseMerkleTree::new(),
+        }
+    }
+
+    /// Capture the ledger at the current height, together with the off-chain
+    /// simulation state needed to carry on from it
+    pub fn snapshot(&self, wallets: &HashMap<String, Wallet>, code_generator: &UniqueCodeGenerator) -> Snapshot {
+        let mut accounts: Vec<(String, Account)> = self.accounts.iter()
+            .map(|(address, account)| (address.clone(), account.clone()))
+            .collect();
+        accounts.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut genesis_allocations: Vec<(String, Vec<(String, Amount)>)> = self.genesis_allocations.iter()
+            .filter(|(address, _)| !self.accounts.contains_key(*address))
+            .map(|(address, allocations)| (address.clone(), allocations.clone()))
+            .collect();
+        genesis_allocations.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut genesis_keys: Vec<(String, Vec<u8>)> = self.genesis_keys.iter()
+            .filter(|(address, _)| !self.accounts.contains_key(*address))
+            .map(|(address, key)| (address.clone(), key.clone()))
+            .collect();
+        genesis_keys.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut wallets: Vec<Wallet> = wallets.values().cloned().collect();
+        wallets.sort_by(|a, b| a.address.cmp(&b.address));
+
+        Snapshot::new(
+            LedgerSnapshot {
+                chain_id: self.chain_id.clone(),
+                height: self.height,
+                tip_hash: self.tip_hash.clone(),
+                state_root: self.state_root(),
+                accounts,
+                amm_pool: self.amm_pool.clone(),
+                pool_created: self.pool_created,
+                genesis_allocations,
+                genesis_keys,
+                rewards: self.rewards,
+                retarget: self.retarget,
+                difficulty: self.difficulty,
+                consensus: self.consensus.clone(),
+            },
+            wallets,
+            code_generator,
+        )
+    }
+
+    /// Rebuild a ledger from a snapshot, checking it against its recorded state root
+    pub fn from_snapshot(snapshot: &LedgerSnapshot) -> Result<Self> {
+        let mut state = State {
+            chain_id: snapshot.chain_id.clone(),
+            accounts: snapshot.accounts.iter().cloned().collect(),
+            amm_pool: snapshot.amm_pool.clone(),
+            pool_created: snapshot.pool_created,
+            genesis_allocations: snapshot.genesis_allocations.iter().cloned().collect(),
+            genesis_keys: snapshot.genesis_keys.iter().cloned().collect(),
+            rewards: snapshot.rewards,
+            retarget: snapshot.retarget,
+            difficulty: snapshot.difficulty,
+            consensus: snapshot.consensus.clone(),
+            height: snapshot.height,
+            tip_hash: snapshot.tip_hash.clone(),
+            tree: SparseMerkleTree::new(),
+        };
+
+        for (address, account) in &state.accounts {
+            state.tree.update(&account_key(address), Some(&account_value_hash(address, account)));
+        }
+        if state.pool_created {
+            let value = pool_value_hash(true, state.amm_pool.zux_reserve, state.amm_pool.usd_reserve);
+            state.tree.update(&pool_key(), Some(&value));
+        }
+
+        if state.state_root() != snapshot.state_root {
+            return Err(BlockchainError::System(format!(
+                "Snapshot at height {} does not match its state root {}", snapshot.height, snapshot.state_root
+            )));
+        }
+        Ok(state)
+    }
+
+    /// Check that `public_key` may register the account `address`
+    ///
+    /// The pool address can never be registered, and the system account and
+    /// the accounts holding a genesis allocation only with the key fixed for
+    /// them at genesis.
+    pub fn check_registration(&self, address: &str, public_key: &[u8]) -> Result<()> {
+        match self.genesis_keys.get(address) {
+            Some(key) if key.as_slice() != public_key => Err(BlockchainError::Transaction(
+                format!("Account {} can only be registered with the key fixed at genesis", address)
+            )),
+            Some(_) => Ok(()),
+            None if address == AMM_POOL_ADDRESS || address == SYSTEM_WALLET_ADDRESS || self.genesis_allocations.contains_key(address) =>
+                Err(BlockchainError::Transaction(format!("Reserved address cannot be registered: {}", address))),
+            None => Ok(()),
+        }
+    }
+
+    /// Commitment to every account and the AMM pool reserves
+    pub fn state_root(&self) -> String {
+        hex::encode(self.tree.root())
+    }
+
+    /// Network this ledger accepts blocks and transactions for
+    pub fn chain_id(&self) -> &str {
+        &self.chain_id
+    }
+
+    /// Height of the last applied block (0 before genesis)
+    pub fn height(&self) -> u64 {
+        self.height
+    }
+
+    /// Hash of the last applied block
+    pub fn tip_hash(&self) -> &str {
+        &self.tip_hash
+    }
+
+    /// The AMM pool as of the last applied block
+    pub fn amm_pool(&self) -> &AmmPool {
+        &self.amm_pool
+    }
+
+    /// Block rewards issued by this chain
+    pub fn rewards(&self) -> &RewardSchedule {
+        &self.rewards
+    }
+
+    /// How the proof-of-work target of this chain is adjusted
+    pub fn retarget_rule(&self) -> &RetargetRule {
+        &self.retarget
+    }
+
+    /// Consensus engine blocks are produced and sealed under
+    pub fn consensus(&self) -> &ConsensusRule {
+        &self.consensus
+    }
+
+    /// Proof-of-work target the next block must carry
+    pub fn next_target(&self) -> Target {
+        self.difficulty.target
+    }
+
+    /// Look up an account
+    pub fn account(&self, address: &str) -> Option<&Account> {
+        self.accounts.get(address)
+    }
+
+    /// All accounts known to the ledger
+    pub fn accounts(&self) -> &HashMap<String, Account> {
+        &self.accounts
+    }
+
+    /// Nonce the next transaction from `address` must carry (0 for an unknown account)
+    pub fn next_nonce(&self, address: &str) -> u64 {
+        self.accounts.get(address).map(|account| account.nonce).unwrap_or(0)
+    }
+
+    /// Get the balance of an account for a specific currency
+    pub fn balance(&self, address: &str, currency: &str) -> Amount {
+        self.accounts.get(address)
+            .and_then(|account| account.balances.get(currency))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Total amount of a currency held by accounts and the AMM pool
+    ///
+    /// For ZUX this is the genesis supply plus the block rewards issued so far.
+    pub fn total_supply(&self, currency: &str) -> Result<Amount> {
+        let pooled = match currency {
+            "ZUX" => self.amm_pool.zux_reserve,
+            "USDZ" => self.amm_pool.usd_reserve,
+            _ => Amount::ZERO,
+        };
+
+        let held = self.accounts.values()
+            .map(|account| account.balances.get(currency).copied().unwrap_or_default());
+        Amount::checked_sum(held.chain(std::iter::once(pooled)))
+    }
+
+    /// Prove the balances of `address` against the state root of the current tip
+    ///
+    /// Only the tip's tree is kept, so there is no proof against an earlier block.
+    pub fn balance_proof(&self, address: &str) -> Result<BalanceProof> {
+        let account = self.accounts.get(address).ok_or_else(|| {
+            BlockchainError::Proof(format!("Unknown account: {}", address))
+        })?;
+        let path = self.tree.proof(&account_key(address));
+        Ok(BalanceProof::new(self.height, address, account, &path))
+    }
+
+    /// Execute transactions in a block timestamped `timestamp` and produced by
+    /// `producer` against the current state without committing them
+    ///
+    /// Block producers use the returned receipts as the block events and the
+    /// state root for the header of the block they are about to seal. Only the
+    /// genesis block has no transactions.
+    pub fn preview(&self, transactions: &[Transaction], timestamp: u64, producer: &str) -> Result<StateDiff> {
+        let mut execution = Execution::new(self, timestamp, producer);
+        if transactions.is_empty() {
+            if self.height != 0 {
+                return Err(BlockchainError::Block(format!("Block #{} carries no transactions", self.height + 1)));
+            }
+            execution.receipts.push(BlockEvent::Genesis);
+        }
+        for tx in transactions {
+            execution.execute(tx)?;
+        }
+        execution.reward_producer()?;
+        Ok(self.with_state_root(execution.into_diff()))
+    }
+
+    /// Start packing the next block, timestamped `timestamp` and produced by `producer`, one transaction at a time
+    pub fn block_builder(&self, timestamp: u64, producer: &str) -> BlockBuilder<'_> {
+        BlockBuilder {
+            execution: Execution::new(self, timestamp, producer),
+            transactions: Vec::new(),
+        }
+    }
+
+    fn with_state_root(&self, mut diff: StateDiff) -> StateDiff {
+        diff.state_root = hex::encode(self.tree.root_with(&self.leaf_updates(&diff)));
+        diff
+    }
+
+    /// Validate a block and apply it, returning what changed
+    ///
+    /// The state is left untouched if the block is rejected.
+    pub fn apply_block(&mut self, block: &Block) -> Result<StateDiff> {
+        // The block must extend the current tip
+        if block.id != self.height + 1 {
+            return Err(BlockchainError::Block(
+                format!("Block #{} does not follow height {}", block.id, self.height)
+            ));
+        }
+        if block.parent_hash != self.tip_hash {
+            return Err(BlockchainError::Block(
+                format!("Block #{} parent {} is not the current tip {}", block.id, block.parent_hash, self.tip_hash)
+            ));
+        }
+
+        if block.network_name != self.chain_id {
+            return Err(BlockchainError::Block(
+                format!("Block #{} belongs to network {}, not {}", block.id, block.network_name, self.chain_id)
+            ));
+        }
+
+        // Header hash, seal and transaction signatures
+        if block.target != self.difficulty.target {
+            return Err(BlockchainError::Block(
+                format!("Block #{} target {} is not the expected {}", block.id, block.target, self.difficulty.target)
+            ));
+        }
+        block.verify()?;
+        self.consensus.engine().verify_seal(block, self)?;
+
+        // The committed transaction root must cover exactly this body
+        if Block::calculate_merkle_root(&block.transactions, &block.events) != block.tx_root {
+            return Err(BlockchainError::Block(format!("Block #{} transaction root does not match its body", block.id)));
+        }
+
+        // The events must be the receipts of executing the body
+        let mut diff = self.preview(&block.transactions, block.timestamp, &block.producer)?;
+        if diff.receipts != block.events {
+            return Err(BlockchainError::Block(
+                format!("Block #{} events do not match its executed transactions", block.id)
+            ));
+        }
+
+        if diff.state_root != block.state_root {
+            return Err(BlockchainError::Block(
+                format!("Block #{} state root {} does not match the executed state {}", block.id, block.state_root, diff.state_root)
+            ));
+        }
+
+        diff.block_hash = block.hash.clone();
+        self.commit(&diff)?;
+        Ok(diff)
+    }
+
+    /// Account leaf value after a diff is applied, or `None` if the account does not exist
+    fn account_after(&self, diff: &StateDiff, address: &str) -> Option<Account> {
+        let mut account = match self.accounts.get(address) {
+            Some(account) => account.clone(),
+            None if diff.registered_accounts.iter().any(|registered| registered == address) => {
+                Self::new_account()
+            },
+            None => return None,
+        };
+
+        if let Some((_, key)) = diff.bound_keys.iter().find(|(bound, _)| bound == address) {
+            account.public_key = Some(key.clone());
+        }
+        if let Some((_, _, nonce)) = diff.nonce_changes.iter().find(|(sender, _, _)| sender == address) {
+            account.nonce = *nonce;
+        }
+        for change in diff.balance_changes.iter().filter(|change| change.address == address) {
+            account.balances.insert(change.currency.clone(), change.after);
+        }
+
+        Some(account)
+    }
+
+    /// Tree updates a diff implies
+    fn leaf_updates(&self, diff: &StateDiff) -> Vec<(Hash, Option<Hash>)> {
+        let mut updates: Vec<(Hash, Option<Hash>)> = diff.touched_accounts().iter()
+            .map(|address| {
+                let value = self.account_after(diff, address)
+                    .map(|account| account_value_hash(address, &account));
+                (account_key(address), value)
+            })
+            .collect();
+
+        if diff.touches_pool() {
+            let pool_created = self.pool_created || diff.creates_pool;
+            updates.push((pool_key(), Some(pool_value_hash(pool_created, diff.pool_after.0, diff.pool_after.1))));
+        }
+
+        updates
+    }
+
+    /// Recompute the tree leaves a diff touched from the live state
+    fn refresh_leaves(&mut self, diff: &StateDiff) {
+        for address in diff.touched_accounts() {
+            let value = self.accounts.get(&address).map(|account| account_value_hash(&address, account));
+            self.tree.update(&account_key(&address), value.as_ref());
+        }
+
+        if diff.touches_pool() {
+            let value = pool_value_hash(self.pool_created, self.amm_pool.zux_reserve, self.amm_pool.usd_reserve);
+            let value = if self.pool_created { Some(value) } else { None };
+            self.tree.update(&pool_key(), value.as_ref());
+        }
+    }
+
+    /// A freshly registered account with zero balances
+    fn new_account() -> Account {
+        let mut account = Account::default();
+        for currency in SUPPORTED_CURRENCIES.iter() {
+            account.balances.insert((*currency).to_string(), Amount::ZERO);
+        }
+        account
+    }
+
+    /// Commit a validated diff
+    ///
+    /// The pool operations are replayed on a copy first, so an error leaves the
+    /// ledger as it was.
+    fn commit(&mut self, diff: &StateDiff) -> Result<()> {
+        // Replay pool operations so price and volume tracking follow the chain
+        let mut amm_pool = self.amm_pool.clone();
+        for receipt in &diff.receipts {
+            match receipt {
+                BlockEvent::LiquidityAdded(_, zux_amount, usd_amount) => {
+                    amm_pool.add_liquidity("ZUX", *zux_amount, diff.timestamp)?;
+                    amm_pool.add_liquidity("USDZ", *usd_amount, diff.timestamp)?;
+                },
+                BlockEvent::Swap(_, true, input_amount, _) => {
+                    amm_pool.swap_zux_to_usd(*input_amount, diff.timestamp)?;
+                },
+                BlockEvent::Swap(_, false, input_amount, _) => {
+                    amm_pool.swap_usd_to_zux(*input_amount, diff.timestamp)?;
+                },
+                _ => {},
+            }
+        }
+        amm_pool.set_reserves(diff.pool_after.0, diff.pool_after.1);
+
+        for address in &diff.registered_accounts {
+            self.accounts.insert(address.clone(), Self::new_account());
+        }
+
+        for (address, key) in &diff.bound_keys {
+            if let Some(account) = self.accounts.get_mut(address) {
+                account.public_key = Some(key.clone());
+            }
+        }
+
+        for (address, _, nonce) in &diff.nonce_changes {
+            if let Some(account) = self.accounts.get_mut(address) {
+                account.nonce = *nonce;
+            }
+        }
+
+        for change in &diff.balance_changes {
+            if let Some(account) = self.accounts.get_mut(&change.address) {
+                account.balances.insert(change.currency.clone(), change.after);
+            }
+        }
+
+        if diff.creates_pool {
+            self.pool_created = true;
+        }
+        self.amm_pool = amm_pool;
+        self.refresh_leaves(diff);
+
+        self.difficulty = diff.difficulty_after;
+        self.height = diff.block_id;
+        self.tip_hash = diff.block_hash.clone();
+        Ok(())
+    }
+
+    /// Undo the most recently applied block
+    ///
+    /// Reserves are restored exactly; the pool's price and volume statistics
+    /// are informational and keep the reverted trades.
+    pub fn revert(&mut self, diff: &StateDiff) -> Result<()> {
+        if diff.block_hash != self.tip_hash {
+            return Err(BlockchainError::Block(
+                format!("Cannot revert block #{}: it is not the current tip", diff.block_id)
+            ));
+        }
+
+        for change in diff.balance_changes.iter().rev() {
+            if let Some(account) = self.accounts.get_mut(&change.address) {
+                account.balances.insert(change.currency.clone(), change.before);
+            }
+        }
+
+        for (address, nonce, _) in &diff.nonce_changes {
+            if let Some(account) = self.accounts.get_mut(address) {
+                account.nonce = *nonce;
+            }
+        }
+
+        for (address, _) in &diff.bound_keys {
+            if let Some(account) = self.accounts.get_mut(address) {
+                account.public_key = None;
+            }
+        }
+
+        for address in &diff.registered_accounts {
+            self.accounts.remove(address);
+        }
+
+        if diff.creates_pool {
+            self.pool_created = false;
+        }
+        self.amm_pool.set_reserves(diff.pool_before.0, diff.pool_before.1);
+        self.refresh_leaves(diff);
+
+        self.difficulty = diff.difficulty_before;
+        self.height = diff.block_id - 1;
+        self.tip_hash = diff.parent_hash.clone();
+        Ok(())
+    }
+}
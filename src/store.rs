@@ -0,0 +1,417 @@
+//! Block storage backends
+//!
+//! A `ChainStore` keeps every accepted block so a chain can outlive the
+//! process that produced it. `FileStore` is an append-only block log plus a
+//! fixed-size index by height, which is enough to resume a simulation from
+//! its last block or browse a finished run in the explorer.
+//!
+//! A store may start after a base block instead of at genesis, for chains
+//! continued from a state snapshot.
+
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use crate::codec;
+use crate::block::Block;
+use crate::error::{BlockchainError, Result};
+use crate::state::State;
+
+/// Storage for a linear chain of blocks, addressed by height and by hash
+pub trait ChainStore {
+    /// Append the next block; it must extend the stored tip
+    fn append(&mut self, block: &Block) -> Result<()>;
+
+    /// Height and hash of the block the stored chain continues from; height 0 for a chain stored from genesis
+    fn base(&self) -> (u64, String);
+
+    /// Id of the last stored block, or the base height for an empty store
+    fn height(&self) -> u64;
+
+    /// Hash of the last stored block, or all zeros for an empty store
+    fn tip_hash(&self) -> String;
+
+    /// Block with the given id
+    fn block_at(&self, height: u64) -> Result<Option<Block>>;
+
+    /// Height of the block with the given hash
+    fn height_of(&self, hash: &str) -> Option<u64>;
+
+    /// Block with the given hash
+    fn block_by_hash(&self, hash: &str) -> Result<Option<Block>> {
+        match self.height_of(hash) {
+            Some(height) => self.block_at(height),
+            None => Ok(None),
+        }
+    }
+
+    /// Every stored block from `from_height` to the tip, in order
+    fn blocks_from(&self, from_height: u64) -> Result<Vec<Block>> {
+        (from_height.max(self.base().0 + 1)..=self.height())
+            .map(|height| {
+                self.block_at(height)?.ok_or_else(|| {
+                    BlockchainError::System(format!("Block #{} is missing from the chain store", height))
+                })
+            })
+            .collect()
+    }
+}
+
+/// Check that `block` extends the tip of `store`
+fn check_extends(store: &dyn ChainStore, block: &Block) -> Result<()> {
+    if block.id != store.height() + 1 || block.parent_hash != store.tip_hash() {
+        return Err(BlockchainError::Block(
+            format!("Block #{} does not extend the stored tip #{} {}", block.id, store.height(), store.tip_hash())
+        ));
+    }
+    Ok(())
+}
+
+/// Apply every stored block from the first to the tip, returning them
+///
+/// `state` must be the state at the store's base: the genesis state the chain
+/// was produced from, or the snapshot it was continued from.
+pub fn replay(store: &dyn ChainStore, state: &mut State) -> Result<Vec<Block>> {
+    let blocks = store.blocks_from(state.height() + 1)?;
+    for block in &blocks {
+        state.apply_block(block)?;
+    }
+    Ok(blocks)
+}
+
+/// Check every stored block from the store's base to the tip, failing on the first mismatch
+///
+/// Each block must be stored under its own height and hash, extend its parent,
+/// carry a valid header hash, seal and signatures, and reproduce its
+/// transaction and state roots when applied to `state`, which must be the state
+/// at the store's base. Returns the number of blocks checked.
+pub fn verify_chain(store: &dyn ChainStore, state: &mut State) -> Result<u64> {
+    let (base_height, base_hash) = store.base();
+    if state.height() != base_height || state.tip_hash() != base_hash {
+        return Err(BlockchainError::Block(format!(
+            "Chain verification failed: the store starts after block #{} {} but the state is at #{} {}",
+            base_height, base_hash, state.height(), state.tip_hash()
+        )));
+    }
+
+    for height in base_height + 1..=store.height() {
+        let fail = |reason: String| BlockchainError::Block(format!("Chain verification failed at block #{}: {}", height, reason));
+        let block = store.block_at(height)?.ok_or_else(|| fail("missing from the chain store".to_string()))?;
+        if block.id != height {
+            return Err(fail(format!("stored block has id #{}", block.id)));
+        }
+        if store.height_of(&block.hash) != Some(height) {
+            return Err(fail(format!("block hash {} is not indexed at this height", block.hash)));
+        }
+        state.apply_block(&block).map_err(|e| fail(e.to_string()))?;
+    }
+
+    if state.tip_hash() != store.tip_hash() {
+        return Err(BlockchainError::Block(
+            format!("Chain verification failed: verified tip {} is not the stored tip {}", state.tip_hash(), store.tip_hash())
+        ));
+    }
+    Ok(store.height() - base_height)
+}
+
+/// In-memory store, for runs that do not need to persist
+#[derive(Debug, Clone)]
+pub struct MemoryStore {
+    base_height: u64,
+    base_hash: String,
+    blocks: Vec<Block>,
+    heights: HashMap<String, u64>,
+}
+
+impl MemoryStore {
+    /// Empty store for a chain starting at genesis
+    pub fn new() -> Self {
+        Self::with_base(0, &"0".repeat(64))
+    }
+
+    /// Empty store for a chain continuing from block `base_height` with hash `base_hash`
+    pub fn with_base(base_height: u64, base_hash: &str) -> Self {
+        MemoryStore {
+            base_height,
+            base_hash: base_hash.to_string(),
+            blocks: Vec::new(),
+            heights: HashMap::new(),
+        }
+    }
+}
+
+impl Default for MemoryStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChainStore for MemoryStore {
+    fn append(&mut self, block: &Block) -> Result<()> {
+        check_extends(self, block)?;
+        self.heights.insert(block.hash.clone(), block.id);
+        self.blocks.push(block.clone());
+        Ok(())
+    }
+
+    fn base(&self) -> (u64, String) {
+        (self.base_height, self.base_hash.clone())
+    }
+
+    fn height(&self) -> u64 {
+        self.base_height + self.blocks.len() as u64
+    }
+
+    fn tip_hash(&self) -> String {
+        self.blocks.last().map(|block| block.hash.clone()).unwrap_or_else(|| self.base_hash.clone())
+    }
+
+    fn block_at(&self, height: u64) -> Result<Option<Block>> {
+        Ok(height.checked_sub(self.base_height + 1).and_then(|index| self.blocks.get(index as usize)).cloned())
+    }
+
+    fn height_of(&self, hash: &str) -> Option<u64> {
+        self.heights.get(hash).copied()
+    }
+}
+
+const LOG_FILE: &str = "blocks.log";
+const INDEX_FILE: &str = "blocks.idx";
+const BASE_FILE: &str = "blocks.base"; // Base height (u64) and hash (32 bytes), if not genesis
+
+// Index record: log offset (u64), record length (u32), block hash (32 bytes)
+const INDEX_RECORD_LEN: usize = 8 + 4 + 32;
+
+/// Location of one block in the log
+#[derive(Debug, Clone)]
+struct IndexEntry {
+    offset: u64,
+    len: u32,
+    hash: String,
+}
+
+/// Append-only file store
+///
+/// `blocks.log` holds length-prefixed block records in the canonical encoding
+/// (see [`crate::codec`]) and `blocks.idx` one fixed-size record per height.
+/// `blocks.base` records the block a chain continued from a snapshot starts
+/// after. Both block files are only ever appended to; a record cut short by a
+/// crash is discarded when the store is reopened for writing. Readers open the store read-only so they can follow a chain that
+/// another process is still appending to.
+#[derive(Debug)]
+pub struct FileStore {
+    dir: PathBuf,
+    log: File,
+    index: File,
+    base_height: u64,
+    base_hash: String,
+    entries: Vec<IndexEntry>,
+    heights: HashMap<String, u64>,
+    log_len: u64,
+    writable: bool,
+}
+
+impl FileStore {
+    /// Open the store in `dir`, creating it if needed, and recover from any torn append
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)?;
+        Self::load(dir, true)
+    }
+
+    /// Open an existing store without modifying it
+    pub fn open_read_only(dir: impl AsRef<Path>) -> Result<Self> {
+        Self::load(dir.as_ref(), false)
+    }
+
+    fn load(dir: &Path, writable: bool) -> Result<Self> {
+        let dir = dir.to_path_buf();
+        let mut open_options = OpenOptions::new();
+        open_options.read(true);
+        if writable {
+            open_options.append(true).create(true);
+        }
+
+        let log = open_options.open(dir.join(LOG_FILE))?;
+        let mut index = open_options.open(dir.join(INDEX_FILE))?;
+
+        let (base_height, base_hash) = match fs::read(dir.join(BASE_FILE)) {
+            Ok(bytes) if bytes.len() == 8 + 32 => {
+                (u64::from_be_bytes(bytes[..8].try_into().unwrap()), hex::encode(&bytes[8..]))
+            },
+            Ok(_) => return Err(BlockchainError::System(format!("Corrupt {} in {}", BASE_FILE, dir.display()))),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => (0, "0".repeat(64)),
+            Err(e) => return Err(e.into()),
+        };
+
+        let log_len = log.metadata()?.len();
+        let mut index_bytes = Vec::new();
+        index.read_to_end(&mut index_bytes)?;
+
+        // Keep index records that point at complete, contiguous log records
+        let mut entries = Vec::new();
+        let mut end = 0u64;
+        for record in index_bytes.chunks_exact(INDEX_RECORD_LEN) {
+            let offset = u64::from_be_bytes(record[0..8].try_into().unwrap());
+            let len = u32::from_be_bytes(record[8..12].try_into().unwrap());
+            if offset != end || offset + 4 + len as u64 > log_len {
+                break;
+            }
+            entries.push(IndexEntry { offset, len, hash: hex::encode(&record[12..]) });
+            end = offset + 4 + len as u64;
+        }
+
+        let mut store = FileStore {
+            dir,
+            log,
+            index,
+            heights: entries.iter().enumerate().map(|(i, entry)| (entry.hash.clone(), base_height + i as u64 + 1)).collect(),
+            base_height,
+            base_hash,
+            entries,
+            log_len,
+            writable,
+        };
+        if writable {
+            store.index.set_len((store.entries.len() * INDEX_RECORD_LEN) as u64)?;
+        }
+
+        // Re-index complete log records whose index record was never written
+        while let Some(len) = store.record_len(end)? {
+            let block = match store.read_record(end, len) {
+                Ok(block) if check_extends(&store, &block).is_ok() => block,
+                _ => break,
+            };
+            store.write_index(IndexEntry { offset: end, len, hash: block.hash })?;
+            end += 4 + len as u64;
+        }
+
+        // Drop whatever is left of a torn append
+        if writable {
+            store.log.set_len(end)?;
+        }
+        store.log_len = end;
+        Ok(store)
+    }
+
+    /// Create an empty store in `dir`, discarding any chain already there
+    pub fn create(dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = dir.as_ref();
+        for file in [LOG_FILE, INDEX_FILE, BASE_FILE] {
+            match fs::remove_file(dir.join(file)) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+                _ => {},
+            }
+        }
+        Self::open(dir)
+    }
+
+    /// Create an empty store in `dir` for a chain continuing from block `base_height` with hash `base_hash`
+    pub fn create_with_base(dir: impl AsRef<Path>, base_height: u64, base_hash: &str) -> Result<Self> {
+        let dir = dir.as_ref();
+        let hash = hex::decode(base_hash)
+            .ok()
+            .filter(|hash| hash.len() == 32)
+            .ok_or_else(|| BlockchainError::Block(format!("Invalid block hash: {}", base_hash)))?;
+
+        Self::create(dir)?;
+        let mut base = base_height.to_be_bytes().to_vec();
+        base.extend_from_slice(&hash);
+        fs::write(dir.join(BASE_FILE), base)?;
+        Self::open(dir)
+    }
+
+    /// Directory holding the store files
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Length of the complete log record at `offset`, if there is one
+    fn record_len(&self, offset: u64) -> Result<Option<u32>> {
+        if offset + 4 > self.log_len {
+            return Ok(None);
+        }
+
+        let mut log = &self.log;
+        let mut len_bytes = [0u8; 4];
+        log.seek(SeekFrom::Start(offset))?;
+        log.read_exact(&mut len_bytes)?;
+        let len = u32::from_be_bytes(len_bytes);
+        Ok((offset + 4 + len as u64 <= self.log_len).then_some(len))
+    }
+
+    fn read_record(&self, offset: u64, len: u32) -> Result<Block> {
+        let mut log = &self.log;
+        let mut bytes = vec![0u8; len as usize];
+        log.seek(SeekFrom::Start(offset + 4))?;
+        log.read_exact(&mut bytes)?;
+        codec::from_bytes(&bytes)
+            .map_err(|e| BlockchainError::System(format!("Corrupt block record at offset {}: {}", offset, e)))
+    }
+
+    fn write_index(&mut self, entry: IndexEntry) -> Result<()> {
+        let hash = hex::decode(&entry.hash)
+            .ok()
+            .filter(|hash| hash.len() == 32)
+            .ok_or_else(|| BlockchainError::Block(format!("Invalid block hash: {}", entry.hash)))?;
+
+        let mut record = Vec::with_capacity(INDEX_RECORD_LEN);
+        record.extend_from_slice(&entry.offset.to_be_bytes());
+        record.extend_from_slice(&entry.len.to_be_bytes());
+        record.extend_from_slice(&hash);
+        if self.writable {
+            self.index.write_all(&record)?;
+        }
+
+        self.heights.insert(entry.hash.clone(), self.height() + 1);
+        self.entries.push(entry);
+        Ok(())
+    }
+}
+
+impl ChainStore for FileStore {
+    fn append(&mut self, block: &Block) -> Result<()> {
+        if !self.writable {
+            return Err(BlockchainError::System(format!("Chain store {} is open read-only", self.dir.display())));
+        }
+        check_extends(self, block)?;
+
+        let bytes = codec::to_bytes(block);
+        let len = u32::try_from(bytes.len())
+            .map_err(|_| BlockchainError::Block(format!("Block #{} is too large to store", block.id)))?;
+
+        // The log record goes first so the index never points past the log
+        let mut record = Vec::with_capacity(4 + bytes.len());
+        record.extend_from_slice(&len.to_be_bytes());
+        record.extend_from_slice(&bytes);
+        self.log.write_all(&record)?;
+
+        let offset = self.log_len;
+        self.log_len += record.len() as u64;
+        self.write_index(IndexEntry { offset, len, hash: block.hash.clone() })
+    }
+
+    fn base(&self) -> (u64, String) {
+        (self.base_height, self.base_hash.clone())
+    }
+
+    fn height(&self) -> u64 {
+        self.base_height + self.entries.len() as u64
+    }
+
+    fn tip_hash(&self) -> String {
+        self.entries.last().map(|entry| entry.hash.clone()).unwrap_or_else(|| self.base_hash.clone())
+    }
+
+    fn block_at(&self, height: u64) -> Result<Option<Block>> {
+        match height.checked_sub(self.base_height + 1).and_then(|index| self.entries.get(index as usize)) {
+            Some(entry) => self.read_record(entry.offset, entry.len).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn height_of(&self, hash: &str) -> Option<u64> {
+        self.heights.get(hash).copied()
+    }
+}
@@ -0,0 +1,316 @@
+//! Parameter sweeps over seeded headless runs
+//!
+//! A sweep varies some scenario fields over lists or ranges of values, runs a
+//! headless simulation for every combination and every seed, spread over the
+//! CPU cores, and compares the combinations by the figures of their run
+//! reports averaged over the seeds. Every combination is run with the same
+//! seeds, so they face the same wallets and the same sequence of decisions as
+//! far as their parameters allow.
+
+use std::fmt::Write as _;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use log::{info, warn};
+
+use crate::config::SimulationConfig;
+use crate::consensus::EngineKind;
+use crate::error::{BlockchainError, Result};
+use crate::report::RunReport;
+use crate::simulation::{run_simulation, SimulationOptions};
+
+/// Directory a sweep keeps its runs and tables in unless another one is given
+pub const DEFAULT_SWEEP_DIR: &str = "zux_sweep";
+
+/// Scenario fields a sweep can vary
+pub const SWEEP_KEYS: [&str; 5] = ["fee_percent", "liquidity_fraction", "whale_probability", "mega_whale_probability", "wallet_count"];
+
+// Most points an inclusive range may expand to
+const MAX_RANGE_POINTS: usize = 1_000;
+
+/// The values one scenario field takes in a sweep
+#[derive(Debug, Clone, PartialEq)]
+pub struct Axis {
+    pub key: String,
+    pub values: Vec<String>,
+}
+
+impl Axis {
+    /// Axis over `key` from a comma-separated list (`0.1,0.3,1`) or an
+    /// inclusive range with a step (`0.1:1:0.3`)
+    pub fn parse(key: &str, text: &str) -> Result<Self> {
+        let invalid = |message: String| BlockchainError::Config(format!("Invalid sweep of {} {:?}: {}", key, text, message));
+        if !SWEEP_KEYS.contains(&key) {
+            return Err(invalid(format!("only {} can be swept", SWEEP_KEYS.join(", "))));
+        }
+
+        let values = match text.split(':').collect::<Vec<_>>()[..] {
+            [start, stop, step] => {
+                let number = |part: &str| part.trim().parse::<f64>().map_err(|e| invalid(e.to_string()));
+                let (start, stop, step) = (number(start)?, number(stop)?, number(step)?);
+                if !start.is_finite() || !stop.is_finite() || !step.is_finite() || step <= 0.0 || start > stop {
+                    return Err(invalid("a range needs start <= stop and a positive step".to_string()));
+                }
+                let count = ((stop - start) / step + 1e-9).floor() as usize + 1;
+                if count > MAX_RANGE_POINTS {
+                    return Err(invalid(format!("the range has more than {} values", MAX_RANGE_POINTS)));
+                }
+                // Rounded so that 0.1 + 2 * 0.1 reads as 0.3
+                (0..count)
+                    .map(|index| format!("{}", ((start + index as f64 * step) * 1e12).round() / 1e12))
+                    .collect()
+            },
+            [list] => list.split(',').map(|value| value.trim().to_string()).collect::<Vec<_>>(),
+            _ => return Err(invalid("expected a list a,b,c or a range start:stop:step".to_string())),
+        };
+        if values.iter().any(|value| value.is_empty()) {
+            return Err(invalid("empty value".to_string()));
+        }
+
+        // Every value must be one the field accepts
+        let mut config = SimulationConfig::default();
+        for value in &values {
+            config.set(key, value)?;
+        }
+        Ok(Axis { key: key.to_string(), values })
+    }
+}
+
+/// What to sweep and how
+#[derive(Debug, Clone)]
+pub struct SweepOptions {
+    pub base: SimulationConfig,  // Fields no axis varies
+    pub axes: Vec<Axis>,
+    pub seeds: Vec<u64>,         // Every point is run once per seed
+    pub consensus: EngineKind,
+    pub jobs: usize,             // Runs executed at the same time
+    pub out_dir: PathBuf,        // One data directory per run, and the tables
+}
+
+impl Default for SweepOptions {
+    fn default() -> Self {
+        SweepOptions {
+            base: SimulationConfig::default(),
+            axes: Vec::new(),
+            seeds: vec![0],
+            consensus: EngineKind::default(),
+            jobs: std::thread::available_parallelism().map_or(1, |cores| cores.get()),
+            out_dir: PathBuf::from(DEFAULT_SWEEP_DIR),
+        }
+    }
+}
+
+impl SweepOptions {
+    /// Scenario of every point of the sweep, the last axis varying fastest
+    ///
+    /// Fails if any point is not a valid scenario.
+    pub fn points(&self) -> Result<Vec<SimulationConfig>> {
+        let mut points = vec![self.base.clone()];
+        for axis in &self.axes {
+            points = points.iter()
+                .flat_map(|point| axis.values.iter().map(move |value| {
+                    let mut point = point.clone();
+                    point.set(&axis.key, value).map(|_| point)
+                }))
+                .collect::<Result<_>>()?;
+        }
+        for point in &points {
+            point.validate().map_err(|e| BlockchainError::Config(format!("Sweep point {}: {}", describe(point, &self.axes), e)))?;
+        }
+        Ok(points)
+    }
+}
+
+/// The swept fields of `config`, e.g. `fee_percent=0.3 wallet_count=200`
+fn describe(config: &SimulationConfig, axes: &[Axis]) -> String {
+    axes.iter().map(|axis| format!("{}={}", axis.key, field(config, &axis.key))).collect::<Vec<_>>().join(" ")
+}
+
+/// The value of a sweepable field of `config`
+fn field(config: &SimulationConfig, key: &str) -> String {
+    match key {
+        "fee_percent" => config.fee_percent.to_string(),
+        "liquidity_fraction" => config.liquidity_fraction.to_string(),
+        "whale_probability" => config.whale_probability.to_string(),
+        "mega_whale_probability" => config.mega_whale_probability.to_string(),
+        "wallet_count" => config.wallet_count.to_string(),
+        _ => String::new(),
+    }
+}
+
+/// Figures of one sweep point, averaged over the runs that finished
+#[derive(Debug, Clone, PartialEq)]
+pub struct PointSummary {
+    pub config: SimulationConfig,
+    pub runs: usize,
+    pub failed: usize,
+    pub volatility_percent: f64,   // Per block
+    pub max_drawdown_percent: f64,
+    pub volume_usdz: f64,          // USDZ side of every swap
+    pub lp_return_percent: f64,    // Against holding the opening reserves
+    pub trader_pnl_usdz: f64,      // Summed over the trading wallets
+    pub profitable_ratio: f64,
+}
+
+impl PointSummary {
+    fn new(config: SimulationConfig, reports: &[&RunReport], failed: usize) -> Self {
+        let mean = |figure: fn(&RunReport) -> f64| {
+            if reports.is_empty() { f64::NAN } else { reports.iter().map(|report| figure(report)).sum::<f64>() / reports.len() as f64 }
+        };
+        PointSummary {
+            config,
+            runs: reports.len(),
+            failed,
+            volatility_percent: mean(|report| report.price.volatility_percent),
+            max_drawdown_percent: mean(|report| report.price.max_drawdown_percent),
+            volume_usdz: mean(|report| report.usdz_traded.to_f64()),
+            lp_return_percent: mean(|report| report.pool.lp_return_percent),
+            trader_pnl_usdz: mean(|report| report.wallets.total_pnl_usdz),
+            profitable_ratio: mean(|report| report.wallets.profitable_ratio),
+        }
+    }
+}
+
+/// Everything a sweep produced
+#[derive(Debug, Clone)]
+pub struct SweepResult {
+    pub axes: Vec<Axis>,
+    pub points: Vec<PointSummary>,
+    pub reports: Vec<RunReport>, // Of every run that finished, point by point and seed by seed
+}
+
+// Figures compared between points: header, width and value
+type Column = (&'static str, usize, fn(&PointSummary) -> String);
+const FIGURES: [Column; 7] = [
+    ("runs", 5, |point| point.runs.to_string()),
+    ("volatility %", 12, |point| format!("{:.3}", point.volatility_percent)),
+    ("drawdown %", 10, |point| format!("{:.2}", point.max_drawdown_percent)),
+    ("volume USDZ", 14, |point| format!("{:.2}", point.volume_usdz)),
+    ("LP return %", 11, |point| format!("{:+.3}", point.lp_return_percent)),
+    ("trader PnL USDZ", 15, |point| format!("{:+.2}", point.trader_pnl_usdz)),
+    ("profitable %", 12, |point| format!("{:.1}", point.profitable_ratio * 100.0)),
+];
+
+impl SweepResult {
+    /// Comparison table of the points, one row each, for a terminal
+    pub fn table(&self) -> String {
+        let widths: Vec<usize> = self.axes.iter().map(|axis| axis.key.len().max(10)).collect();
+        let mut table = String::new();
+        for (axis, width) in self.axes.iter().zip(&widths) {
+            let _ = write!(table, "{:<width$}  ", axis.key, width = *width);
+        }
+        let header: Vec<String> = FIGURES.iter().map(|(name, width, _)| format!("{:>width$}", name, width = *width)).collect();
+        let _ = writeln!(table, "{}", header.join("  "));
+        for point in &self.points {
+            for (axis, width) in self.axes.iter().zip(&widths) {
+                let _ = write!(table, "{:<width$}  ", field(&point.config, &axis.key), width = *width);
+            }
+            let row: Vec<String> = FIGURES.iter().map(|(_, width, value)| format!("{:>width$}", value(point), width = *width)).collect();
+            let _ = write!(table, "{}", row.join("  "));
+            if point.failed > 0 {
+                let _ = write!(table, "  ({} failed)", point.failed);
+            }
+            table.push('\n');
+        }
+        table
+    }
+
+    /// The comparison table as CSV, with every sweepable field
+    pub fn csv(&self) -> String {
+        let mut csv = format!("{},runs,failed,volatility_percent,max_drawdown_percent,volume_usdz,lp_return_percent,trader_pnl_usdz,profitable_ratio\n",
+                              SWEEP_KEYS.join(","));
+        for point in &self.points {
+            let fields: Vec<String> = SWEEP_KEYS.iter().map(|key| field(&point.config, key)).collect();
+            let _ = writeln!(csv, "{},{},{},{},{},{},{},{},{}", fields.join(","), point.runs, point.failed, point.volatility_percent,
+                             point.max_drawdown_percent, point.volume_usdz, point.lp_return_percent, point.trader_pnl_usdz, point.profitable_ratio);
+        }
+        csv
+    }
+
+    /// Every finished run as CSV, one row per run report
+    pub fn runs_csv(&self) -> String {
+        let mut csv = RunReport::csv_header();
+        csv.push('\n');
+        for report in &self.reports {
+            csv.push_str(&report.csv_row());
+            csv.push('\n');
+        }
+        csv
+    }
+}
+
+/// Run every point of the sweep once per seed, `options.jobs` at a time
+///
+/// Each run is headless and keeps its chain and report in a directory of its
+/// own under `options.out_dir`. A failed run is logged and left out of its
+/// point's averages; the sweep fails only if no run finishes.
+pub fn run_sweep(options: &SweepOptions) -> Result<SweepResult> {
+    let points = options.points()?;
+    if options.seeds.is_empty() {
+        return Err(BlockchainError::Config("A sweep needs at least one seed".to_string()));
+    }
+    std::fs::create_dir_all(&options.out_dir)?;
+
+    let runs: Vec<(usize, u64)> = (0..points.len())
+        .flat_map(|point| options.seeds.iter().map(move |&seed| (point, seed)))
+        .collect();
+    let jobs = options.jobs.clamp(1, runs.len());
+    info!("Sweeping {} points x {} seeds = {} runs on {} threads into {}",
+          points.len(), options.seeds.len(), runs.len(), jobs, options.out_dir.display());
+
+    // Workers take the next run until none are left
+    let next = AtomicUsize::new(0);
+    let finished = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<Result<RunReport>>>> = Mutex::new((0..runs.len()).map(|_| None).collect());
+    std::thread::scope(|scope| {
+        for _ in 0..jobs {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::SeqCst);
+                let Some(&(point, seed)) = runs.get(index) else {
+                    break;
+                };
+                let run_options = SimulationOptions {
+                    data_dir: options.out_dir.join(format!("point-{:03}-seed-{}", point, seed)),
+                    consensus: options.consensus,
+                    seed: Some(seed),
+                    config: points[point].clone(),
+                    headless: true,
+                    ..SimulationOptions::default()
+                };
+                let result = run_simulation(&run_options);
+                let done = finished.fetch_add(1, Ordering::SeqCst) + 1;
+                match &result {
+                    Ok(report) => info!("[{}/{}] {} seed {}: volatility {:.3}%, LP return {:+.3}%",
+                                        done, runs.len(), describe(&points[point], &options.axes), seed,
+                                        report.price.volatility_percent, report.pool.lp_return_percent),
+                    Err(e) => warn!("[{}/{}] {} seed {} failed: {}", done, runs.len(), describe(&points[point], &options.axes), seed, e),
+                }
+                results.lock().unwrap()[index] = Some(result);
+            });
+        }
+    });
+
+    // Group the reports by point, in run order
+    let results = results.into_inner().unwrap();
+    let mut reports = Vec::new();
+    let mut summaries = Vec::new();
+    for (point, config) in points.into_iter().enumerate() {
+        let point_results: Vec<&Result<RunReport>> = runs.iter().zip(&results)
+            .filter(|((run_point, _), _)| *run_point == point)
+            .filter_map(|(_, result)| result.as_ref())
+            .collect();
+        let finished: Vec<&RunReport> = point_results.iter().filter_map(|result| result.as_ref().ok()).collect();
+        summaries.push(PointSummary::new(config, &finished, point_results.len() - finished.len()));
+        reports.extend(finished.into_iter().cloned());
+    }
+    if reports.is_empty() {
+        return Err(BlockchainError::System("Every run of the sweep failed".to_string()));
+    }
+
+    let result = SweepResult { axes: options.axes.clone(), points: summaries, reports };
+    std::fs::write(options.out_dir.join("sweep.csv"), result.csv())?;
+    std::fs::write(options.out_dir.join("runs.csv"), result.runs_csv())?;
+    info!("Wrote sweep.csv and runs.csv to {}", options.out_dir.display());
+    Ok(result)
+}
@@ -0,0 +1,294 @@
+//! TCP transport for the peer-to-peer protocol
+//!
+//! A `TcpNode` runs a `Node` behind a listening socket. Every connection, made
+//! or accepted, gets a reader thread that hands incoming messages to the node
+//! and a writer thread that drains the connection's queue, so the node is
+//! never locked while a socket blocks. Between connections, the listener
+//! thread runs the node's upkeep. Each message is framed as a `u32`
+//! big-endian length followed by its canonical encoding.
+//!
+//! A mining thread, when started, seals blocks on top of the node's head and
+//! abandons the block it is working on as soon as the head moves.
+
+use std::collections::HashMap;
+use std::io::{ErrorKind, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use ed25519_dalek::SigningKey;
+use log::{debug, warn};
+
+use crate::clock::{Clock, SystemClock};
+use crate::codec;
+use crate::consensus::Sealer;
+use crate::error::{BlockchainError, Result};
+use crate::mempool::BlockLimits;
+use crate::miner::Miner;
+use crate::p2p::{Message, Node, Outbox, PeerId};
+use crate::transaction::Transaction;
+
+/// Largest frame accepted from a peer
+pub const MAX_FRAME_BYTES: usize = 16 * 1024 * 1024;
+
+// How long the accept loop and an idle miner wait before looking again
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Write `message` as one frame
+pub fn write_frame(stream: &mut impl Write, message: &Message) -> Result<()> {
+    let bytes = codec::to_bytes(message);
+    stream.write_all(&(bytes.len() as u32).to_be_bytes())?;
+    stream.write_all(&bytes)?;
+    stream.flush()?;
+    Ok(())
+}
+
+/// Read one frame, or `None` if the peer closed the connection between frames
+pub fn read_frame(stream: &mut impl Read) -> Result<Option<Message>> {
+    let mut len = [0u8; 4];
+    match stream.read_exact(&mut len) {
+        Ok(()) => {},
+        Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e.into()),
+    }
+    let len = u32::from_be_bytes(len) as usize;
+    if len > MAX_FRAME_BYTES {
+        return Err(BlockchainError::Network(format!("Frame of {} bytes exceeds the limit of {}", len, MAX_FRAME_BYTES)));
+    }
+    let mut bytes = vec![0u8; len];
+    stream.read_exact(&mut bytes)?;
+    codec::from_bytes(&bytes).map(Some)
+}
+
+/// An open connection
+struct Connection {
+    queue: Sender<Message>, // Messages for its writer thread
+    stream: TcpStream,      // Kept to shut the connection down
+}
+
+/// What the threads of a `TcpNode` share
+struct Shared {
+    node: Mutex<Node>,
+    connections: Mutex<HashMap<PeerId, Connection>>,
+    threads: Mutex<Vec<JoinHandle<()>>>,
+    next_peer: AtomicU64,
+    stop: AtomicBool,
+    stale: AtomicBool, // Raised when the head moves, abandoning the block being mined
+}
+
+impl Shared {
+    /// Run `f` on the node, then queue the messages it produced
+    fn with_node(&self, f: impl FnOnce(&mut Node) -> Result<Outbox>) -> Result<()> {
+        let outbox = {
+            let mut node = self.node.lock().unwrap();
+            let head = node.tree().head_hash().to_string();
+            let outbox = f(&mut node);
+            if node.tree().head_hash() != head {
+                self.stale.store(true, Ordering::Relaxed);
+            }
+            outbox?
+        };
+
+        let connections = self.connections.lock().unwrap();
+        for (peer, message) in outbox {
+            if let Some(connection) = connections.get(&peer) {
+                // A closed queue means the connection is going away
+                let _ = connection.queue.send(message);
+            }
+        }
+        Ok(())
+    }
+
+    fn spawn(&self, name: String, f: impl FnOnce() + Send + 'static) -> Result<()> {
+        let handle = thread::Builder::new().name(name).spawn(f)?;
+        self.threads.lock().unwrap().push(handle);
+        Ok(())
+    }
+
+    /// Start serving a new connection
+    fn attach(self: &Arc<Self>, stream: TcpStream) -> Result<()> {
+        stream.set_nodelay(true)?;
+        let peer = self.next_peer.fetch_add(1, Ordering::Relaxed);
+        let (queue, outgoing) = mpsc::channel::<Message>();
+        let mut writer = stream.try_clone()?;
+        let mut reader = stream.try_clone()?;
+        self.connections.lock().unwrap().insert(peer, Connection { queue, stream });
+
+        self.spawn(format!("peer-{}-writer", peer), move || {
+            for message in outgoing {
+                if let Err(e) = write_frame(&mut writer, &message) {
+                    debug!("Failed to send {} to peer {}: {}", message.name(), peer, e);
+                    break;
+                }
+            }
+            let _ = writer.shutdown(Shutdown::Both);
+        })?;
+
+        // The node knows the peer before its first message can arrive
+        self.with_node(|node| Ok(node.connect(peer, SystemClock.now_millis())))?;
+
+        let shared = Arc::clone(self);
+        self.spawn(format!("peer-{}-reader", peer), move || {
+            loop {
+                let message = match read_frame(&mut reader) {
+                    Ok(Some(message)) => message,
+                    Ok(None) => break,
+                    Err(e) => {
+                        if !shared.stop.load(Ordering::Relaxed) {
+                            debug!("Connection to peer {} failed: {}", peer, e);
+                        }
+                        break;
+                    },
+                };
+                if let Err(e) = shared.with_node(|node| node.handle(peer, message, SystemClock.now_millis())) {
+                    warn!("Disconnecting peer {}: {}", peer, e);
+                    break;
+                }
+            }
+            shared.detach(peer);
+        })
+    }
+
+    /// Forget a connection and close it
+    fn detach(&self, peer: PeerId) {
+        if let Some(connection) = self.connections.lock().unwrap().remove(&peer) {
+            let _ = connection.stream.shutdown(Shutdown::Both);
+        }
+        self.node.lock().unwrap().disconnect(peer);
+    }
+}
+
+/// A `Node` reachable over TCP
+pub struct TcpNode {
+    shared: Arc<Shared>,
+    local_addr: SocketAddr,
+}
+
+impl TcpNode {
+    /// Listen on `addr` and serve `node` to every peer that connects
+    ///
+    /// Port 0 picks a free port; see `local_addr`.
+    pub fn start(node: Node, addr: impl ToSocketAddrs) -> Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+        let local_addr = listener.local_addr()?;
+        let shared = Arc::new(Shared {
+            node: Mutex::new(node),
+            connections: Mutex::new(HashMap::new()),
+            threads: Mutex::new(Vec::new()),
+            next_peer: AtomicU64::new(0),
+            stop: AtomicBool::new(false),
+            stale: AtomicBool::new(false),
+        });
+
+        let accepting = Arc::clone(&shared);
+        shared.spawn(format!("listener-{}", local_addr), move || {
+            while !accepting.stop.load(Ordering::Relaxed) {
+                match listener.accept() {
+                    Ok((stream, addr)) => {
+                        let attached = stream.set_nonblocking(false).map_err(BlockchainError::from)
+                            .and_then(|()| accepting.attach(stream));
+                        if let Err(e) = attached {
+                            warn!("Failed to accept a connection from {}: {}", addr, e);
+                        }
+                    },
+                    Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                        let _ = accepting.with_node(|node| Ok(node.tick(SystemClock.now_millis())));
+                        thread::sleep(POLL_INTERVAL);
+                    },
+                    Err(e) => warn!("Failed to accept a connection: {}", e),
+                }
+            }
+        })?;
+
+        Ok(TcpNode { shared, local_addr })
+    }
+
+    /// Address peers connect to
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Open a connection to the node listening on `addr`
+    pub fn connect(&self, addr: impl ToSocketAddrs) -> Result<()> {
+        let stream = TcpStream::connect(addr)?;
+        self.shared.attach(stream)
+    }
+
+    /// Lock the node, to inspect it
+    pub fn node(&self) -> MutexGuard<'_, Node> {
+        self.shared.node.lock().unwrap()
+    }
+
+    /// Add a transaction to the node's mempool and announce it to its peers
+    pub fn submit_transaction(&self, transaction: Transaction) -> Result<()> {
+        self.shared.with_node(|node| node.submit_transaction(transaction, SystemClock.now_millis()))
+    }
+
+    /// Seal blocks on top of the node's head from its mempool, crediting `producer`
+    ///
+    /// `keys` sign blocks under a signed consensus engine; a block whose
+    /// producer has no key here is not sealed.
+    pub fn start_mining(&self, miner: Miner, producer: &str, keys: HashMap<String, SigningKey>, limits: BlockLimits) -> Result<()> {
+        let shared = Arc::clone(&self.shared);
+        let producer = producer.to_string();
+        self.shared.spawn(format!("miner-{}", self.local_addr), move || {
+            while !shared.stop.load(Ordering::Relaxed) {
+                // The stale flag is lowered with the node locked, so any later head change raises it
+                let template = {
+                    let mut node = shared.node.lock().unwrap();
+                    shared.stale.store(false, Ordering::Relaxed);
+                    node.block_template(&producer, &limits, SystemClock.now())
+                        .map(|block| block.map(|block| (block, node.state().consensus().clone())))
+                };
+                let (mut block, consensus) = match template {
+                    Ok(Some(template)) => template,
+                    Ok(None) => {
+                        thread::sleep(POLL_INTERVAL);
+                        continue;
+                    },
+                    Err(e) => {
+                        debug!("No block template: {}", e);
+                        thread::sleep(POLL_INTERVAL);
+                        continue;
+                    },
+                };
+
+                let sealer = Sealer { miner: &miner, cancel: &shared.stale, key: keys.get(&block.producer) };
+                match consensus.engine().seal(&mut block, &sealer) {
+                    Ok(Some(_)) => {
+                        let _ = shared.with_node(|node| Ok(node.submit_block(block, SystemClock.now_millis())));
+                    },
+                    Ok(None) => debug!("Abandoned block #{}: the head moved", block.id),
+                    Err(e) => {
+                        debug!("Failed to seal block #{}: {}", block.id, e);
+                        thread::sleep(POLL_INTERVAL);
+                    },
+                }
+            }
+        })
+    }
+
+    /// Stop mining, close every connection and wait for the node's threads
+    pub fn shutdown(self) {
+        self.shared.stop.store(true, Ordering::Relaxed);
+        self.shared.stale.store(true, Ordering::Relaxed);
+        for connection in self.shared.connections.lock().unwrap().values() {
+            let _ = connection.stream.shutdown(Shutdown::Both);
+        }
+
+        // Threads may still start others while they wind down
+        loop {
+            let threads: Vec<_> = self.shared.threads.lock().unwrap().drain(..).collect();
+            if threads.is_empty() {
+                break;
+            }
+            for thread in threads {
+                let _ = thread.join();
+            }
+        }
+    }
+}
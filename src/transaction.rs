@@ -0,0 +1,378 @@
+//! Signed transactions and their kinds
+
+use std::collections::HashMap;
+use std::fmt;
+
+use ed25519_dalek::{Signature, Signer, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::amm::BASIS_POINTS;
+use crate::amount::Amount;
+use crate::codec::{Decode, Decoder, Encode, Encoder};
+use crate::error::{BlockchainError, Result};
+use crate::wallet::Wallet;
+use crate::SUPPORTED_CURRENCIES;
+
+// Domain tag keeping transaction signatures apart from any other signed message
+const SIGNING_DOMAIN: &str = "ZUX transaction";
+
+/// What a transaction does; each kind has its own validation and execution rules
+///
+/// Transfers and swaps can be sent by any registered account. Minting and
+/// managing the pool's liquidity are reserved for the system wallet, which is
+/// the pool's only liquidity provider.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TxKind {
+    /// Move funds from the sender to another registered account
+    Transfer { recipient: String, currency: String, amount: Amount },
+    /// Trade against the AMM pool; rejected if it would pay out less than
+    /// `min_out` or lands in a block timestamped after `deadline`
+    Swap { is_zux_to_usd: bool, amount_in: Amount, min_out: Amount, deadline: u64 },
+    /// Deposit both currencies into the pool; the first deposit creates the pool
+    /// and sets its price, later ones must keep it
+    AddLiquidity { zux_amount: Amount, usd_amount: Amount },
+    /// Withdraw a fraction of both reserves from the pool, in basis points
+    RemoveLiquidity { share_bps: u32 },
+    /// Issue new tokens to a registered account
+    Mint { recipient: String, currency: String, amount: Amount },
+    /// Register the sender's address and bind it to the signing key
+    WalletRegister,
+}
+
+impl TxKind {
+    /// Checks that do not depend on the ledger
+    pub fn validate(&self) -> Result<()> {
+        let positive = |amount: Amount, what: &str| {
+            if !amount.is_zero() {
+                Ok(())
+            } else {
+                Err(BlockchainError::Transaction(format!("{} must be greater than zero", what)))
+            }
+        };
+        let supported = |currency: &str| {
+            if SUPPORTED_CURRENCIES.contains(&currency) {
+                Ok(())
+            } else {
+                Err(BlockchainError::Transaction(format!("Unsupported currency: {}", currency)))
+            }
+        };
+
+        match self {
+            TxKind::Transfer { currency, amount, .. } | TxKind::Mint { currency, amount, .. } => {
+                positive(*amount, "Transaction amount")?;
+                supported(currency)
+            },
+            TxKind::Swap { amount_in, .. } => positive(*amount_in, "Swap amount"),
+            TxKind::AddLiquidity { zux_amount, usd_amount } => {
+                positive(*zux_amount, "Liquidity amount")?;
+                positive(*usd_amount, "Liquidity amount")
+            },
+            TxKind::RemoveLiquidity { share_bps } => {
+                if *share_bps == 0 || *share_bps >= BASIS_POINTS {
+                    return Err(BlockchainError::Transaction(format!(
+                        "Liquidity share must be between 0 and {} basis points (exclusive), got {}", BASIS_POINTS, share_bps
+                    )));
+                }
+                Ok(())
+            },
+            TxKind::WalletRegister => Ok(()),
+        }
+    }
+
+    /// Short name of the kind, for display
+    pub fn name(&self) -> &'static str {
+        match self {
+            TxKind::Transfer { .. } => "Transfer",
+            TxKind::Swap { .. } => "Swap",
+            TxKind::AddLiquidity { .. } => "Add Liquidity",
+            TxKind::RemoveLiquidity { .. } => "Remove Liquidity",
+            TxKind::Mint { .. } => "Mint",
+            TxKind::WalletRegister => "Wallet Register",
+        }
+    }
+}
+
+impl fmt::Display for TxKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TxKind::Transfer { recipient, currency, amount } => write!(f, "Transfer {} {} to {}", amount, currency, recipient),
+            TxKind::Swap { is_zux_to_usd, amount_in, min_out, deadline } => {
+                let (input, output) = if *is_zux_to_usd { ("ZUX", "USDZ") } else { ("USDZ", "ZUX") };
+                write!(f, "Swap {} {} for at least {} {} (deadline {})", amount_in, input, min_out, output, deadline)
+            },
+            TxKind::AddLiquidity { zux_amount, usd_amount } => write!(f, "Add {} ZUX and {} USDZ to the pool", zux_amount, usd_amount),
+            TxKind::RemoveLiquidity { share_bps } => write!(f, "Remove {:.2}% of the pool's liquidity", *share_bps as f64 / 100.0),
+            TxKind::Mint { recipient, currency, amount } => write!(f, "Mint {} {} to {}", amount, currency, recipient),
+            TxKind::WalletRegister => write!(f, "Register wallet"),
+        }
+    }
+}
+
+impl Encode for TxKind {
+    fn encode(&self, out: &mut Encoder) {
+        match self {
+            TxKind::Transfer { recipient, currency, amount } => {
+                out.put_u8(0);
+                out.put_str(recipient);
+                out.put_str(currency);
+                amount.encode(out);
+            },
+            TxKind::Swap { is_zux_to_usd, amount_in, min_out, deadline } => {
+                out.put_u8(1);
+                out.put_bool(*is_zux_to_usd);
+                amount_in.encode(out);
+                min_out.encode(out);
+                out.put_u64(*deadline);
+            },
+            TxKind::AddLiquidity { zux_amount, usd_amount } => {
+                out.put_u8(2);
+                zux_amount.encode(out);
+                usd_amount.encode(out);
+            },
+            TxKind::RemoveLiquidity { share_bps } => {
+                out.put_u8(3);
+                out.put_u32(*share_bps);
+            },
+            TxKind::Mint { recipient, currency, amount } => {
+                out.put_u8(4);
+                out.put_str(recipient);
+                out.put_str(currency);
+                amount.encode(out);
+            },
+            TxKind::WalletRegister => out.put_u8(5),
+        }
+    }
+}
+
+impl Decode for TxKind {
+    fn decode(input: &mut Decoder) -> Result<Self> {
+        Ok(match input.u8()? {
+            0 => TxKind::Transfer { recipient: input.string()?, currency: input.string()?, amount: Amount::decode(input)? },
+            1 => TxKind::Swap {
+                is_zux_to_usd: input.bool()?,
+                amount_in: Amount::decode(input)?,
+                min_out: Amount::decode(input)?,
+                deadline: input.u64()?,
+            },
+            2 => TxKind::AddLiquidity { zux_amount: Amount::decode(input)?, usd_amount: Amount::decode(input)? },
+            3 => TxKind::RemoveLiquidity { share_bps: input.u32()? },
+            4 => TxKind::Mint { recipient: input.string()?, currency: input.string()?, amount: Amount::decode(input)? },
+            5 => TxKind::WalletRegister,
+            tag => return Err(BlockchainError::Encoding(format!("Unknown transaction kind tag {}", tag))),
+        })
+    }
+}
+
+/// Transaction structure to represent blockchain activity
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Transaction {
+    pub sender: String,
+    pub chain_id: String, // Network the transaction is valid on, e.g. `ZUX-Testnet`
+    pub nonce: u64,       // Sender's sequence number; the ledger accepts each one once, in order
+    pub fee: Amount,      // Network fee in ZUX offered for inclusion; block producers pack the highest fees first
+    pub kind: TxKind,
+    pub timestamp: u64,
+    pub signature: Vec<u8>, // Ed25519 cryptographic signature
+    pub sender_public_key: Vec<u8>, // Sender's public key for signature verification
+}
+
+impl Transaction {
+    /// Create a new transaction
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(sender: String, chain_id: String, nonce: u64, fee: Amount, kind: TxKind, timestamp: u64,
+               signature: Vec<u8>, sender_public_key: Vec<u8>) -> Self {
+        Transaction {
+            sender,
+            chain_id,
+            nonce,
+            fee,
+            kind,
+            timestamp,
+            signature,
+            sender_public_key,
+        }
+    }
+    
+    /// Create a transaction of the given kind from `sender_wallet` for `chain_id`
+    /// offering `fee`, using the wallet's next nonce, timestamped `timestamp` and signed
+    pub fn signed(sender_wallet: &Wallet, chain_id: &str, fee: Amount, kind: TxKind, timestamp: u64) -> Result<Self> {
+        let mut transaction = Transaction::new(
+            sender_wallet.address.clone(), chain_id.to_string(), sender_wallet.nonce, fee, kind, timestamp, Vec::new(), Vec::new()
+        );
+        transaction.sign(sender_wallet)?;
+        Ok(transaction)
+    }
+    
+    /// Get the transaction data that would be signed: the canonical encoding of every field but the signature
+    ///
+    /// This is the only signing payload: every kind is signed by
+    /// [`Transaction::sign`] and checked by [`Transaction::verify`] over the
+    /// same bytes, which include the chain id, the nonce, the fee, the kind and
+    /// all of its fields, so a signature is only valid once and on one network.
+    pub fn get_signing_data(&self) -> Vec<u8> {
+        let mut out = Encoder::new();
+        out.put_str(SIGNING_DOMAIN);
+        self.encode_unsigned(&mut out);
+        out.into_bytes()
+    }
+    
+    fn encode_unsigned(&self, out: &mut Encoder) {
+        out.put_str(&self.sender);
+        out.put_str(&self.chain_id);
+        out.put_u64(self.nonce);
+        self.fee.encode(out);
+        self.kind.encode(out);
+        out.put_u64(self.timestamp);
+        out.put_bytes(&self.sender_public_key);
+    }
+    
+    /// Sign the transaction with the sender's key, then check the result verifies
+    pub fn sign(&mut self, sender_wallet: &Wallet) -> Result<()> {
+        if sender_wallet.address != self.sender {
+            return Err(BlockchainError::Transaction(
+                format!("Wallet {} cannot sign a transaction sent by {}", sender_wallet.address, self.sender)
+            ));
+        }
+        
+        self.sender_public_key = sender_wallet.public_key.clone();
+        let signing_key = sender_wallet.get_signing_key()?;
+        self.signature = signing_key.sign(&self.get_signing_data()).to_bytes().to_vec();
+        self.verify()
+    }
+    
+    /// Verify that the transaction is valid, including cryptographic signature
+    pub fn verify(&self) -> Result<()> {
+        // Amounts, currencies and other checks of the kind
+        self.kind.validate()?;
+        
+        // Verify the cryptographic signature
+        let verifying_key = VerifyingKey::from_bytes(&self.sender_public_key.as_slice().try_into().map_err(|_| {
+            BlockchainError::Transaction("Invalid public key length".to_string())
+        })?).map_err(|e| BlockchainError::Transaction(format!("Invalid public key: {}", e)))?;
+            
+        // Convert signature bytes to a fixed-size array
+        let signature_bytes: [u8; 64] = self.signature.as_slice().try_into().map_err(|_| {
+            BlockchainError::Transaction("Invalid signature length".to_string())
+        })?;
+        
+        // Create a Signature from the bytes
+        let signature = Signature::from_bytes(&signature_bytes);
+            
+        let message = self.get_signing_data();
+        
+        verifying_key.verify(&message, &signature)
+            .map_err(|e| BlockchainError::Transaction(format!("Signature verification failed: {}", e)))?;
+        
+        Ok(())
+    }
+    
+    /// Amounts the sender must hold for this transaction to execute, fee included;
+    /// a currency can be listed more than once
+    pub fn required_funds(&self) -> Vec<(&str, Amount)> {
+        let mut required = vec![("ZUX", self.fee)];
+        match &self.kind {
+            TxKind::Transfer { currency, amount, .. } => required.push((currency.as_str(), *amount)),
+            TxKind::Swap { is_zux_to_usd, amount_in, .. } => {
+                required.push((if *is_zux_to_usd { "ZUX" } else { "USDZ" }, *amount_in));
+            },
+            TxKind::AddLiquidity { zux_amount, usd_amount } => {
+                required.push(("ZUX", *zux_amount));
+                required.push(("USDZ", *usd_amount));
+            },
+            TxKind::RemoveLiquidity { .. } | TxKind::Mint { .. } | TxKind::WalletRegister => {},
+        }
+        required
+    }
+    
+    /// Get a hash of the transaction data
+    pub fn hash(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.get_signing_data());
+        hex::encode(hasher.finalize())
+    }
+}
+
+impl Encode for Transaction {
+    fn encode(&self, out: &mut Encoder) {
+        self.encode_unsigned(out);
+        out.put_bytes(&self.signature);
+    }
+}
+
+impl Decode for Transaction {
+    fn decode(input: &mut Decoder) -> Result<Self> {
+        Ok(Transaction {
+            sender: input.string()?,
+            chain_id: input.string()?,
+            nonce: input.u64()?,
+            fee: Amount::decode(input)?,
+            kind: TxKind::decode(input)?,
+            timestamp: input.u64()?,
+            sender_public_key: input.bytes()?,
+            signature: input.bytes()?,
+        })
+    }
+}
+
+/// Create a transfer with proper validation and error handling using Ed25519 signatures
+/// Takes sender wallet reference instead of wallet info tuple
+#[allow(clippy::too_many_arguments)]
+pub fn create_transaction(
+    sender_wallet: &Wallet, 
+    chain_id: &str,
+    recipient_address: &str, 
+    amount: Amount, 
+    currency: &str,
+    fee: Amount,
+    _wallets: &HashMap<String, Wallet>, // Keeping parameter for compatibility but not using it
+    timestamp: u64
+) -> Result<Transaction> {
+    let kind = TxKind::Transfer { recipient: recipient_address.to_string(), currency: currency.to_string(), amount };
+    kind.validate()?;
+    
+    // Check if sender has sufficient balance directly from the sender_wallet
+    let sender_balance = sender_wallet.get_balance(currency);
+    if sender_balance < amount {
+        return Err(BlockchainError::Transaction(
+            format!("Insufficient balance: {} {} (needed: {})", sender_balance, currency, amount)
+        ));
+    }
+    
+    Transaction::signed(sender_wallet, chain_id, fee, kind, timestamp)
+}
+
+/// Create a swap transaction between a wallet and the AMM pool, timestamped `timestamp`
+///
+/// The output amount is determined by the ledger when the block containing the
+/// swap is applied; the swap fails instead of paying out less than `min_out`.
+#[allow(clippy::too_many_arguments)]
+pub fn create_swap_transaction(
+    wallet: &Wallet,
+    chain_id: &str,
+    is_zux_to_usd: bool,
+    input_amount: Amount,
+    min_out: Amount,
+    deadline: u64,
+    fee: Amount,
+    timestamp: u64
+) -> Result<Transaction> {
+    let kind = TxKind::Swap { is_zux_to_usd, amount_in: input_amount, min_out, deadline };
+    kind.validate()?;
+    
+    // Check if wallet has sufficient balance
+    let input_currency = if is_zux_to_usd { "ZUX" } else { "USDZ" };
+    let wallet_balance = wallet.get_balance(input_currency);
+    if wallet_balance < input_amount {
+        return Err(BlockchainError::Transaction(
+            format!("Insufficient balance: {} {} (needed: {})", wallet_balance, input_currency, input_amount)
+        ));
+    }
+    
+    Transaction::signed(wallet, chain_id, fee, kind, timestamp)
+}
+
+/// Create the transaction registering a new wallet on chain; a new wallet has nothing to pay a fee with
+pub fn create_wallet_registration(wallet: &Wallet, chain_id: &str, timestamp: u64) -> Result<Transaction> {
+    Transaction::signed(wallet, chain_id, Amount::ZERO, TxKind::WalletRegister, timestamp)
+}
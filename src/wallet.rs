@@ -0,0 +1,208 @@
+//! Wallets holding Ed25519 keys and multi-currency balances
+
+use std::collections::HashMap;
+
+use base64::encode;
+use ed25519_dalek::{SigningKey, VerifyingKey};
+use log::warn;
+use rand::{CryptoRng, Rng, RngCore};
+use serde::{Deserialize, Serialize};
+
+use crate::address::UniqueCodeGenerator;
+use crate::agent::{AgentMix, TradingStrategy};
+use crate::amount::Amount;
+use crate::error::{BlockchainError, Result};
+use crate::{SUPPORTED_CURRENCIES, SYSTEM_WALLET_ADDRESS};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Wallet {
+    private_key: Vec<u8>,      // Ed25519 private key bytes
+    pub public_key: Vec<u8>,       // Ed25519 public key bytes
+    pub address: String,           // Unique wallet address
+    pub balances: HashMap<String, Amount>, // Map of currency code to balance
+    pub nonce: u64,                // Next transaction sequence number, as seen by the ledger
+    pub trading_strategy: Option<TradingStrategy>, // Optional trading strategy
+}
+
+impl Wallet {
+    /// Create a new wallet with empty balances
+    pub fn new(private_key: Vec<u8>, public_key: Vec<u8>, address: String) -> Self {
+        let mut balances = HashMap::new();
+        // Initialize with zero balance for all supported currencies
+        for currency in SUPPORTED_CURRENCIES.iter() {
+            balances.insert((*currency).to_string(), Amount::ZERO);
+        }
+        
+        Wallet {
+            private_key,
+            public_key,
+            address,
+            balances,
+            nonce: 0,
+            trading_strategy: None,
+        }
+    }
+    
+    /// Initialize trading strategy for this wallet, drawing its traits from `rng`
+    pub fn initialize_trading_strategy(&mut self, initial_price: f64, rng: &mut impl Rng) {
+        self.trading_strategy = Some(TradingStrategy::new(initial_price, rng));
+    }
+    
+    /// Initialize the trading strategy with the whale odds of `mix`
+    pub fn initialize_trading_strategy_with(&mut self, initial_price: f64, mix: &AgentMix, rng: &mut impl Rng) {
+        self.trading_strategy = Some(TradingStrategy::with_mix(initial_price, mix, rng));
+    }
+    
+    /// Get the private key as a base64 string for display purposes
+    pub fn private_key_base64(&self) -> String {
+        encode(&self.private_key)
+    }
+    
+    /// Get the public key as a base64 string for display purposes
+    pub fn public_key_base64(&self) -> String {
+        encode(&self.public_key)
+    }
+    
+    /// Get the Ed25519 signing key for signing operations
+    pub fn get_signing_key(&self) -> Result<SigningKey> {
+        // Convert private key bytes to a fixed-size array
+        let private_key_bytes: [u8; 32] = self.private_key.as_slice().try_into().map_err(|_| {
+            BlockchainError::Wallet("Invalid private key length".to_string())
+        })?;
+        
+        // Create a SigningKey from the bytes
+        let signing_key = SigningKey::from_bytes(&private_key_bytes);
+        
+        Ok(signing_key)
+    }
+    
+    /// Get the Ed25519 verifying key for verification operations
+    pub fn get_verifying_key(&self) -> Result<VerifyingKey> {
+        // Convert public key bytes to a fixed-size array
+        let public_key_bytes: [u8; 32] = self.public_key.as_slice().try_into().map_err(|_| {
+            BlockchainError::Wallet("Invalid public key length".to_string())
+        })?;
+        
+        // Create a VerifyingKey from the bytes
+        let verifying_key = VerifyingKey::from_bytes(&public_key_bytes).map_err(|e| {
+            BlockchainError::Wallet(format!("Invalid public key: {}", e))
+        })?;
+        
+        Ok(verifying_key)
+    }
+    
+    /// Get the balance for a specific currency
+    pub fn get_balance(&self, currency: &str) -> Amount {
+        self.balances.get(currency).copied().unwrap_or_default()
+    }
+    
+    /// Set the balance for a specific currency
+    pub fn set_balance(&mut self, currency: &str, amount: Amount) {
+        self.balances.insert(currency.to_string(), amount);
+    }
+    
+    /// Add to the balance for a specific currency
+    pub fn add_balance(&mut self, currency: &str, amount: Amount) -> Result<()> {
+        let new_balance = self.get_balance(currency).checked_add(amount)?;
+        self.set_balance(currency, new_balance);
+        Ok(())
+    }
+    
+    /// Subtract from the balance for a specific currency
+    pub fn subtract_balance(&mut self, currency: &str, amount: Amount) -> Result<()> {
+        let current = self.get_balance(currency);
+        if current < amount {
+            return Err(BlockchainError::Wallet(
+                format!("Insufficient balance for wallet {}: has {} {}, needs {} {}", 
+                       self.address, current, currency, amount, currency)
+            ));
+        }
+        self.set_balance(currency, current.checked_sub(amount)?);
+        Ok(())
+    }
+}
+
+/// Function to create a new wallet with initial balances using Ed25519 cryptography
+///
+/// The keypair is drawn from `rng`; a seeded generator gives the same keys every time.
+pub fn create_wallet(code_generator: &mut UniqueCodeGenerator, initial_balance: Amount,
+                     rng: &mut (impl RngCore + CryptoRng)) -> Result<Wallet> {
+    // Generate an Ed25519 keypair
+    let signing_key = SigningKey::generate(rng);
+    let verifying_key = signing_key.verifying_key();
+    
+    // Extract the private and public keys
+    let private_key = signing_key.to_bytes().to_vec();
+    let public_key = verifying_key.to_bytes().to_vec();
+    
+    // Generate a guaranteed unique address using our code generator
+    let address = code_generator.generate()?;
+    
+    // Create a new wallet with empty balances
+    let mut wallet = Wallet::new(private_key, public_key, address);
+    
+    // Set initial balances if specified
+    if !initial_balance.is_zero() {
+        for currency in SUPPORTED_CURRENCIES.iter() {
+            wallet.set_balance(currency, initial_balance);
+        }
+    }
+    
+    Ok(wallet)
+}
+
+/// Function to create a wallet and return it without setting balances
+pub fn create_wallet_without_balance(code_generator: &mut UniqueCodeGenerator, rng: &mut (impl RngCore + CryptoRng)) -> Result<Wallet> {
+    create_wallet(code_generator, Amount::ZERO, rng)
+}
+
+/// Function to create a system wallet with special address and high initial balance
+pub fn create_system_wallet(code_generator: &mut UniqueCodeGenerator, rng: &mut (impl RngCore + CryptoRng)) -> Result<Wallet> {
+    // Generate an Ed25519 keypair
+    let signing_key = SigningKey::generate(rng);
+    let verifying_key = signing_key.verifying_key();
+    
+    // Log a warning about system wallet creation
+    warn!("Creating system wallet with high initial balance");
+    
+    // Extract the private and public keys
+    let private_key = signing_key.to_bytes().to_vec();
+    let public_key = verifying_key.to_bytes().to_vec();
+    
+    // Reserve the system wallet address to prevent it from being generated for other wallets
+    code_generator.reserve_code(SYSTEM_WALLET_ADDRESS);
+    
+    // Create a new wallet with the system address
+    let mut wallet = Wallet::new(private_key, public_key, SYSTEM_WALLET_ADDRESS.to_string());
+    
+    // Set initial balances for the system wallet: 1B ZUX and 5B USDZ
+    wallet.set_balance("ZUX", Amount::from_tokens(1_000_000_000)); // 1 billion ZUX
+    wallet.set_balance("USDZ", Amount::from_tokens(5_000_000_000)); // 5 billion USDZ
+    
+    // Log the creation of the system wallet with a warning about its special status
+    warn!("Created system wallet with address '{}'. This wallet has special privileges and high initial balance.", SYSTEM_WALLET_ADDRESS);
+    
+    Ok(wallet)
+}
+
+/// Create the wallet of block authority number `index`, which seals blocks under a signed consensus engine
+///
+/// Authority addresses are longer than generated ones, so they never collide.
+pub fn create_authority_wallet(index: usize, rng: &mut (impl RngCore + CryptoRng)) -> Wallet {
+    let signing_key = SigningKey::generate(rng);
+    let verifying_key = signing_key.verifying_key();
+
+    Wallet::new(signing_key.to_bytes().to_vec(), verifying_key.to_bytes().to_vec(), format!("AUTHORITY{}", index))
+}
+
+// Function to display wallet information
+pub fn display_wallet(wallet: &Wallet) {
+    println!("\n________________________ZUX Wallet_________________________________________");
+    println!("Address         : {}", wallet.address);
+    println!("Public Key      : {}", wallet.public_key_base64());
+    println!("Private Key     : {}", wallet.private_key_base64());
+    println!("Balances:");
+    println!("  - ZUX         : {}", wallet.get_balance("ZUX"));
+    println!("  - USDZ        : {}", wallet.get_balance("USDZ"));
+    println!("____________________________________________________________________________\n");
+}
@@ -0,0 +1,55 @@
+//! Time-based metrics driven by a simulated clock instead of the wall clock
+
+use zux::market::VolumeTracker;
+use zux::{AmmPool, Amount, Clock, SimulatedClock};
+
+const START_MS: u64 = 1_700_000_000_000;
+
+#[test]
+fn a_simulated_clock_only_moves_when_ticked_or_advanced() {
+    let clock = SimulatedClock::new(START_MS, 2_500);
+    assert_eq!(clock.now(), 1_700_000_000);
+    clock.tick();
+    clock.tick();
+    assert_eq!(clock.now_millis(), START_MS + 5_000);
+    clock.advance(500);
+    clock.advance_to(START_MS);
+    assert_eq!(clock.now_millis(), START_MS + 5_500);
+    clock.advance_to(START_MS + 60_000);
+    assert_eq!(clock.now(), 1_700_000_060);
+}
+
+#[test]
+fn pool_windows_follow_the_clock_they_are_given() {
+    let clock = SimulatedClock::new(START_MS, 2_000);
+    let mut pool = AmmPool::new(Amount::from_tokens(100_000), Amount::from_tokens(1_000), 30, clock.now());
+
+    // Two swaps in the same five-second window add up, the third opens a new one
+    pool.swap_zux_to_usd(Amount::from_tokens(100), clock.now()).unwrap();
+    clock.tick();
+    pool.swap_usd_to_zux(Amount::from_tokens(1), clock.now()).unwrap();
+    let window = pool.recent_volume_usd;
+    assert!(pool.total_volume_usd > 0.0);
+    assert_eq!(window, pool.total_volume_usd);
+    assert_eq!(pool.price_history.iter().map(|point| point.timestamp).collect::<Vec<_>>(),
+               [1_700_000_000, 1_700_000_000, 1_700_000_002]);
+
+    clock.tick();
+    clock.tick();
+    pool.swap_zux_to_usd(Amount::from_tokens(100), clock.now()).unwrap();
+    assert_eq!(pool.last_volume_reset, 1_700_000_006);
+    assert!(pool.recent_volume_usd < pool.total_volume_usd);
+    assert_eq!(pool.recent_volume_usd + window, pool.total_volume_usd);
+}
+
+#[test]
+fn volume_windows_are_measured_at_the_given_time() {
+    let mut tracker = VolumeTracker::new();
+    tracker.update(1_000, 5.0);
+    tracker.update(1_008, 3.0);
+    tracker.update(1_058, 2.0);
+    assert_eq!(tracker.get_volume_5s(1_060), 2.0);
+    assert_eq!(tracker.get_volume_1m(1_060), 10.0);
+    assert_eq!(tracker.get_volume_1m(1_062), 5.0);
+    assert_eq!(tracker.get_volume_1h(4_600), 10.0);
+}
@@ -0,0 +1,226 @@
+//! Round trips and edge cases of the canonical binary encoding
+
+use std::collections::HashMap;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use zux::block::{Block, BlockEvent};
+use zux::codec::{self, ENCODING_VERSION};
+use zux::p2p::{Message, PROTOCOL_VERSION};
+use zux::tcp::{read_frame, write_frame};
+use zux::transaction::{create_swap_transaction, create_transaction, TxKind};
+use zux::wallet::create_wallet;
+use zux::{Amount, BlockchainError, ChainStore, FileStore, Target, Transaction, UniqueCodeGenerator, Wallet};
+
+fn tokens(text: &str) -> Amount {
+    text.parse().unwrap()
+}
+
+fn funded_wallets() -> (Wallet, Wallet) {
+    let mut rng = StdRng::seed_from_u64(1);
+    let mut code_generator = UniqueCodeGenerator::new(&mut rng).unwrap();
+    let mut sender = create_wallet(&mut code_generator, Amount::ZERO, &mut rng).unwrap();
+    sender.set_balance("ZUX", Amount::from_tokens(1_000));
+    sender.set_balance("USDZ", Amount::from_tokens(1_000));
+    let recipient = create_wallet(&mut code_generator, Amount::ZERO, &mut rng).unwrap();
+    (sender, recipient)
+}
+
+fn sample_block() -> Block {
+    let (sender, recipient) = funded_wallets();
+    let transfer = create_transaction(&sender, "ZUX-Testnet", &recipient.address, tokens("12.345678901"), "ZUX", Amount::ZERO, &HashMap::new(), 1_700_000_000).unwrap();
+    let swap = create_swap_transaction(&sender, "ZUX-Testnet", true, tokens("0.1"), tokens("0.45"), 1_700_000_060, tokens("0.001"), 1_700_000_000).unwrap();
+    let events = [
+        BlockEvent::TokenCredit(recipient.address.clone(), "ZUX".to_string(), tokens("12.345678901")),
+        BlockEvent::Swap(sender.address.clone(), true, tokens("0.1"), tokens("0.49")),
+    ];
+    let mut block = Block::new(
+        1, &"0".repeat(64), &[transfer, swap], "ZUX-Testnet", "1.0.0.0.0", 2025, &recipient.address, &events, &"ab".repeat(32),
+        Target::from_leading_zero_bits(8), 1_700_000_000
+    ).unwrap();
+    block.mine().unwrap();
+    block
+}
+
+#[test]
+fn transaction_round_trips() {
+    let (sender, recipient) = funded_wallets();
+    let transaction = create_transaction(&sender, "ZUX-Testnet", &recipient.address, tokens("0.1").checked_add(tokens("0.2")).unwrap(), "USDZ", Amount::ZERO, &HashMap::new(), 1_700_000_000).unwrap();
+
+    let bytes = codec::to_bytes(&transaction);
+    assert_eq!(bytes[0], ENCODING_VERSION);
+    let decoded: Transaction = codec::from_bytes(&bytes).unwrap();
+    assert_eq!(decoded, transaction);
+    match decoded.kind {
+        TxKind::Transfer { amount, .. } => assert_eq!(amount, tokens("0.3")),
+        other => panic!("decoded the wrong kind: {}", other),
+    }
+    decoded.verify().unwrap();
+}
+
+#[test]
+fn block_event_round_trips() {
+    let events = [
+        BlockEvent::Genesis,
+        BlockEvent::WalletCreation("abc".to_string()),
+        BlockEvent::TokenCredit("abc".to_string(), "ZUX".to_string(), Amount::from_tokens(100)),
+        BlockEvent::Swap("abc".to_string(), false, tokens("5.5"), Amount::BASE_UNIT),
+        BlockEvent::LiquidityAdded("AMM_POOL_ZUX_USDZ".to_string(), Amount::from_tokens(1_000), Amount::from_tokens(5_000)),
+        BlockEvent::LiquidityRemoved("AMM_POOL_ZUX_USDZ".to_string(), Amount::from_tokens(10), Amount::from_tokens(50)),
+    ];
+    for event in events {
+        assert_eq!(codec::from_bytes::<BlockEvent>(&codec::to_bytes(&event)).unwrap(), event);
+    }
+}
+
+#[test]
+fn transaction_kinds_round_trip() {
+    let kinds = [
+        TxKind::Transfer { recipient: "abc".to_string(), currency: "ZUX".to_string(), amount: tokens("1.5") },
+        TxKind::Swap { is_zux_to_usd: false, amount_in: Amount::from_tokens(2), min_out: tokens("9.5"), deadline: u64::MAX },
+        TxKind::AddLiquidity { zux_amount: Amount::from_tokens(1_000), usd_amount: Amount::from_tokens(5_000) },
+        TxKind::RemoveLiquidity { share_bps: 2_500 },
+        TxKind::Mint { recipient: "abc".to_string(), currency: "USDZ".to_string(), amount: Amount::from_tokens(100) },
+        TxKind::WalletRegister,
+    ];
+    for kind in kinds {
+        assert_eq!(codec::from_bytes::<TxKind>(&codec::to_bytes(&kind)).unwrap(), kind);
+    }
+}
+
+#[test]
+fn amounts_keep_every_decimal() {
+    let amount = tokens("12.345678901");
+    assert_eq!(amount.base_units(), 12_345_678_901);
+    assert_eq!(amount.to_string(), "12.345678901");
+    assert_eq!(format!("{:.2}", amount), "12.34");
+    assert_eq!(serde_json::to_string(&amount).unwrap(), "\"12.345678901\"");
+    assert_eq!(serde_json::from_str::<Amount>("\"12.345678901\"").unwrap(), amount);
+    assert_eq!(codec::from_bytes::<Amount>(&codec::to_bytes(&amount)).unwrap(), amount);
+
+    for invalid in ["", ".5", "1.0000000001", "-1", "1e3", "1.2.3"] {
+        assert!(invalid.parse::<Amount>().is_err(), "{:?} parsed", invalid);
+    }
+    assert!(Amount::ZERO.checked_sub(Amount::BASE_UNIT).is_err());
+}
+
+#[test]
+fn block_round_trips_and_still_verifies() {
+    let block = sample_block();
+    let decoded: Block = codec::from_bytes(&codec::to_bytes(&block)).unwrap();
+    assert_eq!(decoded, block);
+    decoded.verify().unwrap();
+    assert_eq!(Block::calculate_merkle_root(&decoded.transactions, &decoded.events), block.tx_root);
+}
+
+#[test]
+fn network_messages_round_trip_through_frames() {
+    let block = sample_block();
+    let messages = [
+        Message::Hello {
+            version: PROTOCOL_VERSION, node_id: "node0".to_string(), chain_id: "ZUX-Testnet".to_string(),
+            base_root: "ab".repeat(32), height: 7, head_hash: block.hash.clone(),
+        },
+        Message::Inventory { blocks: vec![block.hash.clone()], transactions: vec!["cd".repeat(32)] },
+        Message::GetBlocks { locator: vec![block.hash.clone(), "0".repeat(64)] },
+        Message::GetData { blocks: Vec::new(), transactions: vec!["cd".repeat(32)] },
+        Message::Transaction(Box::new(block.transactions[0].clone())),
+        Message::Block(Box::new(block)),
+    ];
+
+    let mut stream = Vec::new();
+    for message in &messages {
+        write_frame(&mut stream, message).unwrap();
+    }
+    let mut reader = stream.as_slice();
+    for message in &messages {
+        assert_eq!(read_frame(&mut reader).unwrap().as_ref(), Some(message));
+    }
+    assert!(read_frame(&mut reader).unwrap().is_none());
+
+    // A frame cut short is an error, not a clean close
+    let mut frame = Vec::new();
+    write_frame(&mut frame, &messages[2]).unwrap();
+    let mut reader = &frame[..frame.len() - 1];
+    assert!(read_frame(&mut reader).is_err());
+}
+
+#[test]
+fn adjacent_fields_cannot_collide() {
+    let transaction = |sender: &str, recipient: &str| Transaction::new(
+        sender.to_string(),
+        "ZUX-Testnet".to_string(),
+        0,
+        Amount::ZERO,
+        TxKind::Transfer { recipient: recipient.to_string(), currency: "ZUX".to_string(), amount: Amount::from_tokens(1) },
+        0, Vec::new(), Vec::new()
+    );
+    assert_ne!(transaction("ab", "c").get_signing_data(), transaction("a", "bc").get_signing_data());
+    assert_ne!(transaction("ab", "c").hash(), transaction("a", "bc").hash());
+
+    // Otherwise identical transactions differ by nonce, by fee and by chain
+    let mut next = transaction("ab", "c");
+    next.nonce += 1;
+    assert_ne!(next.hash(), transaction("ab", "c").hash());
+    let mut higher_fee = transaction("ab", "c");
+    higher_fee.fee = Amount::BASE_UNIT;
+    assert_ne!(higher_fee.hash(), transaction("ab", "c").hash());
+    let mut mainnet = transaction("ab", "c");
+    mainnet.chain_id = "ZUX-Mainnet".to_string();
+    assert_ne!(mainnet.hash(), transaction("ab", "c").hash());
+}
+
+#[test]
+fn header_changes_change_the_hash() {
+    let block = sample_block();
+    let mut tampered = block.clone();
+    tampered.network_name = "ZUX-Testne".to_string();
+    tampered.block_class = format!("t{}", tampered.block_class);
+    assert_ne!(tampered.calculate_hash(), block.hash);
+    assert!(tampered.verify().is_err());
+
+    // The producer is paid by the ledger, so it is committed to as well
+    let mut tampered = block.clone();
+    tampered.producer = format!("{}x", tampered.producer);
+    assert_ne!(tampered.calculate_hash(), block.hash);
+
+    let mut tampered = block.clone();
+    tampered.target = Target::from_leading_zero_bits(64);
+    assert!(tampered.verify().is_err());
+
+    let mut tampered = block.clone();
+    tampered.extra_nonce += 1;
+    assert_ne!(tampered.calculate_hash(), block.hash);
+}
+
+#[test]
+fn malformed_encodings_are_rejected() {
+    let bytes = codec::to_bytes(&sample_block());
+
+    let mut other_version = bytes.clone();
+    other_version[0] = ENCODING_VERSION + 1;
+    assert!(matches!(codec::from_bytes::<Block>(&other_version), Err(BlockchainError::Encoding(_))));
+
+    let mut trailing = bytes.clone();
+    trailing.push(0);
+    assert!(matches!(codec::from_bytes::<Block>(&trailing), Err(BlockchainError::Encoding(_))));
+
+    for len in [0, 1, bytes.len() / 2, bytes.len() - 1] {
+        assert!(matches!(codec::from_bytes::<Block>(&bytes[..len]), Err(BlockchainError::Encoding(_))));
+    }
+}
+
+#[test]
+fn file_store_round_trips_blocks() {
+    let dir = std::env::temp_dir().join(format!("zux-codec-test-{}", std::process::id()));
+    let block = sample_block();
+    {
+        let mut store = FileStore::create(&dir).unwrap();
+        store.append(&block).unwrap();
+    }
+
+    let store = FileStore::open_read_only(&dir).unwrap();
+    assert_eq!(store.height(), 1);
+    assert_eq!(store.block_at(1).unwrap(), Some(block));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,91 @@
+//! Fixtures shared by the integration tests: ledgers from genesis, mined
+//! blocks and registrations of fresh wallets
+
+// Every test file uses its own share of the fixtures
+#![allow(dead_code)]
+
+use zux::block::Block;
+use zux::simulation::genesis_state;
+use zux::transaction::create_wallet_registration;
+use zux::wallet::{create_system_wallet, create_wallet_without_balance};
+use zux::{Amount, ConsensusRule, State, Transaction, TxKind, UniqueCodeGenerator, Wallet, SYSTEM_WALLET_ADDRESS};
+
+pub const START: u64 = 1_700_000_000;
+
+/// Mine the block executing `transactions` on top of `state`, and apply it there
+pub fn extend(state: &mut State, transactions: &[Transaction], timestamp: u64) -> Block {
+    let diff = state.preview(transactions, timestamp, SYSTEM_WALLET_ADDRESS).unwrap();
+    let mut block = Block::new(
+        state.height() + 1, state.tip_hash(), transactions, state.chain_id(), "1.0.0.0.0", 2025, SYSTEM_WALLET_ADDRESS,
+        &diff.receipts, &diff.state_root, state.next_target(), timestamp
+    ).unwrap();
+    block.mine().unwrap();
+    state.apply_block(&block).unwrap();
+    block
+}
+
+/// Genesis ledger binding the system account to a fresh system wallet, that wallet,
+/// and the generator its address is reserved in
+pub fn genesis() -> (State, Wallet, UniqueCodeGenerator) {
+    let mut rng = rand::thread_rng();
+    let mut code_generator = UniqueCodeGenerator::new(&mut rng).unwrap();
+    let system = create_system_wallet(&mut code_generator, &mut rng).unwrap();
+    let state = genesis_state(ConsensusRule::default(), &system.public_key);
+    (state, system, code_generator)
+}
+
+/// Ledger with the genesis block and the system wallet registered, the system
+/// wallet at its next nonce, and a generator for fresh wallets
+pub fn started_chain() -> (State, Wallet, UniqueCodeGenerator) {
+    let (mut state, mut system, code_generator) = genesis();
+    extend(&mut state, &[], START);
+    let registration = create_wallet_registration(&system, state.chain_id(), START + 1).unwrap();
+    extend(&mut state, &[registration], START + 1);
+    system.nonce += 1;
+    (state, system, code_generator)
+}
+
+/// Ledger with the system wallet and `count` users holding `tokens` ZUX each,
+/// the wallets at their next nonce, and the block that funded the users
+pub fn funded_chain(count: usize, tokens: u64) -> (State, Wallet, Vec<Wallet>, Block) {
+    let (mut state, mut system, mut code_generator) = genesis();
+    extend(&mut state, &[], START);
+
+    let mut users: Vec<Wallet> = (0..count)
+        .map(|_| create_wallet_without_balance(&mut code_generator, &mut rand::thread_rng()).unwrap())
+        .collect();
+    let registrations: Vec<Transaction> = std::iter::once(&system).chain(&users)
+        .map(|wallet| create_wallet_registration(wallet, state.chain_id(), START + 1).unwrap())
+        .collect();
+    extend(&mut state, &registrations, START + 1);
+    system.nonce += 1;
+
+    let funding: Vec<Transaction> = users.iter_mut()
+        .map(|user| {
+            user.nonce += 1;
+            let kind = TxKind::Transfer { recipient: user.address.clone(), currency: "ZUX".to_string(), amount: Amount::from_tokens(tokens) };
+            let transaction = Transaction::signed(&system, state.chain_id(), Amount::ZERO, kind, START + 2).unwrap();
+            system.nonce += 1;
+            transaction
+        })
+        .collect();
+    let block = extend(&mut state, &funding, START + 2);
+    (state, system, users, block)
+}
+
+/// Registration of a fresh wallet, signed at `timestamp`
+pub fn registration(state: &State, code_generator: &mut UniqueCodeGenerator, timestamp: u64) -> Transaction {
+    let wallet = create_wallet_without_balance(code_generator, &mut rand::thread_rng()).unwrap();
+    create_wallet_registration(&wallet, state.chain_id(), timestamp).unwrap()
+}
+
+/// Extend `state` by `count` blocks, each registering a fresh wallet
+pub fn branch(state: &mut State, code_generator: &mut UniqueCodeGenerator, count: usize) -> Vec<Block> {
+    (0..count)
+        .map(|_| {
+            let timestamp = START + state.height();
+            let registration = registration(state, code_generator, timestamp);
+            extend(state, &[registration], timestamp)
+        })
+        .collect()
+}
@@ -0,0 +1,67 @@
+//! Scenario files, overrides and validation
+
+use std::path::PathBuf;
+
+use zux::{Amount, SimulationConfig};
+
+/// Write `contents` to a fresh scenario file named `name`
+fn scenario_file(name: &str, contents: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("zux-config-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    std::fs::write(&path, contents).unwrap();
+    path
+}
+
+#[test]
+fn scenario_files_fill_in_the_fields_they_leave_out() {
+    let toml = scenario_file("small.toml", "wallet_count = 50\nfee_percent = 0.05\nzux_credit = \"12.5\"\n");
+    let config = SimulationConfig::read_from(&toml).unwrap();
+    assert_eq!(config.wallet_count, 50);
+    assert_eq!(config.fee_bps(), 5);
+    assert_eq!(config.zux_credit, "12.5".parse::<Amount>().unwrap());
+    assert_eq!(config.total_transactions, SimulationConfig::default().total_transactions);
+    config.validate().unwrap();
+
+    let json = scenario_file("small.json", r#"{"wallet_count": 50, "fee_percent": 0.05, "zux_credit": "12.5"}"#);
+    assert_eq!(SimulationConfig::read_from(&json).unwrap(), config);
+
+    // A printed scenario reads back as the same one
+    let printed = scenario_file("printed.toml", &config.to_toml().unwrap());
+    assert_eq!(SimulationConfig::read_from(&printed).unwrap(), config);
+
+    let misspelled = scenario_file("misspelled.toml", "wallets = 50\n");
+    assert!(SimulationConfig::read_from(&misspelled).is_err());
+}
+
+#[test]
+fn overrides_are_parsed_and_validated() {
+    let mut config = SimulationConfig::default();
+    config.validate().unwrap();
+    config.set("liquidity_fraction", "0.01").unwrap();
+    config.set("network_name", "ZUX-Lab").unwrap();
+    assert_eq!(config.liquidity_fraction, 0.01);
+    assert_eq!(config.network_name, "ZUX-Lab");
+    assert!(config.set("wallet_count", "many").is_err());
+    assert!(config.set("gas_price", "1").is_err());
+
+    // The pool opens at the target price
+    let (zux, usdz) = config.pool_deposit(Amount::from_tokens(1_000_000)).unwrap();
+    assert_eq!(zux, Amount::from_tokens(10_000));
+    assert_eq!(usdz, Amount::from_tokens(100));
+
+    let invalid = [
+        ("fee_percent", "0.333"),
+        ("fee_percent", "100"),
+        ("liquidity_fraction", "0"),
+        ("target_price", "-1"),
+        ("wallet_count", "0"),
+        ("total_transactions", "0"),
+        ("usdz_credit", "10000000"), // 1000 wallets need more than the USDZ supply
+    ];
+    for (key, value) in invalid {
+        let mut config = SimulationConfig::default();
+        config.set(key, value).unwrap();
+        assert!(config.validate().is_err(), "{} = {} should be rejected", key, value);
+    }
+}
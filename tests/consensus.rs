@@ -0,0 +1,164 @@
+//! Seals of each consensus engine, and the blocks the ledger refuses for a
+//! wrong producer, a bad or missing signature, or a seal of another engine
+
+mod common;
+
+use std::collections::HashMap;
+use std::sync::atomic::AtomicBool;
+
+use common::{genesis, START};
+use ed25519_dalek::SigningKey;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use zux::block::Block;
+use zux::consensus::{Authority, EngineKind, ProofOfAuthority, ProofOfStake, Sealer};
+use zux::explorer::build_explorer_data;
+use zux::simulation::genesis_state_from;
+use zux::transaction::create_wallet_registration;
+use zux::wallet::{create_authority_wallet, create_system_wallet};
+use zux::{Amount, BlockchainError, ConsensusRule, Miner, RetargetRule, SimulationConfig, State, Transaction, TxKind, UniqueCodeGenerator, Wallet,
+          SYSTEM_WALLET_ADDRESS};
+
+/// Block on top of `state` executing `transactions`, produced by `producer`, not sealed yet
+fn header(state: &State, producer: &str, transactions: &[Transaction], timestamp: u64) -> Block {
+    let diff = state.preview(transactions, timestamp, producer).unwrap();
+    Block::new(
+        state.height() + 1, state.tip_hash(), transactions, state.chain_id(), "1.0.0.0.0", 2025, producer,
+        &diff.receipts, &diff.state_root, state.next_target(), timestamp
+    ).unwrap()
+}
+
+/// Seal `block` under the chain's engine, signing with `key` if it is a signed one
+fn seal(state: &State, mut block: Block, key: Option<&SigningKey>) -> Block {
+    let sealer = Sealer { miner: &Miner::new(1), cancel: &AtomicBool::new(false), key };
+    state.consensus().engine().seal(&mut block, &sealer).unwrap().unwrap();
+    block
+}
+
+fn key(wallet: &Wallet) -> SigningKey {
+    wallet.get_signing_key().unwrap()
+}
+
+fn authority(wallet: &Wallet) -> Authority {
+    Authority { address: wallet.address.clone(), public_key: wallet.public_key.clone() }
+}
+
+/// Expect the seal of `block` to be refused with an error mentioning `reason`, by the engine and by the ledger
+fn assert_refused(state: &State, block: &Block, reason: &str) {
+    let error = state.consensus().engine().verify_seal(block, state).unwrap_err();
+    assert!(error.to_string().contains(reason), "{}", error);
+    assert!(state.clone().apply_block(block).is_err());
+}
+
+#[test]
+fn engine_kinds_parse_from_their_names() {
+    for kind in [EngineKind::ProofOfWork, EngineKind::ProofOfAuthority, EngineKind::ProofOfStake] {
+        assert_eq!(kind.to_string().parse::<EngineKind>().unwrap(), kind);
+    }
+    assert!(matches!("pbft".parse::<EngineKind>(), Err(BlockchainError::Config(_))));
+}
+
+#[test]
+fn proof_of_authority_only_accepts_the_scheduled_authority_signature() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let authorities: Vec<Wallet> = (1..=3).map(|index| create_authority_wallet(index, &mut rng)).collect();
+    let rule = ProofOfAuthority { authorities: authorities.iter().map(authority).collect() };
+    let state = State::new("ZUX-Testnet", &[], &[], 30, Default::default(), Default::default(), ConsensusRule::ProofOfAuthority(rule));
+
+    // Block #1 is the turn of the authority at index 1
+    let scheduled = &authorities[1];
+    assert_eq!(state.consensus().engine().scheduled_producer(&state).unwrap(), Some(scheduled.address.clone()));
+    let block = seal(&state, header(&state, &scheduled.address, &[], START), Some(&key(scheduled)));
+    state.consensus().engine().verify_seal(&block, &state).unwrap();
+
+    let other = &authorities[2];
+    let out_of_turn = seal(&state, header(&state, &other.address, &[], START), Some(&key(other)));
+    assert_refused(&state, &out_of_turn, "was scheduled");
+
+    let wrong_key = seal(&state, header(&state, &scheduled.address, &[], START), Some(&key(other)));
+    assert_refused(&state, &wrong_key, "signature verification failed");
+
+    let mut tampered = block.clone();
+    tampered.signature[0] ^= 1;
+    assert_refused(&state, &tampered, "signature verification failed");
+
+    // Unsigned, whether or not it carries proof of work
+    let mut unsigned = block.clone();
+    unsigned.signature.clear();
+    assert_refused(&state, &unsigned, "no valid signature");
+    let mined = Miner::new(1);
+    let mut mined_block = header(&state, &scheduled.address, &[], START);
+    mined.mine(&mut mined_block, &AtomicBool::new(false)).unwrap().unwrap();
+    assert_refused(&state, &mined_block, "no valid signature");
+
+    let mut state = state;
+    state.apply_block(&block).unwrap();
+    assert_eq!(state.height(), 1);
+
+    // The explorer shows the engine and the producer's signature
+    let data = build_explorer_data(std::slice::from_ref(&block), &state, &HashMap::new(), 0, 0, START).unwrap();
+    assert_eq!(data.consensus, EngineKind::ProofOfAuthority);
+    assert_eq!(data.blocks[0].signature, hex::encode(&block.signature));
+}
+
+#[test]
+fn proof_of_work_refuses_signed_or_unmined_blocks() {
+    let (state, _, _) = genesis();
+    let block = seal(&state, header(&state, SYSTEM_WALLET_ADDRESS, &[], START), None);
+    state.consensus().engine().verify_seal(&block, &state).unwrap();
+
+    // A valid proof of work with a signature added
+    let mut signed = block.clone();
+    signed.signature = vec![7; 64];
+    assert_refused(&state, &signed, "carries a signature");
+
+    // A header hash that misses the target
+    let mut unmined = header(&state, SYSTEM_WALLET_ADDRESS, &[], START);
+    unmined.hash = unmined.calculate_hash();
+    while block.target.is_met_by(&hex::decode(&unmined.hash).unwrap().try_into().unwrap()) {
+        unmined.extra_nonce += 1;
+        unmined.hash = unmined.calculate_hash();
+    }
+    assert_refused(&state, &unmined, "does not meet its target");
+
+    let mut garbled = block.clone();
+    garbled.hash = "not a hash".to_string();
+    assert_refused(&state, &garbled, "not 32 bytes of hex");
+}
+
+#[test]
+fn proof_of_stake_follows_the_draw_from_the_bootstrap_authority_to_the_stakers() {
+    let mut rng = StdRng::seed_from_u64(3);
+    let mut code_generator = UniqueCodeGenerator::new(&mut rng).unwrap();
+    let bootstrap = create_authority_wallet(1, &mut rng);
+    let mut system = create_system_wallet(&mut code_generator, &mut rng).unwrap();
+    let rule = ConsensusRule::ProofOfStake(ProofOfStake { bootstrap: authority(&bootstrap) });
+    let mut state = genesis_state_from(&SimulationConfig::default(), rule, RetargetRule::default(), &system.public_key);
+
+    // Nobody holds ZUX: the bootstrap authority produces genesis, and the block registering the system wallet
+    let genesis = seal(&state, header(&state, &bootstrap.address, &[], START), Some(&key(&bootstrap)));
+    state.apply_block(&genesis).unwrap();
+    let registrations = [
+        create_wallet_registration(&system, state.chain_id(), START + 1).unwrap(),
+        create_wallet_registration(&bootstrap, state.chain_id(), START + 1).unwrap(),
+    ];
+    let block = seal(&state, header(&state, &bootstrap.address, &registrations, START + 1), Some(&key(&bootstrap)));
+    state.apply_block(&block).unwrap();
+    system.nonce += 1;
+
+    // The system wallet now holds all the stake, so it is drawn for every block
+    let kind = TxKind::Transfer { recipient: bootstrap.address.clone(), currency: "ZUX".to_string(), amount: Amount::from_tokens(1) };
+    let transfer = [Transaction::signed(&system, state.chain_id(), Amount::ZERO, kind, START + 2).unwrap()];
+    assert_eq!(state.consensus().engine().scheduled_producer(&state).unwrap(), Some(SYSTEM_WALLET_ADDRESS.to_string()));
+    let by_bootstrap = seal(&state, header(&state, &bootstrap.address, &transfer, START + 2), Some(&key(&bootstrap)));
+    assert_refused(&state, &by_bootstrap, "was scheduled");
+    let wrong_key = seal(&state, header(&state, SYSTEM_WALLET_ADDRESS, &transfer, START + 2), Some(&key(&bootstrap)));
+    assert_refused(&state, &wrong_key, "signature verification failed");
+    let mut unsigned = seal(&state, header(&state, SYSTEM_WALLET_ADDRESS, &transfer, START + 2), Some(&key(&system)));
+    unsigned.signature.clear();
+    assert_refused(&state, &unsigned, "no valid signature");
+
+    let block = seal(&state, header(&state, SYSTEM_WALLET_ADDRESS, &transfer, START + 2), Some(&key(&system)));
+    state.apply_block(&block).unwrap();
+    assert_eq!(state.height(), 3);
+}
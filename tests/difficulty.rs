@@ -0,0 +1,97 @@
+//! Target arithmetic, retargeting within its bounds and the hash rate estimate
+
+mod common;
+
+use common::START;
+use zux::difficulty::{estimate_hash_rate, Difficulty};
+use zux::{RetargetRule, Target};
+
+/// Target whose last bytes read `value`, all others zero
+fn target(value: u64) -> Target {
+    let mut bytes = [0u8; 32];
+    bytes[24..].copy_from_slice(&value.to_be_bytes());
+    Target::from_bytes(bytes)
+}
+
+/// Rule expecting windows of 10 blocks one second apart, so 10 seconds each
+fn rule() -> RetargetRule {
+    RetargetRule {
+        target_block_time_ms: 1_000,
+        retarget_interval: 10,
+        max_adjustment: 4,
+        pow_limit: Target::from_leading_zero_bits(8),
+    }
+}
+
+#[test]
+fn scaling_a_target_rounds_down_saturates_and_never_reaches_zero() {
+    assert_eq!(target(1_000).scaled(3, 2), target(1_500));
+    assert_eq!(target(1_000).scaled(1, 3), target(333));
+    assert_eq!(target(7).scaled(0, 5), target(1));
+    assert_eq!(target(7).scaled(1, 0), target(7));
+
+    // Carries cross the 64-bit limbs both ways
+    let high = target(u64::MAX).scaled(2, 1);
+    assert_eq!(high.as_bytes()[23], 1);
+    assert_eq!(high.scaled(1, 2), target(u64::MAX));
+
+    let bits = Target::from_leading_zero_bits(20);
+    assert_eq!(bits.scaled(2, 1).scaled(1, 2), bits);
+    assert_eq!(bits.scaled(1, 2), Target::from_leading_zero_bits(21));
+    assert_eq!(Target::from_leading_zero_bits(1).scaled(4, 1), Target::MAX);
+    assert_eq!(Target::MAX.scaled(1, 1), Target::MAX);
+}
+
+#[test]
+fn retargeting_follows_the_window_time_within_the_clamp_and_the_pow_limit() {
+    let rule = rule();
+    let current = Target::from_leading_zero_bits(16);
+
+    // On time keeps the target; twice as fast halves it, twice as slow doubles it
+    assert_eq!(rule.retarget(&current, 10), current);
+    assert_eq!(rule.retarget(&current, 5), current.scaled(1, 2));
+    assert_eq!(rule.retarget(&current, 20), current.scaled(2, 1));
+
+    // Beyond `max_adjustment` either way, the change is clamped
+    assert_eq!(rule.retarget(&current, 1), current.scaled(1, 4));
+    assert_eq!(rule.retarget(&current, 0), current.scaled(1, 4));
+    assert_eq!(rule.retarget(&current, 1_000), current.scaled(4, 1));
+
+    // Never easier than the proof-of-work limit
+    let near_limit = Target::from_leading_zero_bits(9);
+    assert_eq!(rule.retarget(&near_limit, 40), rule.pow_limit);
+    assert_eq!(rule.retarget(&rule.pow_limit, 1_000), rule.pow_limit);
+}
+
+#[test]
+fn the_target_only_changes_at_the_end_of_each_window() {
+    let rule = rule();
+    let retarget_heights: Vec<u64> = (0..=31).filter(|&height| rule.is_retarget_height(height)).collect();
+    assert_eq!(retarget_heights, [11, 21, 31]);
+
+    // Genesis opens the first window at the pow limit; blocks then come twice as fast as expected
+    let mut difficulty = Difficulty::initial(&rule).after_block(&rule, 1, START);
+    assert_eq!(difficulty, Difficulty { target: rule.pow_limit, window_start: START });
+    for height in 2..=10 {
+        difficulty = difficulty.after_block(&rule, height, START + height);
+    }
+    assert_eq!(difficulty.target, rule.pow_limit);
+
+    difficulty = difficulty.after_block(&rule, 11, START + 5);
+    assert_eq!(difficulty, Difficulty { target: rule.pow_limit.scaled(1, 2), window_start: START + 5 });
+}
+
+#[test]
+fn the_hash_rate_is_the_work_after_the_first_block_over_the_time_taken() {
+    assert_eq!(estimate_hash_rate(&[]), 0.0);
+    assert_eq!(estimate_hash_rate(&[(START, Target::MAX)]), 0.0);
+
+    // Each block at 8 leading zero bits takes 256 hashes; the first one's work is not counted
+    let easy = Target::from_leading_zero_bits(8);
+    assert_eq!(easy.work(), 256.0);
+    let blocks = [(START, Target::from_leading_zero_bits(30)), (START + 2, easy), (START + 4, easy), (START + 4, easy)];
+    assert_eq!(estimate_hash_rate(&blocks), 3.0 * 256.0 / 4.0);
+
+    // Blocks in the same second count as one second
+    assert_eq!(estimate_hash_rate(&[(START, easy), (START, easy)]), 256.0);
+}
@@ -0,0 +1,196 @@
+//! Fork choice and reorganizations of the block tree, on deliberately forked chains
+
+mod common;
+
+use common::{branch, genesis, started_chain, START};
+use zux::block::Block;
+use zux::consensus::{Authority, ProofOfStake};
+use zux::simulation::genesis_state_from;
+use zux::{BlockTree, BlockchainError, ConsensusRule, Imported, RetargetRule, SimulationConfig, Target};
+
+fn import_all(tree: &mut BlockTree, blocks: &[Block]) -> Vec<Imported> {
+    blocks.iter().map(|block| tree.import(block.clone()).unwrap()).collect()
+}
+
+#[test]
+fn blocks_on_the_head_extend_the_canonical_chain() {
+    let (base, _, mut code_generator) = started_chain();
+    let mut tree = BlockTree::new(base.clone());
+    let mut expected = base;
+    let blocks = branch(&mut expected, &mut code_generator, 3);
+
+    for imported in import_all(&mut tree, &blocks) {
+        assert!(matches!(imported, Imported::Extended(_)));
+    }
+    assert_eq!(tree.height(), expected.height());
+    assert_eq!(tree.head_hash(), blocks[2].hash);
+    assert_eq!(tree.state().state_root(), expected.state_root());
+    assert!(matches!(tree.import(blocks[1].clone()).unwrap(), Imported::Known));
+    assert_eq!(tree.canonical_blocks().map(|block| block.hash.as_str()).collect::<Vec<_>>(),
+        blocks.iter().map(|block| block.hash.as_str()).collect::<Vec<_>>());
+}
+
+#[test]
+fn a_lighter_side_branch_leaves_the_head_alone() {
+    let (base, _, mut code_generator) = started_chain();
+    let mut tree = BlockTree::new(base.clone());
+    let mut main = base.clone();
+    let main_blocks = branch(&mut main, &mut code_generator, 3);
+    let mut side = base;
+    let side_blocks = branch(&mut side, &mut code_generator, 2);
+
+    import_all(&mut tree, &main_blocks);
+    for imported in import_all(&mut tree, &side_blocks) {
+        assert!(matches!(imported, Imported::SideChain));
+    }
+    assert_eq!(tree.head_hash(), main_blocks[2].hash);
+    assert_eq!(tree.state().state_root(), main.state_root());
+    assert_eq!(tree.len(), 5);
+    assert!(!tree.is_canonical(&side_blocks[0].hash));
+    assert!(tree.block(&side_blocks[1].hash).is_some());
+}
+
+#[test]
+fn a_heavier_branch_reorganizes_the_state() {
+    let (base, _, mut code_generator) = started_chain();
+    let mut tree = BlockTree::new(base.clone());
+    let mut main = base.clone();
+    let main_blocks = branch(&mut main, &mut code_generator, 2);
+
+    // Fork after the first main block and outgrow the main chain
+    let mut fork = base;
+    fork.apply_block(&main_blocks[0]).unwrap();
+    let fork_blocks = branch(&mut fork, &mut code_generator, 2);
+
+    import_all(&mut tree, &main_blocks);
+    assert!(matches!(tree.import(fork_blocks[0].clone()).unwrap(), Imported::SideChain));
+    match tree.import(fork_blocks[1].clone()).unwrap() {
+        Imported::Reorganized { reverted, applied } => {
+            assert_eq!(reverted.iter().map(|diff| &diff.block_hash).collect::<Vec<_>>(), [&main_blocks[1].hash]);
+            assert_eq!(applied.iter().map(|diff| &diff.block_hash).collect::<Vec<_>>(), [&fork_blocks[0].hash, &fork_blocks[1].hash]);
+        },
+        other => panic!("expected a reorganization, got {:?}", other),
+    }
+
+    assert_eq!(tree.head_hash(), fork_blocks[1].hash);
+    assert_eq!(tree.height(), fork.height());
+    assert_eq!(tree.state().state_root(), fork_blocks[1].state_root);
+    assert_eq!(tree.state().accounts(), fork.accounts());
+    assert!(tree.is_canonical(&main_blocks[0].hash));
+    assert!(!tree.is_canonical(&main_blocks[1].hash));
+
+    // The old branch wins back once it is the heaviest again
+    let old_tip = main_blocks.last().unwrap();
+    let revived = branch(&mut main, &mut code_generator, 2);
+    assert!(matches!(tree.import(revived[0].clone()).unwrap(), Imported::SideChain));
+    assert!(matches!(tree.import(revived[1].clone()).unwrap(), Imported::Reorganized { .. }));
+    assert!(tree.is_canonical(&old_tip.hash));
+    assert_eq!(tree.state().state_root(), main.state_root());
+}
+
+#[test]
+fn equal_weight_keeps_the_first_seen_head() {
+    let (base, _, mut code_generator) = started_chain();
+    let mut tree = BlockTree::new(base.clone());
+    let mut first = base.clone();
+    let first_blocks = branch(&mut first, &mut code_generator, 2);
+    let mut second = base;
+    let second_blocks = branch(&mut second, &mut code_generator, 2);
+
+    import_all(&mut tree, &first_blocks);
+    import_all(&mut tree, &second_blocks);
+    assert_eq!(tree.head_weight(), first_blocks.iter().map(Block::difficulty).sum::<f64>());
+    assert_eq!(tree.head_hash(), first_blocks[1].hash);
+    assert_eq!(tree.state().state_root(), first.state_root());
+}
+
+#[test]
+fn an_invalid_block_on_a_heavier_branch_restores_the_previous_chain() {
+    let (base, _, mut code_generator) = started_chain();
+    let mut tree = BlockTree::new(base.clone());
+    let mut main = base.clone();
+    let main_blocks = branch(&mut main, &mut code_generator, 2);
+    let mut fork = base;
+    let mut fork_blocks = branch(&mut fork, &mut code_generator, 3);
+
+    // A well-mined block whose state root does not match its execution
+    let forged = &mut fork_blocks[2];
+    forged.state_root = "ab".repeat(32);
+    forged.mine().unwrap();
+
+    import_all(&mut tree, &main_blocks);
+    import_all(&mut tree, &fork_blocks[..2]);
+    let error = tree.import(fork_blocks[2].clone()).unwrap_err();
+    assert!(matches!(error, BlockchainError::Block(_)), "{}", error);
+
+    assert_eq!(tree.head_hash(), main_blocks[1].hash);
+    assert_eq!(tree.state().state_root(), main.state_root());
+    assert_eq!(tree.state().accounts(), main.accounts());
+
+    // Nothing built on the forged block is accepted
+    let mut child = fork_blocks[2].clone();
+    child.id += 1;
+    child.parent_hash = fork_blocks[2].hash.clone();
+    child.mine().unwrap();
+    assert!(tree.import(child).is_err());
+
+    // A copy of a valid block claiming the invalid block as parent does not get the valid one refused
+    let next = branch(&mut main, &mut code_generator, 1).remove(0);
+    let mut impostor = next.clone();
+    impostor.parent_hash = fork_blocks[2].hash.clone();
+    assert!(tree.import(impostor).is_err());
+    assert!(matches!(tree.import(next).unwrap(), Imported::Extended(_)));
+}
+
+#[test]
+fn unsealed_blocks_are_refused_before_they_weigh_on_a_branch() {
+    let (base, _, mut code_generator) = started_chain();
+    let mut tree = BlockTree::new(base.clone());
+    let mut main = base.clone();
+    import_all(&mut tree, &branch(&mut main, &mut code_generator, 1));
+    let mut side = base;
+    let side_block = branch(&mut side, &mut code_generator, 1).remove(0);
+
+    // Claiming a far harder target than the main chain's would outweigh it, had the block been mined
+    let mut unmined = side_block.clone();
+    unmined.target = Target::from_leading_zero_bits(64);
+    unmined.hash = unmined.calculate_hash();
+    let error = tree.import(unmined).unwrap_err();
+    assert!(error.to_string().contains("does not meet its target"), "{}", error);
+
+    let mut signed = side_block.clone();
+    signed.signature = vec![7; 64];
+    let error = tree.import(signed).unwrap_err();
+    assert!(error.to_string().contains("carries a signature"), "{}", error);
+    assert_eq!(tree.len(), 1);
+
+    // Under a signed engine, a block without a signature is refused even on a side branch
+    let (_, system, _) = genesis();
+    let bootstrap = Authority { address: system.address.clone(), public_key: system.public_key.clone() };
+    let rule = ConsensusRule::ProofOfStake(ProofOfStake { bootstrap });
+    let staked = genesis_state_from(&SimulationConfig::default(), rule, RetargetRule::default(), &system.public_key);
+    let diff = staked.preview(&[], START, &system.address).unwrap();
+    let mut unsigned = Block::new(
+        1, staked.tip_hash(), &[], staked.chain_id(), "1.0.0.0.0", 2025, &system.address,
+        &diff.receipts, &diff.state_root, staked.next_target(), START
+    ).unwrap();
+    unsigned.hash = unsigned.calculate_hash();
+    let error = BlockTree::new(staked).import(unsigned).unwrap_err();
+    assert!(error.to_string().contains("no valid signature"), "{}", error);
+
+    // The genuine block is still accepted
+    assert!(matches!(tree.import(side_block).unwrap(), Imported::SideChain));
+}
+
+#[test]
+fn blocks_with_an_unknown_parent_are_rejected() {
+    let (base, _, mut code_generator) = started_chain();
+    let mut tree = BlockTree::new(base.clone());
+    let mut ahead = base;
+    let blocks = branch(&mut ahead, &mut code_generator, 2);
+
+    let error = tree.import(blocks[1].clone()).unwrap_err();
+    assert!(error.to_string().contains("unknown"), "{}", error);
+    assert!(tree.is_empty());
+    assert_eq!(tree.height(), 2);
+}
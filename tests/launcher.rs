@@ -0,0 +1,19 @@
+//! Launch modes of the companion binaries
+
+use zux::launcher::Launched;
+use zux::{BlockchainError, Companion, LaunchMode, Launcher};
+
+#[test]
+fn launch_modes_parse_from_their_names() {
+    for mode in [LaunchMode::Auto, LaunchMode::Tmux, LaunchMode::Terminal, LaunchMode::Headless] {
+        assert_eq!(mode.to_string().parse::<LaunchMode>().unwrap(), mode);
+    }
+    assert!(matches!("window".parse::<LaunchMode>(), Err(BlockchainError::Config(_))));
+}
+
+#[test]
+fn headless_runs_open_no_companions() {
+    let launcher = Launcher::new(LaunchMode::Headless);
+    assert_eq!(launcher.launch(Companion::PriceMonitor), Launched::Skipped);
+    assert_eq!(launcher.launch(Companion::BlockchainExplorer), Launched::Skipped);
+}
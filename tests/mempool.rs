@@ -0,0 +1,152 @@
+//! Mempool admission, replacement, eviction and block packing
+
+mod common;
+
+use common::{extend, funded_chain, START};
+use zux::{codec, Amount, BlockLimits, BlockchainError, Mempool, MempoolConfig, State, Transaction, TxKind, Wallet, SYSTEM_WALLET_ADDRESS};
+
+/// A transfer of `tokens` ZUX to the system wallet from `wallet`, offering `fee` ZUX, with the wallet's next nonce
+fn pay(wallet: &mut Wallet, state: &State, tokens: u64, fee: u64) -> Transaction {
+    let kind = TxKind::Transfer { recipient: SYSTEM_WALLET_ADDRESS.to_string(), currency: "ZUX".to_string(), amount: Amount::from_tokens(tokens) };
+    let transaction = Transaction::signed(wallet, state.chain_id(), Amount::from_tokens(fee), kind, START).unwrap();
+    wallet.nonce += 1;
+    transaction
+}
+
+#[test]
+fn replayed_and_foreign_transactions_are_refused_as_replays() {
+    let (mut state, _, mut users, _) = funded_chain(1, 100);
+    let user = &mut users[0];
+    let mut mempool = Mempool::new(MempoolConfig::default());
+
+    // Included in a block, then submitted again
+    let included = pay(user, &state, 1, 0);
+    extend(&mut state, std::slice::from_ref(&included), START + 1);
+    assert!(matches!(mempool.submit(included, &state, START + 1), Err(BlockchainError::Replay(_))));
+
+    // Signed with the next nonce, but for another network
+    let foreign = Transaction::signed(user, "ZUX-Mainnet", Amount::ZERO, TxKind::Transfer {
+        recipient: SYSTEM_WALLET_ADDRESS.to_string(), currency: "ZUX".to_string(), amount: Amount::from_tokens(1)
+    }, START + 1).unwrap();
+    assert!(matches!(mempool.submit(foreign, &state, START + 1), Err(BlockchainError::Replay(_))));
+    assert!(mempool.is_empty());
+
+    // The same transaction for this network is accepted
+    mempool.submit(pay(user, &state, 1, 0), &state, START + 1).unwrap();
+    assert_eq!(mempool.len(), 1);
+}
+
+#[test]
+fn nonces_must_follow_the_ledger_and_the_pending_ones() {
+    let (state, _, mut users, _) = funded_chain(1, 100);
+    let user = &mut users[0];
+    let mut mempool = Mempool::new(MempoolConfig::default());
+
+    // Skipping ahead of the ledger's next nonce leaves a gap
+    user.nonce += 1;
+    let ahead = pay(user, &state, 1, 0);
+    assert!(matches!(mempool.submit(ahead.clone(), &state, START), Err(BlockchainError::Mempool(_))));
+
+    // Once the missing nonce is pending, the next one fits
+    user.nonce -= 2;
+    mempool.submit(pay(user, &state, 1, 0), &state, START).unwrap();
+    assert_eq!(mempool.next_nonce(&user.address, &state), state.next_nonce(&user.address) + 1);
+    mempool.submit(ahead, &state, START).unwrap();
+    assert_eq!(mempool.next_nonce(&user.address, &state), state.next_nonce(&user.address) + 2);
+}
+
+#[test]
+fn a_pending_nonce_is_only_replaced_by_a_higher_fee() {
+    let (state, _, mut users, _) = funded_chain(1, 100);
+    let user = &mut users[0];
+    let mut mempool = Mempool::new(MempoolConfig::default());
+    let original = mempool.submit(pay(user, &state, 1, 2), &state, START).unwrap();
+
+    user.nonce -= 1;
+    let same_fee = pay(user, &state, 2, 2);
+    assert!(matches!(mempool.submit(same_fee, &state, START), Err(BlockchainError::Mempool(_))));
+    user.nonce -= 1;
+    let replacement = mempool.submit(pay(user, &state, 2, 3), &state, START).unwrap();
+
+    assert_eq!(mempool.len(), 1);
+    assert!(!mempool.contains(&original));
+    assert_eq!(mempool.get(&replacement).unwrap().fee, Amount::from_tokens(3));
+}
+
+#[test]
+fn senders_are_limited_and_a_full_pool_evicts_the_lowest_fee_it_is_outbid_by() {
+    let (state, _, mut users, _) = funded_chain(3, 100);
+    let config = MempoolConfig { capacity: 3, max_per_sender: 2, ..MempoolConfig::default() };
+    let mut mempool = Mempool::new(config);
+
+    // The first sender's cheap transaction is the lowest fee, but its second one depends on it
+    let cheap = mempool.submit(pay(&mut users[0], &state, 1, 1), &state, START).unwrap();
+    let dependent = mempool.submit(pay(&mut users[0], &state, 1, 4), &state, START).unwrap();
+    let third = pay(&mut users[0], &state, 1, 9);
+    assert!(matches!(mempool.submit(third, &state, START), Err(BlockchainError::Mempool(_))));
+    let middle = mempool.submit(pay(&mut users[1], &state, 1, 3), &state, START).unwrap();
+
+    // Full: an incoming fee must outbid the lowest transaction nothing depends on
+    let outbid = pay(&mut users[2], &state, 1, 2);
+    assert!(matches!(mempool.submit(outbid, &state, START), Err(BlockchainError::Mempool(_))));
+    users[2].nonce -= 1;
+    let incoming = mempool.submit(pay(&mut users[2], &state, 1, 5), &state, START).unwrap();
+
+    assert_eq!(mempool.len(), 3);
+    assert!(!mempool.contains(&middle));
+    for hash in [&cheap, &dependent, &incoming] {
+        assert!(mempool.contains(hash));
+    }
+}
+
+#[test]
+fn packing_takes_the_highest_fees_first_within_the_block_limits() {
+    let (mut state, _, mut users, _) = funded_chain(3, 100);
+    let mut mempool = Mempool::new(MempoolConfig::default());
+    let submit = |mempool: &mut Mempool, transaction: Transaction| {
+        mempool.submit(transaction.clone(), &state, START).unwrap();
+        transaction
+    };
+    let low = submit(&mut mempool, pay(&mut users[0], &state, 1, 1));
+    let after_low = submit(&mut mempool, pay(&mut users[0], &state, 1, 5));
+    let high = submit(&mut mempool, pay(&mut users[1], &state, 1, 3));
+    let middle = submit(&mut mempool, pay(&mut users[2], &state, 1, 2));
+
+    // A sender's later nonce waits for its earlier one, whatever it pays
+    let all = BlockLimits { max_transactions: 10, ..BlockLimits::default() };
+    let packed = mempool.pack(&state, &all, START + 1, SYSTEM_WALLET_ADDRESS).unwrap();
+    assert_eq!(packed.transactions, [high.clone(), middle.clone(), low, after_low]);
+    assert!(packed.dropped.is_empty());
+    assert_eq!(mempool.len(), 4);
+
+    let two = BlockLimits { max_transactions: 2, ..BlockLimits::default() };
+    assert_eq!(mempool.pack(&state, &two, START + 1, SYSTEM_WALLET_ADDRESS).unwrap().transactions, [high.clone(), middle.clone()]);
+    let bytes = codec::to_bytes(&high).len() + codec::to_bytes(&middle).len();
+    let small = BlockLimits { max_transactions: 10, max_bytes: bytes };
+    let packed = mempool.pack(&state, &small, START + 1, SYSTEM_WALLET_ADDRESS).unwrap();
+    assert_eq!(packed.transactions, [high, middle]);
+
+    // Applying the block prunes what it included
+    extend(&mut state, &packed.transactions, START + 1);
+    assert_eq!(mempool.prune(&state, START + 1), 2);
+    assert_eq!(mempool.len(), 2);
+}
+
+#[test]
+fn packing_drops_a_failing_transaction_with_its_later_nonces() {
+    let (state, _, mut users, _) = funded_chain(2, 100);
+    let mut mempool = Mempool::new(MempoolConfig::default());
+
+    // Each fits the balance on its own, not both
+    mempool.submit(pay(&mut users[0], &state, 60, 0), &state, START).unwrap();
+    let overdraft = mempool.submit(pay(&mut users[0], &state, 60, 0), &state, START).unwrap();
+    let later = mempool.submit(pay(&mut users[0], &state, 1, 0), &state, START).unwrap();
+    mempool.submit(pay(&mut users[1], &state, 1, 0), &state, START).unwrap();
+
+    let packed = mempool.pack(&state, &BlockLimits::default(), START + 1, SYSTEM_WALLET_ADDRESS).unwrap();
+    assert_eq!(packed.transactions.len(), 2);
+    assert_eq!(packed.dropped.len(), 1);
+    assert_eq!(packed.dropped[0].0, overdraft);
+    assert!(!mempool.contains(&overdraft) && !mempool.contains(&later));
+    assert_eq!(mempool.len(), 2);
+}
@@ -0,0 +1,61 @@
+//! The miner rolls the extra nonce when its nonce range runs out, and stops when cancelled
+
+mod common;
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use common::START;
+use zux::block::Block;
+use zux::{Miner, Target, SYSTEM_WALLET_ADDRESS};
+
+/// Empty block after genesis carrying `target`
+fn block(target: Target) -> Block {
+    Block::new(2, &"0".repeat(64), &[], "ZUX-Testnet", "1.0.0.0.0", 2025, SYSTEM_WALLET_ADDRESS, &[], &"0".repeat(64), target, START)
+        .unwrap()
+}
+
+#[test]
+fn an_exhausted_nonce_range_rolls_the_extra_nonce() {
+    // One nonce per extra nonce, against a target met once in 256 hashes
+    let miner = Miner { threads: 2, nonce_range: 1 };
+    let mut block = block(Target::from_leading_zero_bits(8));
+    let stats = miner.mine(&mut block, &AtomicBool::new(false)).unwrap().expect("mining was not cancelled");
+
+    assert!(stats.extra_nonces > 0);
+    assert_eq!(block.nonce, 0);
+    assert_eq!(block.extra_nonce, stats.extra_nonces);
+    assert_eq!(stats.hashes, stats.extra_nonces + 1);
+    block.verify().unwrap();
+    assert!(block.target.is_met_by(&hex::decode(&block.hash).unwrap().try_into().unwrap()));
+
+    // The same search from the same header finds the same seal
+    let mut again = self::block(block.target);
+    miner.mine(&mut again, &AtomicBool::new(false)).unwrap().unwrap();
+    assert_eq!((again.extra_nonce, again.hash), (block.extra_nonce, block.hash));
+}
+
+#[test]
+fn a_cancelled_miner_leaves_the_block_unmined() {
+    let miner = Miner { threads: 2, nonce_range: 1 << 16 };
+    let unreachable = Target::from_leading_zero_bits(200);
+
+    let mut block = block(unreachable);
+    assert_eq!(miner.mine(&mut block, &AtomicBool::new(true)).unwrap(), None);
+    assert_eq!((block.nonce, block.extra_nonce), (0, 0));
+    assert!(block.verify().is_err());
+
+    // Raised while the workers are searching, across extra nonce rolls
+    let cancel = AtomicBool::new(false);
+    let mut block = self::block(unreachable);
+    let mined = thread::scope(|scope| {
+        scope.spawn(|| {
+            thread::sleep(Duration::from_millis(50));
+            cancel.store(true, Ordering::Relaxed);
+        });
+        miner.mine(&mut block, &cancel).unwrap()
+    });
+    assert_eq!(mined, None);
+    assert!(block.verify().is_err());
+}
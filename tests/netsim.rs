@@ -0,0 +1,153 @@
+//! Consensus scenarios on the in-process network simulator, timed by its virtual clock
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use zux::mempool::MempoolConfig;
+use zux::simulation::genesis_state;
+use zux::transaction::create_wallet_registration;
+use zux::wallet::{create_system_wallet, create_wallet_without_balance};
+use zux::{ConsensusRule, LinkConfig, NetworkEvent, Node, SimConfig, SimNetwork, UniqueCodeGenerator, Wallet};
+
+/// `count` unconnected nodes on the same genesis ledger, binding the system account to `system_wallet`
+fn network(count: usize, config: SimConfig, system_wallet: &Wallet) -> SimNetwork {
+    let genesis = genesis_state(ConsensusRule::default(), &system_wallet.public_key);
+    let nodes = (0..count)
+        .map(|index| Node::new(&format!("node{}", index), genesis.clone(), MempoolConfig::default()))
+        .collect();
+    SimNetwork::new(nodes, config)
+}
+
+/// Produce the genesis block and register the system wallet on node 0, and wait for every node to follow
+fn start(network: &mut SimNetwork, system_wallet: &Wallet) {
+    network.produce_block(0).unwrap().unwrap();
+    let registration = create_wallet_registration(system_wallet, network.node(0).state().chain_id(), network.timestamp()).unwrap();
+    network.submit_transaction(0, registration).unwrap();
+    network.produce_block(0).unwrap().unwrap();
+    assert!(network.run_until_agreed(60_000).unwrap());
+}
+
+/// Submit a fresh wallet's registration to `node`, returning its hash
+fn register(network: &mut SimNetwork, node: usize, code_generator: &mut UniqueCodeGenerator, rng: &mut StdRng) -> String {
+    let wallet = create_wallet_without_balance(code_generator, rng).unwrap();
+    let registration = create_wallet_registration(&wallet, network.node(node).state().chain_id(), network.timestamp()).unwrap();
+    let hash = registration.hash();
+    network.submit_transaction(node, registration).unwrap();
+    hash
+}
+
+#[test]
+fn blocks_travel_hop_by_hop_at_the_link_latency() {
+    let link = LinkConfig { latency_ms: 100, ..LinkConfig::default() };
+    let system_wallet = create_system_wallet(&mut UniqueCodeGenerator::new(&mut rand::thread_rng()).unwrap(), &mut rand::thread_rng()).unwrap();
+    let mut network = network(3, SimConfig { link, ..SimConfig::default() }, &system_wallet);
+    network.connect(0, 1);
+    network.connect(1, 2);
+    network.run_until(500).unwrap();
+    assert_eq!(network.node(1).peer_ids(), ["node0", "node2"]);
+
+    // Each hop is an announcement, a request and the block itself
+    let genesis = network.produce_block(0).unwrap().unwrap();
+    network.run_until(1_099).unwrap();
+    assert_eq!(network.node(1).block_seen_at(&genesis.hash), Some(800));
+    assert_eq!(network.node(2).tree().height(), 0);
+    network.run_until(1_100).unwrap();
+    assert_eq!(network.node(2).block_seen_at(&genesis.hash), Some(1_100));
+    assert!(network.heads_agree());
+}
+
+#[test]
+fn a_healed_partition_reorganizes_the_lighter_side() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let mut code_generator = UniqueCodeGenerator::new(&mut rng).unwrap();
+    let system_wallet = create_system_wallet(&mut code_generator, &mut rng).unwrap();
+    let mut network = network(4, SimConfig::default(), &system_wallet);
+    network.connect_all();
+    start(&mut network, &system_wallet);
+
+    network.partition(&[vec![0, 1], vec![2, 3]]);
+    let lost = register(&mut network, 0, &mut code_generator, &mut rng);
+    network.run_for(1_000).unwrap();
+    let light = network.produce_block(0).unwrap().unwrap();
+    let mut heavy = Vec::new();
+    for _ in 0..2 {
+        register(&mut network, 2, &mut code_generator, &mut rng);
+        network.run_for(1_000).unwrap();
+        heavy.push(network.produce_block(2).unwrap().unwrap());
+    }
+    network.run_for(2_000).unwrap();
+    assert_eq!(network.node(1).tree().head_hash(), light.hash);
+    assert_eq!(network.node(3).tree().head_hash(), heavy[1].hash);
+    assert!(network.stats().partitioned > 0);
+
+    network.heal();
+    assert!(network.run_until_agreed(30_000).unwrap());
+    assert_eq!(network.node(0).tree().head_hash(), heavy[1].hash);
+    for node in &network.nodes()[..2] {
+        assert_eq!(node.stats().reorgs, 1);
+        assert_eq!(node.stats().deepest_reorg, 1);
+        assert!(!node.tree().is_canonical(&light.hash));
+    }
+
+    // The undone registration goes back to the mempool and into the next block
+    assert!(network.node(0).mempool().contains(&lost));
+    let next = network.produce_block(0).unwrap().unwrap();
+    assert!(next.transactions.iter().any(|transaction| transaction.hash() == lost));
+    assert!(network.run_until_agreed(30_000).unwrap());
+}
+
+#[test]
+fn lost_messages_only_delay_agreement() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let mut code_generator = UniqueCodeGenerator::new(&mut rng).unwrap();
+    let link = LinkConfig { latency_ms: 40, jitter_ms: 30, loss: 0.2 };
+    let system_wallet = create_system_wallet(&mut code_generator, &mut rng).unwrap();
+    let mut network = network(3, SimConfig { seed: 7, link, ..SimConfig::default() }, &system_wallet);
+    network.connect_all();
+    start(&mut network, &system_wallet);
+
+    for round in 0..6 {
+        let node = round % 3;
+        register(&mut network, node, &mut code_generator, &mut rng);
+        network.produce_block(node).unwrap().unwrap();
+        network.run_for(1_500).unwrap();
+    }
+
+    // Nodes left on branches of equal weight keep them until one gets heavier
+    network.run_for(30_000).unwrap();
+    register(&mut network, 0, &mut code_generator, &mut rng);
+    network.produce_block(0).unwrap().unwrap();
+    assert!(network.run_until_agreed(120_000).unwrap());
+    assert!(network.stats().lost > 0);
+    assert!(network.node(0).tree().height() >= 4);
+}
+
+#[test]
+fn the_same_seed_replays_the_same_run() {
+    let run = |seed| {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut code_generator = UniqueCodeGenerator::new(&mut rng).unwrap();
+        let link = LinkConfig { latency_ms: 30, jitter_ms: 200, loss: 0.05 };
+        let system_wallet = create_system_wallet(&mut code_generator, &mut rng).unwrap();
+        let mut network = network(4, SimConfig { seed, link, ..SimConfig::default() }, &system_wallet);
+        network.connect_all();
+        start(&mut network, &system_wallet);
+
+        // A scripted partition with the larger side producing more, then a heal
+        let at = network.now();
+        network.schedule(at, NetworkEvent::Partition(vec![vec![0], vec![1, 2, 3]]));
+        network.schedule(at + 5_000, NetworkEvent::Heal);
+        for step in 0..4 {
+            network.run_until(at + step * 1_000).unwrap();
+            for node in if step % 2 == 0 { vec![0, 3] } else { vec![3] } {
+                register(&mut network, node, &mut code_generator, &mut rng);
+                network.schedule(at + step * 1_000 + 500, NetworkEvent::Produce(node));
+            }
+        }
+        network.run_until(at + 5_000).unwrap();
+        assert!(network.run_until_agreed(60_000).unwrap());
+
+        let stats: Vec<_> = network.nodes().iter().map(|node| (node.tree().height(), node.stats().clone())).collect();
+        (network.now(), network.stats().clone(), stats, network.node(0).tree().head_hash().to_string())
+    };
+    assert_eq!(run(11), run(11));
+}
@@ -0,0 +1,196 @@
+//! Nodes driven by hand: the handshake, chain sync by locator, orphan blocks
+//! and the mempool after a reorganization
+
+mod common;
+
+use std::collections::VecDeque;
+
+use common::{branch, extend, registration, started_chain, START};
+use zux::block::Block;
+use zux::p2p::{Outbox, PeerId, PROTOCOL_VERSION};
+use zux::{MempoolConfig, Message, Node, State};
+
+const NOW: u64 = START * 1_000;
+
+/// Deliver the messages `from` sent until none are left; in every node, peer `n` is node `n`
+fn deliver(nodes: &mut [Node], from: usize, outbox: Outbox) {
+    let mut queue: VecDeque<(usize, PeerId, Message)> = outbox.into_iter().map(|(to, message)| (from, to, message)).collect();
+    while let Some((from, to, message)) = queue.pop_front() {
+        let to = to as usize;
+        let replies = nodes[to].handle(from as PeerId, message, NOW).unwrap();
+        queue.extend(replies.into_iter().map(|(peer, message)| (to, peer, message)));
+    }
+}
+
+/// Open a connection between nodes `a` and `b`, whose greetings cross, and deliver what follows
+fn connect(nodes: &mut [Node], a: usize, b: usize) {
+    let [(_, from_a)] = <[_; 1]>::try_from(nodes[a].connect(b as PeerId, NOW)).unwrap();
+    let [(_, from_b)] = <[_; 1]>::try_from(nodes[b].connect(a as PeerId, NOW)).unwrap();
+    let replies_of_b = nodes[b].handle(a as PeerId, from_a, NOW).unwrap();
+    let replies_of_a = nodes[a].handle(b as PeerId, from_b, NOW).unwrap();
+    deliver(nodes, b, replies_of_b);
+    deliver(nodes, a, replies_of_a);
+}
+
+/// Greeting of a peer whose chain starts from and ends at `base`
+fn hello(base: &State) -> Message {
+    Message::Hello {
+        version: PROTOCOL_VERSION,
+        node_id: "peer".to_string(),
+        chain_id: base.chain_id().to_string(),
+        base_root: base.state_root(),
+        height: base.height(),
+        head_hash: base.tip_hash().to_string(),
+    }
+}
+
+#[test]
+fn the_handshake_refuses_other_versions_chains_and_ledgers() {
+    let (base, _, mut code_generator) = started_chain();
+    let mut node = Node::new("node", base.clone(), MempoolConfig::default());
+    assert!(node.handle(9, hello(&base), NOW).is_err());
+
+    let refused = |change: &dyn Fn(&mut Message)| {
+        let mut node = Node::new("node", base.clone(), MempoolConfig::default());
+        node.connect(1, NOW);
+        let mut greeting = hello(&base);
+        change(&mut greeting);
+        node.handle(1, greeting, NOW).unwrap_err().to_string()
+    };
+    let version = refused(&|hello| if let Message::Hello { version, .. } = hello { *version += 1 });
+    assert!(version.contains("protocol version"), "{}", version);
+    let chain = refused(&|hello| if let Message::Hello { chain_id, .. } = hello { *chain_id = "ZUX-Mainnet".to_string() });
+    assert!(chain.contains("another chain"), "{}", chain);
+    let ledger = refused(&|hello| if let Message::Hello { base_root, .. } = hello { *base_root = "ab".repeat(32) });
+    assert!(ledger.contains("another chain"), "{}", ledger);
+
+    // Nothing but a greeting is heard from a peer before its own
+    node.connect(1, NOW);
+    let mut ahead = base.clone();
+    let blocks = branch(&mut ahead, &mut code_generator, 1);
+    assert!(node.handle(1, Message::Block(Box::new(blocks[0].clone())), NOW).unwrap().is_empty());
+    assert_eq!(node.tree().height(), base.height());
+    assert!(node.peer_ids().is_empty());
+
+    // A peer ahead of this node is asked for its blocks as soon as it greets
+    let mut greeting = hello(&base);
+    if let Message::Hello { height, head_hash, .. } = &mut greeting {
+        *height += 1;
+        *head_hash = blocks[0].hash.clone();
+    }
+    let outbox = node.handle(1, greeting, NOW).unwrap();
+    assert_eq!(outbox, [(1, Message::GetBlocks { locator: node.tree().locator() })]);
+    assert_eq!(node.peer_ids(), ["peer"]);
+}
+
+#[test]
+fn a_locator_is_answered_with_the_blocks_after_the_last_shared_one() {
+    let (base, _, mut code_generator) = started_chain();
+    let mut state = base.clone();
+    let blocks = branch(&mut state, &mut code_generator, 5);
+    let mut nodes = vec![
+        Node::new("a", base.clone(), MempoolConfig::default()),
+        Node::new("b", base.clone(), MempoolConfig::default()),
+    ];
+    for block in &blocks {
+        nodes[0].submit_block(block.clone(), NOW);
+    }
+    let hashes: Vec<String> = blocks.iter().map(|block| block.hash.clone()).collect();
+
+    nodes[0].connect(1, NOW);
+    nodes[0].handle(1, hello(&base), NOW).unwrap();
+    let mut ask = |locator: &[&str]| {
+        let locator = locator.iter().map(|hash| hash.to_string()).collect();
+        nodes[0].handle(1, Message::GetBlocks { locator }, NOW).unwrap()
+    };
+    let inventory = |blocks: &[String]| vec![(1, Message::Inventory { blocks: blocks.to_vec(), transactions: Vec::new() })];
+
+    // The newest known hash of the locator decides, unknown ones are skipped
+    assert_eq!(ask(&["unknown", &hashes[2], &hashes[0]]), inventory(&hashes[3..]));
+    assert_eq!(ask(&["unknown"]), inventory(&hashes));
+    assert_eq!(ask(&[]), inventory(&hashes));
+    assert!(ask(&[&hashes[4]]).is_empty());
+
+    // A fresh peer syncs the whole chain through the handshake
+    nodes[0].disconnect(1);
+    connect(&mut nodes, 0, 1);
+    assert_eq!(nodes[1].tree().height(), nodes[0].tree().height());
+    assert_eq!(nodes[1].tree().head_hash(), hashes[4]);
+    assert_eq!(nodes[1].state().state_root(), state.state_root());
+}
+
+#[test]
+fn orphans_wait_for_their_parent_up_to_a_limit() {
+    let (base, _, mut code_generator) = started_chain();
+    let mut state = base.clone();
+    let blocks = branch(&mut state, &mut code_generator, 3);
+    let mut node = Node::new("node", base.clone(), MempoolConfig::default());
+    node.connect(1, NOW);
+    node.handle(1, hello(&base), NOW).unwrap();
+    let send = |node: &mut Node, block: &Block| node.handle(1, Message::Block(Box::new(block.clone())), NOW).unwrap();
+
+    // Arriving before their parent, blocks are kept and the sender is asked to sync
+    assert_eq!(send(&mut node, &blocks[2]), [(1, Message::GetBlocks { locator: node.tree().locator() })]);
+    send(&mut node, &blocks[1]);
+    assert_eq!(node.orphan_count(), 2);
+    assert_eq!(node.stats().orphan_blocks, 2);
+    assert!(send(&mut node, &blocks[1]).is_empty());
+    assert_eq!(node.stats().duplicate_blocks, 1);
+
+    // The parent connects the whole chain of orphans
+    send(&mut node, &blocks[0]);
+    assert_eq!(node.orphan_count(), 0);
+    assert_eq!(node.tree().head_hash(), blocks[2].hash);
+
+    // Unconnectable blocks never hold more than the limit
+    let orphans: Vec<Block> = (0..300u64)
+        .map(|index| {
+            let mut orphan = blocks[0].clone();
+            orphan.parent_hash = format!("{:064x}", index + 1);
+            orphan.id = 100 + index;
+            orphan.hash = orphan.calculate_hash();
+            orphan
+        })
+        .collect();
+    for orphan in &orphans {
+        send(&mut node, orphan);
+    }
+    assert_eq!(node.orphan_count(), 256);
+    assert_eq!(node.stats().orphan_blocks, 2 + 300);
+
+    // The lowest are kept, being the closest to connecting
+    let kept = node.handle(1, Message::GetData { blocks: vec![orphans[0].hash.clone()], transactions: Vec::new() }, NOW).unwrap();
+    assert_eq!(kept, [(1, Message::Block(Box::new(orphans[0].clone())))]);
+}
+
+#[test]
+fn a_reorganization_returns_the_undone_transactions_to_the_mempool() {
+    let (base, _, mut code_generator) = started_chain();
+    let mut main = base.clone();
+    let undone = registration(&main, &mut code_generator, START + 2);
+    let main_block = extend(&mut main, std::slice::from_ref(&undone), START + 2);
+    let mut side = base.clone();
+    let side_blocks = branch(&mut side, &mut code_generator, 2);
+
+    let mut nodes = vec![
+        Node::new("a", base.clone(), MempoolConfig::default()),
+        Node::new("b", base, MempoolConfig::default()),
+    ];
+    connect(&mut nodes, 0, 1);
+    let outbox = nodes[0].submit_block(main_block.clone(), NOW);
+    deliver(&mut nodes, 0, outbox);
+    assert_eq!(nodes[1].tree().head_hash(), main_block.hash);
+    assert!(nodes[0].mempool().is_empty());
+
+    // A heavier branch without the transaction arrives from the peer
+    for block in &side_blocks {
+        let outbox = nodes[1].submit_block(block.clone(), NOW);
+        deliver(&mut nodes, 1, outbox);
+    }
+    for node in &nodes {
+        assert_eq!(node.tree().head_hash(), side_blocks[1].hash);
+        assert_eq!(node.stats().reorgs, 1);
+        assert_eq!(node.mempool().len(), 1);
+        assert!(node.mempool().contains(&undone.hash()));
+    }
+}
@@ -0,0 +1,76 @@
+//! Transaction and balance proofs verify, and forged ones do not
+
+mod common;
+
+use common::funded_chain;
+use zux::{Amount, BalanceProof};
+
+#[test]
+fn transaction_proofs_follow_the_path_of_their_index() {
+    // Five transactions and five receipts: ten leaves, with promoted nodes on the way up
+    let (_, _, _, block) = funded_chain(5, 10);
+    for index in 0..block.transactions.len() {
+        let proof = block.transaction_proof(index).unwrap();
+        proof.verify_transaction(&block.transactions[index], &block.tx_root).unwrap();
+    }
+    assert!(block.transaction_proof(block.transactions.len()).is_err());
+
+    // A tampered sibling leads to another root
+    let mut tampered = block.transaction_proof(2).unwrap();
+    tampered.steps[0].sibling = "00".repeat(32);
+    assert!(tampered.verify(&block.tx_root).is_err());
+
+    // The right path claimed for another index, or with a step missing or added, is rejected
+    let mut moved = block.transaction_proof(2).unwrap();
+    moved.tx_index = 3;
+    assert!(moved.verify(&block.tx_root).is_err());
+    let mut short = block.transaction_proof(2).unwrap();
+    short.steps.pop();
+    assert!(short.verify(&block.tx_root).is_err());
+    let mut long = block.transaction_proof(2).unwrap();
+    long.steps.push(long.steps[0].clone());
+    assert!(long.verify(&block.tx_root).is_err());
+    let mut outside = block.transaction_proof(2).unwrap();
+    outside.tx_index = outside.leaf_count;
+    assert!(outside.verify(&block.tx_root).is_err());
+}
+
+#[test]
+fn balance_proofs_verify_against_the_tip_state_root() {
+    let (state, _, users, _) = funded_chain(3, 10);
+    let proof = state.balance_proof(&users[1].address).unwrap();
+    proof.verify(&state.state_root()).unwrap();
+    assert_eq!(proof.balance("ZUX"), Amount::from_tokens(10));
+    assert_eq!(proof.balance("USDZ"), Amount::ZERO);
+    assert!(state.balance_proof("NOBODY").is_err());
+}
+
+#[test]
+fn forged_balance_proofs_are_rejected() {
+    let (state, _, users, _) = funded_chain(3, 10);
+    let root = state.state_root();
+    let proof = state.balance_proof(&users[0].address).unwrap();
+    let zux = proof.balances.iter().position(|(currency, _)| currency == "ZUX").unwrap();
+
+    let forge = |change: &dyn Fn(&mut BalanceProof)| {
+        let mut forged = proof.clone();
+        change(&mut forged);
+        forged
+    };
+    let inflated = forge(&|forged| forged.balances[zux].1 = Amount::from_tokens(1_000));
+    let other_account = forge(&|forged| forged.address = users[1].address.clone());
+    let tampered_sibling = forge(&|forged| forged.siblings[0].hash = "11".repeat(32));
+    for forged in [inflated, other_account, tampered_sibling] {
+        assert!(forged.verify(&root).is_err());
+    }
+
+    // A second, larger ZUX entry ahead of the committed one would otherwise be what `balance` reports
+    let duplicated = forge(&|forged| forged.balances.insert(0, ("ZUX".to_string(), Amount::from_tokens(1_000))));
+    let error = duplicated.verify(&root).unwrap_err();
+    assert!(error.to_string().contains("more than once"), "{}", error);
+
+    let unknown = forge(&|forged| forged.balances.push(("BTC".to_string(), Amount::from_tokens(1))));
+    assert!(unknown.verify(&root).is_err());
+    let missing = forge(&|forged| { forged.balances.remove(zux); });
+    assert!(missing.verify(&root).is_err());
+}
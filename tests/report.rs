@@ -0,0 +1,63 @@
+//! Price path statistics, and the report a headless run writes
+
+use std::path::PathBuf;
+
+use zux::report::PriceStats;
+use zux::simulation::{run_simulation, SimulationOptions, REPORT_FILE};
+use zux::{RunReport, SimulationConfig};
+
+#[test]
+fn price_stats_measure_change_volatility_and_drawdown() {
+    let stats = PriceStats::from_path(&[1.0, 2.0, 1.0, 1.5]);
+    assert_eq!((stats.initial, stats.last, stats.min, stats.max), (1.0, 1.5, 1.0, 2.0));
+    assert!((stats.mean - 1.375).abs() < 1e-12);
+    assert!((stats.change_percent - 50.0).abs() < 1e-9);
+    assert!((stats.max_drawdown_percent - 50.0).abs() < 1e-9);
+
+    // Returns of +100%, -50% and +50% around their mean of 1/3
+    let expected = ((2.0f64 / 3.0).powi(2) + (5.0f64 / 6.0).powi(2) + (1.0f64 / 6.0).powi(2)) / 2.0;
+    assert!((stats.volatility_percent - expected.sqrt() * 100.0).abs() < 1e-9);
+
+    let flat = PriceStats::from_path(&[0.5; 10]);
+    assert_eq!((flat.volatility_percent, flat.max_drawdown_percent, flat.change_percent), (0.0, 0.0, 0.0));
+    assert_eq!(PriceStats::from_path(&[]), PriceStats::default());
+}
+
+/// Run a small seeded headless simulation in a fresh directory named after `name`, reporting to `report` if given
+fn simulate(name: &str, report: Option<&str>) -> (RunReport, PathBuf) {
+    let data_dir = std::env::temp_dir().join(format!("zux-report-{}-{}", std::process::id(), name));
+    let config = SimulationConfig { wallet_count: 6, total_transactions: 8, block_delay_ms: 0, ..SimulationConfig::default() };
+    let options = SimulationOptions {
+        data_dir: data_dir.clone(), seed: Some(1), config, headless: true, report: report.map(|file| data_dir.join(file)),
+        ..SimulationOptions::default()
+    };
+    (run_simulation(&options).unwrap(), data_dir)
+}
+
+#[test]
+fn a_headless_run_writes_its_report_as_json_by_default() {
+    let (report, data_dir) = simulate("json", None);
+    let json = std::fs::read_to_string(data_dir.join(REPORT_FILE)).unwrap();
+    assert_eq!(serde_json::from_str::<RunReport>(&json).unwrap(), report);
+    std::fs::remove_dir_all(data_dir).unwrap();
+}
+
+#[test]
+fn a_csv_report_has_a_header_and_one_row_of_as_many_columns() {
+    let (report, data_dir) = simulate("csv", Some("report.csv"));
+    assert!(!data_dir.join(REPORT_FILE).exists());
+    let csv = std::fs::read_to_string(data_dir.join("report.csv")).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines, [RunReport::csv_header(), report.csv_row()]);
+
+    // No field needs quoting, so every comma separates two columns
+    assert!(!csv.contains('"'));
+    let header: Vec<&str> = lines[0].split(',').collect();
+    let row: Vec<&str> = lines[1].split(',').collect();
+    assert_eq!(header.len(), row.len());
+    let column = |name: &str| row[header.iter().position(|column| *column == name).unwrap()];
+    assert_eq!(column("tip_hash"), report.tip_hash);
+    assert_eq!(column("swaps"), "8");
+    assert_eq!(column("seed"), "1");
+    std::fs::remove_dir_all(data_dir).unwrap();
+}
@@ -0,0 +1,137 @@
+//! Block rewards halve on schedule, network fees move from senders to the
+//! producer, and the pool keeps swap fees in the currency they were paid in
+
+mod common;
+
+use common::{extend, START};
+use zux::transaction::create_wallet_registration;
+use zux::wallet::{create_system_wallet, create_wallet_without_balance};
+use zux::{AmmPool, Amount, ConsensusRule, RetargetRule, RewardSchedule, State, Transaction, TxKind, UniqueCodeGenerator, Wallet,
+          SYSTEM_WALLET_ADDRESS};
+
+const FEE_BPS: u32 = 30;
+
+/// Sign a transaction of `kind` from `wallet` offering `fee` ZUX, and advance its nonce
+fn sign(wallet: &mut Wallet, state: &State, fee: u64, kind: TxKind) -> Transaction {
+    let transaction = Transaction::signed(wallet, state.chain_id(), Amount::from_tokens(fee), kind, START).unwrap();
+    wallet.nonce += 1;
+    transaction
+}
+
+fn transfer(recipient: &str, currency: &str, tokens: u64) -> TxKind {
+    TxKind::Transfer { recipient: recipient.to_string(), currency: currency.to_string(), amount: Amount::from_tokens(tokens) }
+}
+
+/// Ledger rewarding `rewards`, with the system wallet producing every block and two registered users
+fn chain(rewards: RewardSchedule) -> (State, Wallet, Vec<Wallet>) {
+    let mut rng = rand::thread_rng();
+    let mut code_generator = UniqueCodeGenerator::new(&mut rng).unwrap();
+    let mut system = create_system_wallet(&mut code_generator, &mut rng).unwrap();
+    let allocations = [(SYSTEM_WALLET_ADDRESS, "ZUX", Amount::from_tokens(1_000_000)), (SYSTEM_WALLET_ADDRESS, "USDZ", Amount::from_tokens(1_000_000))];
+    let keys = [(SYSTEM_WALLET_ADDRESS, system.public_key.as_slice())];
+    let mut state = State::new("ZUX-Testnet", &allocations, &keys, FEE_BPS, rewards, RetargetRule::default(), ConsensusRule::default());
+    extend(&mut state, &[], START);
+
+    let mut users: Vec<Wallet> = (0..2).map(|_| create_wallet_without_balance(&mut code_generator, &mut rng).unwrap()).collect();
+    let registrations: Vec<Transaction> = std::iter::once(&system).chain(&users)
+        .map(|wallet| create_wallet_registration(wallet, state.chain_id(), START).unwrap())
+        .collect();
+    extend(&mut state, &registrations, START);
+    system.nonce += 1;
+    for user in &mut users {
+        user.nonce += 1;
+    }
+    (state, system, users)
+}
+
+#[test]
+fn the_reward_halves_every_interval_and_the_total_follows() {
+    let schedule = RewardSchedule::new(Amount::from_tokens(8), 2);
+    let rewards: Vec<Amount> = (0..=7).map(|height| schedule.reward_at(height)).collect();
+    let tokens = |tokens: u64| Amount::from_tokens(tokens);
+    assert_eq!(rewards, [Amount::ZERO, Amount::ZERO, tokens(8), tokens(8), tokens(4), tokens(4), tokens(2), tokens(2)]);
+
+    assert_eq!(schedule.total_issued(1).unwrap(), Amount::ZERO);
+    assert_eq!(schedule.total_issued(3).unwrap(), tokens(16));
+    assert_eq!(schedule.total_issued(6).unwrap(), tokens(26));
+
+    // The total is the sum of the rewards, including once they round down to nothing
+    let mut sum = Amount::ZERO;
+    for height in 0..=200 {
+        sum = sum.checked_add(schedule.reward_at(height)).unwrap();
+        assert_eq!(schedule.total_issued(height).unwrap(), sum, "height {}", height);
+    }
+    assert!(schedule.reward_at(200).is_zero());
+    assert_eq!(schedule.total_issued(u64::MAX).unwrap(), sum);
+
+    // Without halvings every block after genesis issues the same reward
+    let flat = RewardSchedule::new(tokens(8), 0);
+    assert_eq!(flat.reward_at(1_000_000), tokens(8));
+    assert_eq!(flat.total_issued(11).unwrap(), tokens(80));
+
+    let default = RewardSchedule::default();
+    assert_eq!(default.reward_at(10_001), tokens(50));
+    assert_eq!(default.reward_at(10_002), tokens(25));
+    assert_eq!(default.total_issued(10_001).unwrap(), tokens(500_000));
+}
+
+#[test]
+fn the_producer_earns_the_block_reward_and_the_fees_senders_pay() {
+    let (mut state, mut system, mut users) = chain(RewardSchedule::new(Amount::from_tokens(8), 2));
+    let funding = [
+        sign(&mut system, &state, 0, transfer(&users[0].address, "ZUX", 100)),
+        sign(&mut system, &state, 0, transfer(&users[1].address, "ZUX", 100)),
+    ];
+    extend(&mut state, &funding, START);
+
+    let producer_before = state.balance(SYSTEM_WALLET_ADDRESS, "ZUX");
+    let supply_before = state.total_supply("ZUX").unwrap();
+    let recipient = users[1].address.clone();
+    let transactions = [
+        sign(&mut users[0], &state, 2, transfer(&recipient, "ZUX", 10)),
+        sign(&mut users[0], &state, 3, transfer(&recipient, "ZUX", 5)),
+    ];
+    let block = extend(&mut state, &transactions, START + 1);
+
+    // Block #4 is in the second era of the schedule
+    assert_eq!(block.id, 4);
+    let reward = Amount::from_tokens(4);
+    assert_eq!(state.balance(&users[0].address, "ZUX"), Amount::from_tokens(100 - 10 - 2 - 5 - 3));
+    assert_eq!(state.balance(&recipient, "ZUX"), Amount::from_tokens(115));
+    let fees = Amount::from_tokens(5);
+    assert_eq!(state.balance(SYSTEM_WALLET_ADDRESS, "ZUX"), producer_before.checked_add(reward).unwrap().checked_add(fees).unwrap());
+
+    // Fees only move between accounts; the reward is the only new ZUX
+    assert_eq!(state.total_supply("ZUX").unwrap(), supply_before.checked_add(reward).unwrap());
+}
+
+#[test]
+fn swap_fees_are_kept_in_the_currency_they_were_paid_in() {
+    let (mut state, mut system, mut users) = chain(RewardSchedule::default());
+    let user = &mut users[0];
+    let setup = [
+        sign(&mut system, &state, 0, TxKind::AddLiquidity { zux_amount: Amount::from_tokens(1_000), usd_amount: Amount::from_tokens(2_000) }),
+        sign(&mut system, &state, 0, transfer(&user.address, "ZUX", 100)),
+        sign(&mut system, &state, 0, transfer(&user.address, "USDZ", 100)),
+    ];
+    extend(&mut state, &setup, START);
+    assert_eq!(state.amm_pool().fees_collected, Default::default());
+
+    let swap = |is_zux_to_usd, tokens| TxKind::Swap {
+        is_zux_to_usd, amount_in: Amount::from_tokens(tokens), min_out: Amount::ZERO, deadline: START + 60
+    };
+    let swaps = [sign(user, &state, 0, swap(true, 10)), sign(user, &state, 0, swap(false, 40))];
+    extend(&mut state, &swaps, START + 1);
+
+    let fees = state.amm_pool().fees_collected;
+    assert_eq!(fees.zux, AmmPool::swap_fee(Amount::from_tokens(10), FEE_BPS).unwrap());
+    assert_eq!(fees.usdz, AmmPool::swap_fee(Amount::from_tokens(40), FEE_BPS).unwrap());
+    assert!(!fees.zux.is_zero() && !fees.usdz.is_zero());
+
+    // Another swap only adds to the fees of its input currency
+    let more = [sign(user, &state, 0, swap(true, 20))];
+    extend(&mut state, &more, START + 2);
+    let after = state.amm_pool().fees_collected;
+    assert_eq!(after.usdz, fees.usdz);
+    assert_eq!(after.zux, fees.zux.checked_add(AmmPool::swap_fee(Amount::from_tokens(20), FEE_BPS).unwrap()).unwrap());
+}
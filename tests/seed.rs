@@ -0,0 +1,75 @@
+//! Runs drawn from a seeded generator replay exactly
+
+use std::collections::HashMap;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use zux::agent::create_intelligent_swap;
+use zux::simulation::{run_simulation, SimulationOptions};
+use zux::wallet::create_wallet;
+use zux::{AmmPool, Amount, RunReport, SimulationConfig, UniqueCodeGenerator, Wallet};
+
+// Address, public key, whale flag and manipulation intent of a wallet
+type Profile = (String, Vec<u8>, bool, i8);
+
+/// Addresses, keys and trading strategies of `count` funded wallets, then the hashes of `swaps` swaps among them
+fn run(seed: u64, count: usize, swaps: usize) -> (Vec<Profile>, Vec<String>) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut code_generator = UniqueCodeGenerator::new(&mut rng).unwrap();
+    let pool = AmmPool::new(Amount::from_tokens(100_000), Amount::from_tokens(1_000), 30, 1_700_000_000);
+    let mut wallets = HashMap::new();
+    for _ in 0..count {
+        let mut wallet: Wallet = create_wallet(&mut code_generator, Amount::from_tokens(500), &mut rng).unwrap();
+        wallet.initialize_trading_strategy(pool.get_zux_price(), &mut rng);
+        wallets.insert(wallet.address.clone(), wallet);
+    }
+
+    let mut profiles: Vec<_> = wallets.values()
+        .map(|wallet| {
+            let strategy = wallet.trading_strategy.as_ref().unwrap();
+            (wallet.address.clone(), wallet.public_key.clone(), strategy.whale_mode, strategy.manipulation_intent)
+        })
+        .collect();
+    profiles.sort();
+
+    let hashes = (0..swaps as u64)
+        .map(|second| {
+            let (address, _, _, transaction) = create_intelligent_swap(&mut wallets, &pool, "ZUX-Testnet", 1_700_000_000 + second, &mut rng).unwrap();
+            wallets.get_mut(&address).unwrap().nonce += 1;
+            transaction.hash()
+        })
+        .collect();
+    (profiles, hashes)
+}
+
+/// Report of a small headless run seeded with `seed`, kept in a directory of its own
+fn simulate(seed: u64, name: &str) -> RunReport {
+    let data_dir = std::env::temp_dir().join(format!("zux-seed-{}-{}", std::process::id(), name));
+    let config = SimulationConfig { wallet_count: 8, total_transactions: 12, block_delay_ms: 0, ..SimulationConfig::default() };
+    let options = SimulationOptions { data_dir: data_dir.clone(), seed: Some(seed), config, headless: true, ..SimulationOptions::default() };
+    let report = run_simulation(&options).unwrap();
+    std::fs::remove_dir_all(data_dir).unwrap();
+    report
+}
+
+#[test]
+fn the_same_seed_draws_the_same_wallets_and_swaps() {
+    let first = run(42, 20, 50);
+    assert_eq!(first, run(42, 20, 50));
+
+    let other = run(43, 20, 50);
+    assert_ne!(first.0, other.0);
+    assert_ne!(first.1, other.1);
+}
+
+#[test]
+fn the_same_seed_and_config_end_at_the_same_chain_tip() {
+    let first = simulate(7, "first");
+    let again = simulate(7, "again");
+    assert_eq!((first.height, &first.tip_hash), (again.height, &again.tip_hash));
+    assert_eq!(first.swaps, 12);
+    assert_eq!(first.pool, again.pool);
+
+    let other = simulate(8, "other");
+    assert_ne!(first.tip_hash, other.tip_hash);
+}
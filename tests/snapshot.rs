@@ -0,0 +1,88 @@
+//! Snapshot files round-trip, and damaged or mismatched ones are rejected
+
+mod common;
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use common::started_chain;
+use zux::{Amount, Snapshot, State, Wallet, SYSTEM_WALLET_ADDRESS};
+
+/// Snapshot of a chain with the system wallet registered
+fn snapshot() -> (Snapshot, State) {
+    let (state, system, code_generator) = started_chain();
+    let wallets: HashMap<String, Wallet> = [(system.address.clone(), system)].into_iter().collect();
+    (state.snapshot(&wallets, &code_generator), state)
+}
+
+/// Snapshot file of test `name`
+fn snapshot_file(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("zux-snapshot-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir.join(name)
+}
+
+/// Write `bytes` to a file and expect reading it back to fail with `reason`
+fn assert_unreadable(name: &str, bytes: &[u8], reason: &str) {
+    let path = snapshot_file(name);
+    std::fs::write(&path, bytes).unwrap();
+    let error = Snapshot::read_from(&path).err().expect("the snapshot should be rejected");
+    assert!(error.to_string().contains(reason), "{}", error);
+}
+
+#[test]
+fn a_written_snapshot_restores_the_same_ledger() {
+    let (snapshot, state) = snapshot();
+    let path = snapshot_file("round-trip.snapshot");
+    snapshot.write_to(&path).unwrap();
+
+    let read = Snapshot::read_from(&path).unwrap();
+    assert_eq!((read.height(), read.tip_hash()), (state.height(), state.tip_hash()));
+    let (restored, wallets, _) = read.restore().unwrap();
+    assert_eq!(restored.state_root(), state.state_root());
+    assert_eq!(restored.accounts(), state.accounts());
+    assert_eq!(restored.next_target(), state.next_target());
+    assert!(wallets.contains_key(SYSTEM_WALLET_ADDRESS));
+    assert!(!path.with_extension("tmp").exists());
+}
+
+#[test]
+fn damaged_snapshot_files_are_rejected() {
+    let (snapshot, _) = snapshot();
+    let path = snapshot_file("original.snapshot");
+    snapshot.write_to(&path).unwrap();
+    let bytes = std::fs::read(&path).unwrap();
+
+    let mut magic = bytes.clone();
+    magic[0] = b'X';
+    assert_unreadable("magic.snapshot", &magic, "not a ZUX snapshot");
+
+    let mut version = bytes.clone();
+    version[11] ^= 1;
+    assert_unreadable("version.snapshot", &version, "unsupported version");
+
+    let mut checksum = bytes.clone();
+    *checksum.last_mut().unwrap() ^= 1;
+    assert_unreadable("checksum.snapshot", &checksum, "checksum mismatch");
+
+    let mut payload = bytes.clone();
+    payload[30] ^= 1;
+    assert_unreadable("payload.snapshot", &payload, "checksum mismatch");
+
+    assert_unreadable("truncated.snapshot", &bytes[..bytes.len() - 1], "truncated");
+    assert_unreadable("header.snapshot", &bytes[..16], "not a ZUX snapshot");
+}
+
+#[test]
+fn a_ledger_that_does_not_match_its_state_root_is_rejected() {
+    let (mut snapshot, _) = snapshot();
+    let (_, account) = snapshot.ledger.accounts.iter_mut().find(|(address, _)| address == SYSTEM_WALLET_ADDRESS).unwrap();
+    account.balances.insert("ZUX".to_string(), Amount::from_tokens(1));
+    assert!(State::from_snapshot(&snapshot.ledger).is_err());
+
+    // A well-formed file does not make the ledger in it valid
+    let path = snapshot_file("mismatch.snapshot");
+    snapshot.write_to(&path).unwrap();
+    let error = Snapshot::read_from(&path).unwrap().restore().err().expect("the ledger should be rejected");
+    assert!(error.to_string().contains("does not match its state root"), "{}", error);
+}
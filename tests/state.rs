@@ -0,0 +1,190 @@
+//! Blocks the ledger rejects leave it exactly as it was, and signed
+//! transactions only execute once, in nonce order, on their own chain
+
+mod common;
+
+use common::{genesis, START};
+use zux::block::Block;
+use zux::transaction::create_wallet_registration;
+use zux::wallet::{create_system_wallet, create_wallet_without_balance};
+use zux::{Amount, BlockEvent, BlockchainError, ConsensusRule, Mempool, MempoolConfig, State, Transaction, TxKind, UniqueCodeGenerator, Wallet,
+          AMM_POOL_ADDRESS, SYSTEM_WALLET_ADDRESS};
+
+/// Mine a block carrying `transactions` on top of `state` with the receipts and root of executing them
+fn mine(state: &State, transactions: &[Transaction], timestamp: u64) -> Block {
+    let diff = state.preview(transactions, timestamp, SYSTEM_WALLET_ADDRESS).unwrap();
+    seal(state, transactions, &diff.receipts, &diff.state_root, timestamp)
+}
+
+/// Mine a block on top of `state` claiming `events` and `state_root`, whether or not they are right
+fn seal(state: &State, transactions: &[Transaction], events: &[BlockEvent], state_root: &str, timestamp: u64) -> Block {
+    let mut block = Block::new(
+        state.height() + 1, state.tip_hash(), transactions, state.chain_id(), "1.0.0.0.0", 2025, SYSTEM_WALLET_ADDRESS,
+        events, state_root, state.next_target(), timestamp
+    ).unwrap();
+    block.mine().unwrap();
+    block
+}
+
+/// Sign a transaction of `kind` from `wallet` and advance its nonce
+fn sign(wallet: &mut Wallet, chain_id: &str, kind: TxKind, timestamp: u64) -> Transaction {
+    let transaction = Transaction::signed(wallet, chain_id, Amount::ZERO, kind, timestamp).unwrap();
+    wallet.nonce += 1;
+    transaction
+}
+
+fn transfer(recipient: &str, tokens: u64) -> TxKind {
+    TxKind::Transfer { recipient: recipient.to_string(), currency: "ZUX".to_string(), amount: Amount::from_tokens(tokens) }
+}
+
+/// Ledger with the system wallet and one user holding 100 ZUX, and both wallets
+fn chain_with_user() -> (State, Wallet, Wallet, UniqueCodeGenerator) {
+    let (mut state, mut system, mut code_generator) = genesis();
+    let block = mine(&state, &[], START);
+    state.apply_block(&block).unwrap();
+
+    let mut user = create_wallet_without_balance(&mut code_generator, &mut rand::thread_rng()).unwrap();
+    let registrations = [
+        sign(&mut system, state.chain_id(), TxKind::WalletRegister, START + 1),
+        create_wallet_registration(&user, state.chain_id(), START + 1).unwrap(),
+    ];
+    user.nonce += 1;
+    let block = mine(&state, &registrations, START + 1);
+    state.apply_block(&block).unwrap();
+
+    let funding = sign(&mut system, state.chain_id(), transfer(&user.address, 100), START + 2);
+    let block = mine(&state, &[funding], START + 2);
+    state.apply_block(&block).unwrap();
+    assert_eq!(state.balance(&user.address, "ZUX"), Amount::from_tokens(100));
+    (state, system, user, code_generator)
+}
+
+/// Apply `block` expecting it to be rejected with an error mentioning `reason`, and check nothing changed
+fn assert_rejected(state: &mut State, block: &Block, reason: &str) {
+    let before = state.clone();
+    let error = state.apply_block(block).unwrap_err();
+    assert!(error.to_string().contains(reason), "{}", error);
+    assert_eq!(state.state_root(), before.state_root());
+    assert_eq!(state.accounts(), before.accounts());
+    assert_eq!((state.height(), state.tip_hash()), (before.height(), before.tip_hash()));
+    assert_eq!(state.amm_pool().zux_reserve, before.amm_pool().zux_reserve);
+}
+
+#[test]
+fn an_overdraft_block_is_rejected_without_changes() {
+    let (mut state, _, mut user, _) = chain_with_user();
+    let recipient = SYSTEM_WALLET_ADDRESS;
+
+    // The first transfer is fine on its own; the block fails on the second
+    let transactions = [
+        sign(&mut user, state.chain_id(), transfer(recipient, 60), START + 3),
+        sign(&mut user, state.chain_id(), transfer(recipient, 60), START + 3),
+    ];
+    assert!(state.preview(&transactions, START + 3, SYSTEM_WALLET_ADDRESS).is_err());
+    let block = seal(&state, &transactions, &[], &state.state_root(), START + 3);
+    assert_rejected(&mut state, &block, "Overdraft");
+    assert_eq!(state.balance(&user.address, "ZUX"), Amount::from_tokens(100));
+
+    // The block holding only the first one is accepted
+    let block = mine(&state, &transactions[..1], START + 3);
+    state.apply_block(&block).unwrap();
+    assert_eq!(state.balance(&user.address, "ZUX"), Amount::from_tokens(40));
+}
+
+#[test]
+fn a_block_with_a_bad_signature_is_rejected_without_changes() {
+    let (mut state, _, mut user, _) = chain_with_user();
+    let valid = sign(&mut user, state.chain_id(), transfer(SYSTEM_WALLET_ADDRESS, 10), START + 3);
+    let block = mine(&state, std::slice::from_ref(&valid), START + 3);
+
+    // Tamper with the signature, then with the signed amount
+    let mut forged = block.clone();
+    forged.transactions[0].signature[0] ^= 1;
+    forged.tx_root = Block::calculate_merkle_root(&forged.transactions, &forged.events);
+    forged.mine().unwrap();
+    assert_rejected(&mut state, &forged, "Signature verification failed");
+
+    let mut inflated = valid;
+    inflated.kind = transfer(SYSTEM_WALLET_ADDRESS, 90);
+    let forged = seal(&state, &[inflated], &block.events, &block.state_root, START + 3);
+    assert_rejected(&mut state, &forged, "Signature verification failed");
+
+    state.apply_block(&block).unwrap();
+    assert_eq!(state.balance(&user.address, "ZUX"), Amount::from_tokens(90));
+}
+
+#[test]
+fn a_block_from_an_unknown_sender_is_rejected_without_changes() {
+    let (mut state, _, _, mut code_generator) = chain_with_user();
+    let mut stranger = create_wallet_without_balance(&mut code_generator, &mut rand::thread_rng()).unwrap();
+    let transaction = sign(&mut stranger, state.chain_id(), transfer(SYSTEM_WALLET_ADDRESS, 1), START + 3);
+    let block = seal(&state, &[transaction], &[], &state.state_root(), START + 3);
+    assert_rejected(&mut state, &block, "Unknown sender");
+    assert!(state.account(&stranger.address).is_none());
+}
+
+#[test]
+fn reserved_accounts_are_only_registered_with_the_key_fixed_at_genesis() {
+    let mut rng = rand::thread_rng();
+    let (mut state, system, mut code_generator) = genesis();
+    let block = mine(&state, &[], START);
+    state.apply_block(&block).unwrap();
+
+    // Another key claiming the system account, and its allocation and privileges
+    let impostor = create_system_wallet(&mut UniqueCodeGenerator::new(&mut rng).unwrap(), &mut rng).unwrap();
+    let claim = create_wallet_registration(&impostor, state.chain_id(), START + 1).unwrap();
+    let block = seal(&state, std::slice::from_ref(&claim), &[], &state.state_root(), START + 1);
+    assert_rejected(&mut state, &block, "key fixed at genesis");
+    let refused = Mempool::new(MempoolConfig::default()).submit(claim, &state, START + 1).unwrap_err();
+    assert!(refused.to_string().contains("key fixed at genesis"), "{}", refused);
+
+    // The pool address, and an allocation no key was fixed for, can never be registered
+    let mut pool = create_wallet_without_balance(&mut code_generator, &mut rng).unwrap();
+    pool.address = AMM_POOL_ADDRESS.to_string();
+    let claim = create_wallet_registration(&pool, state.chain_id(), START + 1).unwrap();
+    let block = seal(&state, &[claim], &[], &state.state_root(), START + 1);
+    assert_rejected(&mut state, &block, "Reserved address");
+
+    let user = create_wallet_without_balance(&mut code_generator, &mut rng).unwrap();
+    let unbound = State::new("ZUX-Testnet", &[(&user.address, "ZUX", Amount::from_tokens(1))], &[], 30,
+                             Default::default(), Default::default(), ConsensusRule::default());
+    let error = unbound.preview(&[create_wallet_registration(&user, "ZUX-Testnet", START).unwrap()], START, SYSTEM_WALLET_ADDRESS).unwrap_err();
+    assert!(error.to_string().contains("Reserved address"), "{}", error);
+
+    // The genesis key registers the system account and releases its allocation
+    let registration = create_wallet_registration(&system, state.chain_id(), START + 1).unwrap();
+    let block = mine(&state, &[registration], START + 1);
+    state.apply_block(&block).unwrap();
+    assert!(!state.balance(SYSTEM_WALLET_ADDRESS, "ZUX").is_zero());
+}
+
+#[test]
+fn replayed_reordered_and_foreign_transactions_are_replays() {
+    let (mut state, _, mut user, _) = chain_with_user();
+    let chain_id = state.chain_id().to_string();
+    let first = sign(&mut user, &chain_id, transfer(SYSTEM_WALLET_ADDRESS, 1), START + 3);
+    let block = mine(&state, std::slice::from_ref(&first), START + 3);
+    state.apply_block(&block).unwrap();
+
+    // Included again in a later block
+    let replayed = seal(&state, std::slice::from_ref(&first), &block.events, &state.state_root(), START + 4);
+    let before = state.state_root();
+    assert!(matches!(state.apply_block(&replayed), Err(BlockchainError::Replay(_))));
+    assert_eq!(state.state_root(), before);
+
+    // Twice in one block, or behind the sender's next one
+    let second = sign(&mut user, &chain_id, transfer(SYSTEM_WALLET_ADDRESS, 1), START + 4);
+    let third = sign(&mut user, &chain_id, transfer(SYSTEM_WALLET_ADDRESS, 1), START + 4);
+    let preview = |transactions: &[Transaction]| state.preview(transactions, START + 4, SYSTEM_WALLET_ADDRESS);
+    assert!(matches!(preview(&[second.clone(), second.clone()]), Err(BlockchainError::Replay(_))));
+    assert!(matches!(preview(&[third.clone(), second.clone()]), Err(BlockchainError::Replay(_))));
+    assert!(matches!(preview(std::slice::from_ref(&third)), Err(BlockchainError::Replay(_))));
+    preview(&[second.clone(), third]).unwrap();
+
+    // Signed with the right nonce, but for another network
+    let mut elsewhere = user.clone();
+    elsewhere.nonce = state.next_nonce(&user.address);
+    let foreign = Transaction::signed(&elsewhere, "ZUX-Mainnet", Amount::ZERO, transfer(SYSTEM_WALLET_ADDRESS, 1), START + 4).unwrap();
+    assert_eq!(foreign.nonce, second.nonce);
+    assert!(matches!(preview(&[foreign]), Err(BlockchainError::Replay(_))));
+}
@@ -0,0 +1,193 @@
+//! File store recovery from torn appends, stores continuing from a base block,
+//! and full-chain verification of what a store holds
+
+mod common;
+
+use std::fs::OpenOptions;
+use std::path::{Path, PathBuf};
+
+use common::{branch, extend, genesis, START};
+use zux::block::Block;
+use zux::store::verify_chain;
+use zux::transaction::create_wallet_registration;
+use zux::{ChainStore, FileStore, State};
+
+// Bytes of one `blocks.idx` record
+const INDEX_RECORD_LEN: u64 = 8 + 4 + 32;
+
+/// `count` blocks from genesis, each after the first registering a wallet, and the state before them
+fn chain(count: usize) -> (State, Vec<Block>) {
+    let (base, system, mut code_generator) = genesis();
+    let mut state = base.clone();
+    let mut blocks = vec![extend(&mut state, &[], START)];
+    let registration = create_wallet_registration(&system, state.chain_id(), START + 1).unwrap();
+    blocks.push(extend(&mut state, &[registration], START + 1));
+    blocks.extend(branch(&mut state, &mut code_generator, count.saturating_sub(2)));
+    (base, blocks)
+}
+
+/// Fresh directory for the store of test `name`
+fn store_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("zux-store-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+fn file_len(path: &Path) -> u64 {
+    std::fs::metadata(path).unwrap().len()
+}
+
+fn truncate(path: &Path, len: u64) {
+    OpenOptions::new().write(true).open(path).unwrap().set_len(len).unwrap();
+}
+
+fn store_with(dir: &Path, blocks: &[Block]) -> FileStore {
+    let mut store = FileStore::create(dir).unwrap();
+    for block in blocks {
+        store.append(block).unwrap();
+    }
+    store
+}
+
+#[test]
+fn a_torn_log_record_is_dropped_on_reopen() {
+    let (_, blocks) = chain(4);
+    let dir = store_dir("torn-log");
+    drop(store_with(&dir, &blocks[..3]));
+    let three_blocks = file_len(&dir.join("blocks.log"));
+    drop(store_with(&dir, &blocks));
+
+    // The crash cut the last record short, after its index record was written
+    let log = dir.join("blocks.log");
+    truncate(&log, file_len(&log) - 10);
+
+    // A reader sees the complete blocks and leaves the files alone
+    let reader = FileStore::open_read_only(&dir).unwrap();
+    assert_eq!(reader.height(), 3);
+    assert_eq!(file_len(&dir.join("blocks.idx")), 4 * INDEX_RECORD_LEN);
+
+    let mut store = FileStore::open(&dir).unwrap();
+    assert_eq!(store.height(), 3);
+    assert_eq!(store.tip_hash(), blocks[2].hash);
+    assert_eq!(store.block_at(3).unwrap().as_ref(), Some(&blocks[2]));
+    assert_eq!(store.block_at(4).unwrap(), None);
+    assert_eq!(store.height_of(&blocks[3].hash), None);
+    assert_eq!(store.blocks_from(1).unwrap(), blocks[..3]);
+    assert_eq!(file_len(&log), three_blocks);
+    assert_eq!(file_len(&dir.join("blocks.idx")), 3 * INDEX_RECORD_LEN);
+
+    // The lost block can be appended again
+    store.append(&blocks[3]).unwrap();
+    drop(store);
+    let store = FileStore::open(&dir).unwrap();
+    assert_eq!(store.blocks_from(1).unwrap(), blocks);
+}
+
+#[test]
+fn a_missing_index_record_is_rebuilt_from_the_log() {
+    let (_, blocks) = chain(5);
+    let dir = store_dir("torn-index");
+    drop(store_with(&dir, &blocks));
+
+    // The crash wrote every log record but cut the index in the middle of the third record
+    let index = dir.join("blocks.idx");
+    truncate(&index, 2 * INDEX_RECORD_LEN + 20);
+
+    let store = FileStore::open(&dir).unwrap();
+    assert_eq!(store.height(), 5);
+    assert_eq!(store.tip_hash(), blocks[4].hash);
+    assert_eq!(store.height_of(&blocks[3].hash), Some(4));
+    assert_eq!(store.block_at(4).unwrap().as_ref(), Some(&blocks[3]));
+    assert_eq!(store.blocks_from(2).unwrap(), blocks[1..]);
+    assert_eq!(file_len(&index), 5 * INDEX_RECORD_LEN);
+
+    // A record torn in the log as well only loses that block
+    drop(store);
+    truncate(&index, INDEX_RECORD_LEN);
+    let log = dir.join("blocks.log");
+    truncate(&log, file_len(&log) - 1);
+    let store = FileStore::open(&dir).unwrap();
+    assert_eq!(store.height(), 4);
+    assert_eq!(store.blocks_from(1).unwrap(), blocks[..4]);
+}
+
+#[test]
+fn a_store_continues_from_its_base_block() {
+    let (_, blocks) = chain(5);
+    let dir = store_dir("base");
+    let mut store = FileStore::create_with_base(&dir, 2, &blocks[1].hash).unwrap();
+    assert_eq!((store.height(), store.tip_hash()), (2, blocks[1].hash.clone()));
+    assert!(store.append(&blocks[1]).is_err());
+    for block in &blocks[2..] {
+        store.append(block).unwrap();
+    }
+    drop(store);
+
+    // Torn after the base, the store keeps its base and the complete blocks
+    let log = dir.join("blocks.log");
+    truncate(&log, file_len(&log) - 3);
+    let store = FileStore::open(&dir).unwrap();
+    assert_eq!(store.base(), (2, blocks[1].hash.clone()));
+    assert_eq!(store.height(), 4);
+    assert_eq!(store.block_at(2).unwrap(), None);
+    assert_eq!(store.block_at(3).unwrap().as_ref(), Some(&blocks[2]));
+    assert_eq!(store.height_of(&blocks[3].hash), Some(4));
+    assert_eq!(store.blocks_from(0).unwrap(), blocks[2..4]);
+    drop(store);
+
+    // An empty store after the base reports the base as its tip
+    truncate(&log, 0);
+    truncate(&dir.join("blocks.idx"), 0);
+    let store = FileStore::open(&dir).unwrap();
+    assert_eq!((store.height(), store.tip_hash()), (2, blocks[1].hash.clone()));
+    assert!(store.blocks_from(0).unwrap().is_empty());
+    drop(store);
+
+    std::fs::write(dir.join("blocks.base"), [0u8; 12]).unwrap();
+    assert!(FileStore::open(&dir).is_err());
+}
+
+#[test]
+fn verification_fails_loudly_on_a_corrupted_block() {
+    let (base, blocks) = chain(4);
+    let dir = store_dir("verify");
+    let store = store_with(&dir, &blocks);
+    assert_eq!(verify_chain(&store, &mut base.clone()).unwrap(), 4);
+    drop(store);
+
+    // Store the chain with its last block replaced by `corrupted`, and verify it from genesis
+    let verify_with = |corrupted: Block| {
+        let mut store = store_with(&dir, &blocks[..3]);
+        store.append(&corrupted).unwrap();
+        drop(store);
+        let store = FileStore::open_read_only(&dir).unwrap();
+        verify_chain(&store, &mut base.clone()).unwrap_err().to_string()
+    };
+
+    // A header changed after mining no longer matches its hash
+    let mut stale_hash = blocks[3].clone();
+    stale_hash.timestamp += 1;
+    let error = verify_with(stale_hash);
+    assert!(error.contains("Chain verification failed at block #4"), "{}", error);
+
+    // A re-mined block whose transaction signature was tampered with
+    let mut bad_signature = blocks[3].clone();
+    bad_signature.transactions[0].signature[5] ^= 1;
+    bad_signature.tx_root = Block::calculate_merkle_root(&bad_signature.transactions, &bad_signature.events);
+    bad_signature.mine().unwrap();
+    let error = verify_with(bad_signature);
+    assert!(error.contains("block #4") && error.contains("Signature verification failed"), "{}", error);
+
+    // A re-mined block claiming another state root
+    let mut bad_state_root = blocks[3].clone();
+    bad_state_root.state_root = "ab".repeat(32);
+    bad_state_root.mine().unwrap();
+    let error = verify_with(bad_state_root);
+    assert!(error.contains("block #4") && error.contains("state root"), "{}", error);
+
+    // Verifying from anything but the store's base fails before any block is applied
+    let store = store_with(&dir, &blocks);
+    let mut ahead = base.clone();
+    ahead.apply_block(&blocks[0]).unwrap();
+    assert!(verify_chain(&store, &mut ahead).is_err());
+}
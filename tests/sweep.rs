@@ -0,0 +1,82 @@
+//! Sweep axes, the points they span, and the tables a seeded sweep writes
+
+use zux::sweep::run_sweep;
+use zux::{Axis, SimulationConfig, SweepOptions, SweepResult};
+
+#[test]
+fn axes_take_lists_and_inclusive_ranges() {
+    let list = Axis::parse("fee_percent", "0.05, 0.3,1").unwrap();
+    assert_eq!(list.values, ["0.05", "0.3", "1"]);
+
+    // Steps that do not land exactly on the stop still include it, without float noise
+    let range = Axis::parse("whale_probability", "0.1:0.5:0.1").unwrap();
+    assert_eq!(range.values, ["0.1", "0.2", "0.3", "0.4", "0.5"]);
+    assert_eq!(Axis::parse("wallet_count", "100:300:100").unwrap().values, ["100", "200", "300"]);
+
+    assert!(Axis::parse("target_price", "0.01,0.02").is_err()); // Not sweepable
+    assert!(Axis::parse("fee_percent", "1:0:0.1").is_err());
+    assert!(Axis::parse("fee_percent", "0:1:0").is_err());
+    assert!(Axis::parse("wallet_count", "10,,20").is_err());
+    assert!(Axis::parse("wallet_count", "10.5").is_err());
+}
+
+#[test]
+fn points_span_every_combination_and_are_validated() {
+    let options = SweepOptions {
+        axes: vec![
+            Axis::parse("fee_percent", "0.1,0.3").unwrap(),
+            Axis::parse("wallet_count", "50:150:50").unwrap(),
+        ],
+        ..SweepOptions::default()
+    };
+    let points = options.points().unwrap();
+    assert_eq!(points.len(), 6);
+    assert_eq!((points[0].fee_bps(), points[0].wallet_count), (10, 50));
+    assert_eq!((points[2].fee_bps(), points[2].wallet_count), (10, 150));
+    assert_eq!((points[5].fee_bps(), points[5].wallet_count), (30, 150));
+    assert!(points.iter().all(|point| point.total_transactions == SimulationConfig::default().total_transactions));
+
+    // Probabilities parse as numbers but must stay within [0, 1]
+    let invalid = SweepOptions {
+        axes: vec![Axis::parse("mega_whale_probability", "0.5:1.5:0.5").unwrap()],
+        ..SweepOptions::default()
+    };
+    assert!(invalid.points().is_err());
+}
+
+/// Sweep two fee levels over two seeds, two runs at a time, into a fresh directory named after `name`
+fn sweep(name: &str) -> (SweepResult, String, String) {
+    let out_dir = std::env::temp_dir().join(format!("zux-sweep-{}-{}", std::process::id(), name));
+    let options = SweepOptions {
+        base: SimulationConfig { wallet_count: 6, total_transactions: 8, block_delay_ms: 0, ..SimulationConfig::default() },
+        axes: vec![Axis::parse("fee_percent", "0.1,0.3").unwrap()],
+        seeds: vec![1, 2],
+        jobs: 2,
+        out_dir: out_dir.clone(),
+        ..SweepOptions::default()
+    };
+    let result = run_sweep(&options).unwrap();
+    let sweep_csv = std::fs::read_to_string(out_dir.join("sweep.csv")).unwrap();
+    let runs_csv = std::fs::read_to_string(out_dir.join("runs.csv")).unwrap();
+    std::fs::remove_dir_all(out_dir).unwrap();
+    (result, sweep_csv, runs_csv)
+}
+
+#[test]
+fn a_seeded_sweep_writes_a_row_per_point_and_per_run_and_repeats_itself() {
+    let (first, sweep_csv, runs_csv) = sweep("first");
+    assert_eq!(sweep_csv.lines().count(), 1 + 2); // Header and one row per point
+    assert_eq!(runs_csv.lines().count(), 1 + 2 * 2); // Header and one row per run
+    assert!(first.points.iter().all(|point| (point.runs, point.failed) == (2, 0)));
+    assert_eq!(first.reports.iter().map(|report| (report.config.fee_bps(), report.seed)).collect::<Vec<_>>(),
+               [(10, Some(1)), (10, Some(2)), (30, Some(1)), (30, Some(2))]);
+
+    // Only the wall time of a run may differ between reruns, and the point table leaves it out
+    let (again, again_sweep_csv, _) = sweep("again");
+    assert_eq!(again.points, first.points);
+    assert_eq!(again_sweep_csv, sweep_csv);
+    let outcome = |result: &SweepResult| -> Vec<(u64, String)> {
+        result.reports.iter().map(|report| (report.height, report.tip_hash.clone())).collect()
+    };
+    assert_eq!(outcome(&again), outcome(&first));
+}